@@ -4,6 +4,11 @@ include!("grpc.rs");
 
 use super::*;
 
+use futures::stream::Stream;
+#[cfg(feature = "stub_client")]
+use futures::stream::StreamExt;
+use std::pin::Pin;
+
 #[cfg(not(feature = "stub_client"))]
 use lib_common::grpc::ClientConnect;
 use lib_common::grpc::{Client, GrpcClient};
@@ -11,6 +16,25 @@ use rpc_service_client::RpcServiceClient;
 /// GrpcClient implementation of the RpcServiceClient
 pub type TelemetryClient = GrpcClient<RpcServiceClient<Channel>>;
 
+/// Stream of live telemetry events
+pub type TelemetryEventStream =
+    Pin<Box<dyn Stream<Item = Result<TelemetryEvent, tonic::Status>> + Send>>;
+
+/// Stream of telemetry packets to push
+pub type TelemetryPacketStream = Pin<Box<dyn Stream<Item = TelemetryPacket> + Send>>;
+
+/// Wraps a message in a request carrying a JWT in its `authorization` metadata
+#[cfg(not(feature = "stub_client"))]
+fn with_token<T>(message: T, token: &str) -> Result<tonic::Request<T>, tonic::Status> {
+    let mut request = tonic::Request::new(message);
+    let value = format!("Bearer {token}")
+        .parse()
+        .map_err(|_| tonic::Status::invalid_argument("Invalid token."))?;
+
+    request.metadata_mut().insert("authorization", value);
+    Ok(request)
+}
+
 cfg_if::cfg_if! {
     if #[cfg(feature = "stub_backends")] {
         use svc_telemetry::grpc::server::{RpcServiceServer, ServerImpl};
@@ -27,6 +51,21 @@ cfg_if::cfg_if! {
 impl crate::service::Client<RpcServiceClient<Channel>> for TelemetryClient {
     type ReadyRequest = ReadyRequest;
     type ReadyResponse = ReadyResponse;
+    type BoundingBoxRequest = BoundingBoxRequest;
+    type RadiusRequest = RadiusRequest;
+    type AltitudeBand = AltitudeBand;
+    type AircraftStateList = AircraftStateList;
+    type HistoryRequest = HistoryRequest;
+    type HistoryResponse = HistoryResponse;
+    type SubscribeRequest = SubscribeRequest;
+    type TelemetryEventStream = TelemetryEventStream;
+    type PushAdsbRequest = PushAdsbRequest;
+    type PushNetridRequest = PushNetridRequest;
+    type PushResponse = PushResponse;
+    type TelemetryPacketStream = TelemetryPacketStream;
+    type PushStreamResponse = PushStreamResponse;
+    type FlightStatusRequest = FlightStatusRequest;
+    type FlightStatus = FlightStatus;
 
     async fn is_ready(
         &self,
@@ -36,6 +75,113 @@ impl crate::service::Client<RpcServiceClient<Channel>> for TelemetryClient {
         grpc_debug!("(is_ready) request: {:?}", request);
         self.get_client().await?.is_ready(request).await
     }
+
+    async fn query_bounding_box(
+        &self,
+        request: Self::BoundingBoxRequest,
+        token: &str,
+    ) -> Result<tonic::Response<Self::AircraftStateList>, tonic::Status> {
+        grpc_info!("(query_bounding_box) {} client.", self.get_name());
+        grpc_debug!("(query_bounding_box) request: {:?}", request);
+        let request = with_token(request, token)?;
+        self.get_client().await?.query_bounding_box(request).await
+    }
+
+    async fn query_radius(
+        &self,
+        request: Self::RadiusRequest,
+        token: &str,
+    ) -> Result<tonic::Response<Self::AircraftStateList>, tonic::Status> {
+        grpc_info!("(query_radius) {} client.", self.get_name());
+        grpc_debug!("(query_radius) request: {:?}", request);
+        let request = with_token(request, token)?;
+        self.get_client().await?.query_radius(request).await
+    }
+
+    async fn query_altitude_band(
+        &self,
+        request: Self::AltitudeBand,
+        token: &str,
+    ) -> Result<tonic::Response<Self::AircraftStateList>, tonic::Status> {
+        grpc_info!("(query_altitude_band) {} client.", self.get_name());
+        grpc_debug!("(query_altitude_band) request: {:?}", request);
+        let request = with_token(request, token)?;
+        self.get_client().await?.query_altitude_band(request).await
+    }
+
+    async fn get_history(
+        &self,
+        request: Self::HistoryRequest,
+        token: &str,
+    ) -> Result<tonic::Response<Self::HistoryResponse>, tonic::Status> {
+        grpc_info!("(get_history) {} client.", self.get_name());
+        grpc_debug!("(get_history) request: {:?}", request);
+        let request = with_token(request, token)?;
+        self.get_client().await?.get_history(request).await
+    }
+
+    async fn subscribe_telemetry(
+        &self,
+        request: Self::SubscribeRequest,
+        token: &str,
+    ) -> Result<tonic::Response<Self::TelemetryEventStream>, tonic::Status> {
+        grpc_info!("(subscribe_telemetry) {} client.", self.get_name());
+        grpc_debug!("(subscribe_telemetry) request: {:?}", request);
+        let request = with_token(request, token)?;
+        let response = self
+            .get_client()
+            .await?
+            .subscribe_telemetry(request)
+            .await?;
+
+        Ok(response.map(|events| Box::pin(events) as TelemetryEventStream))
+    }
+
+    async fn push_adsb(
+        &self,
+        request: Self::PushAdsbRequest,
+        token: &str,
+    ) -> Result<tonic::Response<Self::PushResponse>, tonic::Status> {
+        grpc_info!("(push_adsb) {} client.", self.get_name());
+        grpc_debug!("(push_adsb) request: {:?}", request);
+        let request = with_token(request, token)?;
+        self.get_client().await?.push_adsb(request).await
+    }
+
+    async fn push_netrid(
+        &self,
+        request: Self::PushNetridRequest,
+        token: &str,
+    ) -> Result<tonic::Response<Self::PushResponse>, tonic::Status> {
+        grpc_info!("(push_netrid) {} client.", self.get_name());
+        grpc_debug!("(push_netrid) request: {:?}", request);
+        let request = with_token(request, token)?;
+        self.get_client().await?.push_netrid(request).await
+    }
+
+    async fn push_telemetry_stream(
+        &self,
+        request: Self::TelemetryPacketStream,
+        token: &str,
+    ) -> Result<tonic::Response<Self::PushStreamResponse>, tonic::Status> {
+        grpc_info!("(push_telemetry_stream) {} client.", self.get_name());
+        let request = with_token(request, token)?;
+        self.get_client()
+            .await?
+            .push_telemetry_stream(request)
+            .await
+    }
+
+    async fn get_flight_status(
+        &self,
+        request: Self::FlightStatusRequest,
+        token: &str,
+    ) -> Result<tonic::Response<Self::FlightStatus>, tonic::Status> {
+        grpc_info!("(get_flight_status) {} client.", self.get_name());
+        grpc_debug!("(get_flight_status) request: {:?}", request);
+        let request = with_token(request, token)?;
+        self.get_client().await?.get_flight_status(request).await
+    }
 }
 
 #[cfg(feature = "stub_client")]
@@ -43,6 +189,21 @@ impl crate::service::Client<RpcServiceClient<Channel>> for TelemetryClient {
 impl crate::service::Client<RpcServiceClient<Channel>> for TelemetryClient {
     type ReadyRequest = ReadyRequest;
     type ReadyResponse = ReadyResponse;
+    type BoundingBoxRequest = BoundingBoxRequest;
+    type RadiusRequest = RadiusRequest;
+    type AltitudeBand = AltitudeBand;
+    type AircraftStateList = AircraftStateList;
+    type HistoryRequest = HistoryRequest;
+    type HistoryResponse = HistoryResponse;
+    type SubscribeRequest = SubscribeRequest;
+    type TelemetryEventStream = TelemetryEventStream;
+    type PushAdsbRequest = PushAdsbRequest;
+    type PushNetridRequest = PushNetridRequest;
+    type PushResponse = PushResponse;
+    type TelemetryPacketStream = TelemetryPacketStream;
+    type PushStreamResponse = PushStreamResponse;
+    type FlightStatusRequest = FlightStatusRequest;
+    type FlightStatus = FlightStatus;
 
     async fn is_ready(
         &self,
@@ -52,6 +213,117 @@ impl crate::service::Client<RpcServiceClient<Channel>> for TelemetryClient {
         grpc_debug!("(is_ready MOCK) request: {:?}", request);
         Ok(tonic::Response::new(ReadyResponse { ready: true }))
     }
+
+    async fn query_bounding_box(
+        &self,
+        request: Self::BoundingBoxRequest,
+        _token: &str,
+    ) -> Result<tonic::Response<Self::AircraftStateList>, tonic::Status> {
+        grpc_warn!("(query_bounding_box MOCK) {} client.", self.get_name());
+        grpc_debug!("(query_bounding_box MOCK) request: {:?}", request);
+        Ok(tonic::Response::new(AircraftStateList { list: vec![] }))
+    }
+
+    async fn query_radius(
+        &self,
+        request: Self::RadiusRequest,
+        _token: &str,
+    ) -> Result<tonic::Response<Self::AircraftStateList>, tonic::Status> {
+        grpc_warn!("(query_radius MOCK) {} client.", self.get_name());
+        grpc_debug!("(query_radius MOCK) request: {:?}", request);
+        Ok(tonic::Response::new(AircraftStateList { list: vec![] }))
+    }
+
+    async fn query_altitude_band(
+        &self,
+        request: Self::AltitudeBand,
+        _token: &str,
+    ) -> Result<tonic::Response<Self::AircraftStateList>, tonic::Status> {
+        grpc_warn!("(query_altitude_band MOCK) {} client.", self.get_name());
+        grpc_debug!("(query_altitude_band MOCK) request: {:?}", request);
+        Ok(tonic::Response::new(AircraftStateList { list: vec![] }))
+    }
+
+    async fn get_history(
+        &self,
+        request: Self::HistoryRequest,
+        _token: &str,
+    ) -> Result<tonic::Response<Self::HistoryResponse>, tonic::Status> {
+        grpc_warn!("(get_history MOCK) {} client.", self.get_name());
+        grpc_debug!("(get_history MOCK) request: {:?}", request);
+        Ok(tonic::Response::new(HistoryResponse {
+            identifier: request.identifier,
+            points: vec![],
+            next: None,
+        }))
+    }
+
+    async fn subscribe_telemetry(
+        &self,
+        request: Self::SubscribeRequest,
+        _token: &str,
+    ) -> Result<tonic::Response<Self::TelemetryEventStream>, tonic::Status> {
+        grpc_warn!("(subscribe_telemetry MOCK) {} client.", self.get_name());
+        grpc_debug!("(subscribe_telemetry MOCK) request: {:?}", request);
+        Ok(tonic::Response::new(Box::pin(futures::stream::empty())))
+    }
+
+    async fn push_adsb(
+        &self,
+        request: Self::PushAdsbRequest,
+        _token: &str,
+    ) -> Result<tonic::Response<Self::PushResponse>, tonic::Status> {
+        grpc_warn!("(push_adsb MOCK) {} client.", self.get_name());
+        grpc_debug!("(push_adsb MOCK) request: {:?}", request);
+        Ok(tonic::Response::new(PushResponse { count: 1 }))
+    }
+
+    async fn push_netrid(
+        &self,
+        request: Self::PushNetridRequest,
+        _token: &str,
+    ) -> Result<tonic::Response<Self::PushResponse>, tonic::Status> {
+        grpc_warn!("(push_netrid MOCK) {} client.", self.get_name());
+        grpc_debug!("(push_netrid MOCK) request: {:?}", request);
+        Ok(tonic::Response::new(PushResponse { count: 1 }))
+    }
+
+    async fn push_telemetry_stream(
+        &self,
+        request: Self::TelemetryPacketStream,
+        _token: &str,
+    ) -> Result<tonic::Response<Self::PushStreamResponse>, tonic::Status> {
+        grpc_warn!("(push_telemetry_stream MOCK) {} client.", self.get_name());
+        let processed = request.count().await as u32;
+        Ok(tonic::Response::new(PushStreamResponse {
+            processed,
+            rejected: 0,
+        }))
+    }
+
+    async fn get_flight_status(
+        &self,
+        request: Self::FlightStatusRequest,
+        _token: &str,
+    ) -> Result<tonic::Response<Self::FlightStatus>, tonic::Status> {
+        grpc_warn!("(get_flight_status MOCK) {} client.", self.get_name());
+        grpc_debug!("(get_flight_status MOCK) request: {:?}", request);
+        let identifier = match request.target {
+            Some(flight_status_request::Target::Identifier(identifier)) => identifier,
+            Some(flight_status_request::Target::SessionId(session_id)) => session_id,
+            None => return Err(tonic::Status::invalid_argument("no aircraft or session")),
+        };
+
+        Ok(tonic::Response::new(FlightStatus {
+            identifier,
+            last_seen: Some(std::time::SystemTime::now().into()),
+            phase: Some(FlightPhase::OnGround as i32),
+            position: None,
+            velocity: None,
+            status: AircraftStatus::Active as i32,
+            seconds_since_update: 0.,
+        }))
+    }
 }
 
 #[cfg(test)]
@@ -89,4 +361,77 @@ mod tests {
         assert!(result.is_ok());
         assert_eq!(result.unwrap().into_inner().ready, true);
     }
+
+    #[tokio::test]
+    async fn test_client_query_altitude_band_request() {
+        let name = "telemetry";
+        let (server_host, server_port) =
+            lib_common::grpc::get_endpoint_from_env("GRPC_HOST", "GRPC_PORT");
+
+        let client: TelemetryClient = GrpcClient::new_client(&server_host, server_port, name);
+        assert_eq!(client.get_name(), name);
+
+        let result = client
+            .query_altitude_band(
+                AltitudeBand {
+                    min_altitude_meters: Some(0.),
+                    max_altitude_meters: Some(500.),
+                },
+                "token",
+            )
+            .await;
+        println!("{:?}", result);
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_client_subscribe_telemetry_request() {
+        let name = "telemetry";
+        let (server_host, server_port) =
+            lib_common::grpc::get_endpoint_from_env("GRPC_HOST", "GRPC_PORT");
+
+        let client: TelemetryClient = GrpcClient::new_client(&server_host, server_port, name);
+        assert_eq!(client.get_name(), name);
+
+        let result = client
+            .subscribe_telemetry(
+                SubscribeRequest {
+                    identifiers: vec![],
+                    kinds: vec![TelemetryEventKind::Position as i32],
+                    bounding_box: None,
+                },
+                "token",
+            )
+            .await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_client_push_telemetry_stream_request() {
+        let name = "telemetry";
+        let (server_host, server_port) =
+            lib_common::grpc::get_endpoint_from_env("GRPC_HOST", "GRPC_PORT");
+
+        let client: TelemetryClient = GrpcClient::new_client(&server_host, server_port, name);
+        assert_eq!(client.get_name(), name);
+
+        let packet = TelemetryPacket {
+            packet: Some(telemetry_packet::Packet::Netrid(PushNetridRequest {
+                payload: vec![0; 25],
+                subject: "aircraft".to_string(),
+                received: None,
+                rssi: None,
+            })),
+        };
+
+        let packets = futures::stream::iter(vec![packet.clone(), packet]);
+        let result = client
+            .push_telemetry_stream(Box::pin(packets), "token")
+            .await;
+        println!("{:?}", result);
+        assert!(result.is_ok());
+
+        let response = result.unwrap().into_inner();
+        assert_eq!(response.processed + response.rejected, 2);
+    }
 }
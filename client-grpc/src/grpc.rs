@@ -14,6 +14,523 @@ pub struct ReadyResponse {
     #[prost(bool, tag = "1")]
     pub ready: bool,
 }
+/// Range of altitudes, unbounded on sides which aren't given
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AltitudeBand {
+    /// Lowest altitude (m)
+    #[prost(double, optional, tag = "1")]
+    pub min_altitude_meters: ::core::option::Option<f64>,
+    /// Highest altitude (m)
+    #[prost(double, optional, tag = "2")]
+    pub max_altitude_meters: ::core::option::Option<f64>,
+}
+/// Bounding Box Request object
+///
+/// Boxes crossing the antimeridian are not supported.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BoundingBoxRequest {
+    /// Southern edge (degrees)
+    #[prost(double, tag = "1")]
+    pub min_latitude: f64,
+    /// Western edge (degrees)
+    #[prost(double, tag = "2")]
+    pub min_longitude: f64,
+    /// Northern edge (degrees)
+    #[prost(double, tag = "3")]
+    pub max_latitude: f64,
+    /// Eastern edge (degrees)
+    #[prost(double, tag = "4")]
+    pub max_longitude: f64,
+    /// Altitudes to include, all if not given
+    #[prost(message, optional, tag = "5")]
+    pub altitude_band: ::core::option::Option<AltitudeBand>,
+}
+/// Radius Request object
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RadiusRequest {
+    /// Latitude of the center (degrees)
+    #[prost(double, tag = "1")]
+    pub latitude: f64,
+    /// Longitude of the center (degrees)
+    #[prost(double, tag = "2")]
+    pub longitude: f64,
+    /// Radius (m)
+    #[prost(double, tag = "3")]
+    pub radius_meters: f64,
+    /// Altitudes to include, all if not given
+    #[prost(message, optional, tag = "4")]
+    pub altitude_band: ::core::option::Option<AltitudeBand>,
+}
+/// Latest position of an aircraft
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AircraftStatePosition {
+    /// Latitude (degrees)
+    #[prost(double, tag = "1")]
+    pub latitude: f64,
+    /// Longitude (degrees)
+    #[prost(double, tag = "2")]
+    pub longitude: f64,
+    /// Altitude (m)
+    #[prost(double, tag = "3")]
+    pub altitude_meters: f64,
+    /// Time the position was received
+    #[prost(message, optional, tag = "4")]
+    pub timestamp_network: ::core::option::Option<::prost_types::Timestamp>,
+    /// Time of the position reported by the aircraft, if known
+    #[prost(message, optional, tag = "5")]
+    pub timestamp_asset: ::core::option::Option<::prost_types::Timestamp>,
+}
+/// Latest velocity of an aircraft
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AircraftStateVelocity {
+    /// Ground speed (m/s)
+    #[prost(float, tag = "1")]
+    pub velocity_horizontal_ground_mps: f32,
+    /// Vertical speed (m/s), positive when climbing
+    #[prost(float, tag = "2")]
+    pub velocity_vertical_mps: f32,
+    /// Track angle (degrees clockwise from true north)
+    #[prost(float, tag = "3")]
+    pub track_angle_degrees: f32,
+    /// Time the velocity was received
+    #[prost(message, optional, tag = "4")]
+    pub timestamp_network: ::core::option::Option<::prost_types::Timestamp>,
+    /// Time of the velocity reported by the aircraft, if known
+    #[prost(message, optional, tag = "5")]
+    pub timestamp_asset: ::core::option::Option<::prost_types::Timestamp>,
+}
+/// Latest state of an aircraft
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AircraftState {
+    /// Canonical identifier of the aircraft
+    #[prost(string, tag = "1")]
+    pub identifier: ::prost::alloc::string::String,
+    /// Type of the aircraft, if identified
+    #[prost(string, optional, tag = "2")]
+    pub aircraft_type: ::core::option::Option<::prost::alloc::string::String>,
+    /// Latest position
+    #[prost(message, optional, tag = "3")]
+    pub position: ::core::option::Option<AircraftStatePosition>,
+    /// Latest velocity
+    #[prost(message, optional, tag = "4")]
+    pub velocity: ::core::option::Option<AircraftStateVelocity>,
+    /// Protocol of the latest telemetry
+    #[prost(enumeration = "TelemetrySource", optional, tag = "5")]
+    pub source: ::core::option::Option<i32>,
+    /// Time the latest telemetry was received
+    #[prost(message, optional, tag = "6")]
+    pub last_update: ::core::option::Option<::prost_types::Timestamp>,
+    /// Status of the aircraft
+    #[prost(enumeration = "AircraftStatus", tag = "7")]
+    pub status: i32,
+    /// Flight phase, if a velocity was received
+    #[prost(enumeration = "FlightPhase", optional, tag = "8")]
+    pub phase: ::core::option::Option<i32>,
+}
+/// List of aircraft states
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AircraftStateList {
+    /// Aircraft states
+    #[prost(message, repeated, tag = "1")]
+    pub list: ::prost::alloc::vec::Vec<AircraftState>,
+}
+/// History Request object
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct HistoryRequest {
+    /// Canonical identifier of the aircraft
+    #[prost(string, tag = "1")]
+    pub identifier: ::prost::alloc::string::String,
+    /// Start of the time range
+    #[prost(message, optional, tag = "2")]
+    pub from: ::core::option::Option<::prost_types::Timestamp>,
+    /// End of the time range
+    #[prost(message, optional, tag = "3")]
+    pub to: ::core::option::Option<::prost_types::Timestamp>,
+    /// Only points after this time, the `next` cursor of the previous page
+    #[prost(message, optional, tag = "4")]
+    pub after: ::core::option::Option<::prost_types::Timestamp>,
+    /// Largest number of points in the page
+    #[prost(uint32, optional, tag = "5")]
+    pub limit: ::core::option::Option<u32>,
+}
+/// A decoded telemetry packet of an aircraft's track
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TrackPoint {
+    /// Time the packet was received
+    #[prost(message, optional, tag = "1")]
+    pub timestamp: ::core::option::Option<::prost_types::Timestamp>,
+    /// Protocol of the packet
+    #[prost(enumeration = "TelemetrySource", tag = "2")]
+    pub source: i32,
+    /// Position, if the packet contained one
+    #[prost(message, optional, tag = "3")]
+    pub position: ::core::option::Option<AircraftStatePosition>,
+    /// Velocity, if the packet contained one
+    #[prost(message, optional, tag = "4")]
+    pub velocity: ::core::option::Option<AircraftStateVelocity>,
+}
+/// History Response object, a page of an aircraft's track in time order
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct HistoryResponse {
+    /// Canonical identifier of the aircraft
+    #[prost(string, tag = "1")]
+    pub identifier: ::prost::alloc::string::String,
+    /// Points of the track on this page
+    #[prost(message, repeated, tag = "2")]
+    pub points: ::prost::alloc::vec::Vec<TrackPoint>,
+    /// Cursor of the next page, passed as `after`, if more points may follow
+    #[prost(message, optional, tag = "3")]
+    pub next: ::core::option::Option<::prost_types::Timestamp>,
+}
+/// Area bounded by latitudes and longitudes
+///
+/// Boxes crossing the antimeridian are not supported.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BoundingBox {
+    /// Southern edge (degrees)
+    #[prost(double, tag = "1")]
+    pub min_latitude: f64,
+    /// Western edge (degrees)
+    #[prost(double, tag = "2")]
+    pub min_longitude: f64,
+    /// Northern edge (degrees)
+    #[prost(double, tag = "3")]
+    pub max_latitude: f64,
+    /// Eastern edge (degrees)
+    #[prost(double, tag = "4")]
+    pub max_longitude: f64,
+}
+/// Subscribe Request object, all filters which are given must match
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SubscribeRequest {
+    /// Canonical identifiers of aircraft, all if empty
+    #[prost(string, repeated, tag = "1")]
+    pub identifiers: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// Kinds of events, all if empty
+    #[prost(enumeration = "TelemetryEventKind", repeated, tag = "2")]
+    pub kinds: ::prost::alloc::vec::Vec<i32>,
+    /// Area of the aircraft, everywhere if not given
+    #[prost(message, optional, tag = "3")]
+    pub bounding_box: ::core::option::Option<BoundingBox>,
+}
+/// Intrusion of an aircraft into a restricted zone
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ZoneIntrusion {
+    /// Identifier of the zone
+    #[prost(string, tag = "1")]
+    pub zone_id: ::prost::alloc::string::String,
+    /// Name of the zone
+    #[prost(string, tag = "2")]
+    pub zone_name: ::prost::alloc::string::String,
+    /// If the aircraft is predicted to enter the zone, rather than inside it
+    #[prost(bool, tag = "3")]
+    pub predicted: bool,
+    /// Seconds until the aircraft enters the zone, zero if inside it
+    #[prost(double, tag = "4")]
+    pub seconds_to_entry: f64,
+}
+/// A live telemetry event
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TelemetryEvent {
+    /// Canonical identifier of the aircraft
+    #[prost(string, tag = "1")]
+    pub identifier: ::prost::alloc::string::String,
+    /// Kind of the event
+    #[prost(enumeration = "TelemetryEventKind", tag = "2")]
+    pub kind: i32,
+    /// Protocol of the telemetry
+    #[prost(enumeration = "TelemetrySource", tag = "3")]
+    pub source: i32,
+    /// Type of the aircraft, for identity events
+    #[prost(string, optional, tag = "4")]
+    pub aircraft_type: ::core::option::Option<::prost::alloc::string::String>,
+    /// Position, for position events
+    #[prost(message, optional, tag = "5")]
+    pub position: ::core::option::Option<AircraftStatePosition>,
+    /// Velocity, for velocity events
+    #[prost(message, optional, tag = "6")]
+    pub velocity: ::core::option::Option<AircraftStateVelocity>,
+    /// Restricted zone, for intrusion events
+    #[prost(message, optional, tag = "7")]
+    pub intrusion: ::core::option::Option<ZoneIntrusion>,
+}
+/// Push ADS-B Request object
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PushAdsbRequest {
+    /// ADS-B packet (14 bytes)
+    #[prost(bytes = "vec", tag = "1")]
+    pub payload: ::prost::alloc::vec::Vec<u8>,
+    /// UUID of the reporter, the subject of its JWT
+    #[prost(string, tag = "2")]
+    pub reporter_id: ::prost::alloc::string::String,
+    /// Time the reporter received the packet, now if not given
+    #[prost(message, optional, tag = "3")]
+    pub received: ::core::option::Option<::prost_types::Timestamp>,
+    /// Signal strength of the received packet (dBm), if known
+    #[prost(float, optional, tag = "4")]
+    pub rssi: ::core::option::Option<f32>,
+}
+/// Push Remote ID Request object
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PushNetridRequest {
+    /// Remote ID packet (25 bytes)
+    #[prost(bytes = "vec", tag = "1")]
+    pub payload: ::prost::alloc::vec::Vec<u8>,
+    /// Identifier of the aircraft, the subject of its JWT
+    #[prost(string, tag = "2")]
+    pub subject: ::prost::alloc::string::String,
+    /// Time the packet was received, now if not given
+    #[prost(message, optional, tag = "3")]
+    pub received: ::core::option::Option<::prost_types::Timestamp>,
+    /// Signal strength of the received packet (dBm), if known
+    #[prost(float, optional, tag = "4")]
+    pub rssi: ::core::option::Option<f32>,
+}
+/// Push Response object
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PushResponse {
+    /// Number of reporters of the packet
+    #[prost(uint32, tag = "1")]
+    pub count: u32,
+}
+/// A packet of a telemetry stream
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TelemetryPacket {
+    /// The packet and its protocol
+    #[prost(oneof = "telemetry_packet::Packet", tags = "1, 2")]
+    pub packet: ::core::option::Option<telemetry_packet::Packet>,
+}
+/// Nested message and enum types in `TelemetryPacket`.
+pub mod telemetry_packet {
+    /// The packet and its protocol
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Packet {
+        /// ADS-B packet
+        #[prost(message, tag = "1")]
+        Adsb(super::PushAdsbRequest),
+        /// Remote ID packet
+        #[prost(message, tag = "2")]
+        Netrid(super::PushNetridRequest),
+    }
+}
+/// Push Stream Response object
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PushStreamResponse {
+    /// Number of packets processed
+    #[prost(uint32, tag = "1")]
+    pub processed: u32,
+    /// Number of packets rejected
+    #[prost(uint32, tag = "2")]
+    pub rejected: u32,
+}
+/// Flight Status Request object
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FlightStatusRequest {
+    /// The aircraft, or its current flight
+    #[prost(oneof = "flight_status_request::Target", tags = "1, 2")]
+    pub target: ::core::option::Option<flight_status_request::Target>,
+}
+/// Nested message and enum types in `FlightStatusRequest`.
+pub mod flight_status_request {
+    /// The aircraft, or its current flight
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Target {
+        /// Canonical identifier of the aircraft
+        #[prost(string, tag = "1")]
+        Identifier(::prost::alloc::string::String),
+        /// Remote ID session ID of the aircraft's flight
+        #[prost(string, tag = "2")]
+        SessionId(::prost::alloc::string::String),
+    }
+}
+/// Flight status of an aircraft
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FlightStatus {
+    /// Canonical identifier of the aircraft
+    #[prost(string, tag = "1")]
+    pub identifier: ::prost::alloc::string::String,
+    /// Time the latest telemetry was received
+    #[prost(message, optional, tag = "2")]
+    pub last_seen: ::core::option::Option<::prost_types::Timestamp>,
+    /// Flight phase, if a velocity was received
+    #[prost(enumeration = "FlightPhase", optional, tag = "3")]
+    pub phase: ::core::option::Option<i32>,
+    /// Latest position
+    #[prost(message, optional, tag = "4")]
+    pub position: ::core::option::Option<AircraftStatePosition>,
+    /// Latest velocity
+    #[prost(message, optional, tag = "5")]
+    pub velocity: ::core::option::Option<AircraftStateVelocity>,
+    /// Status of the aircraft
+    #[prost(enumeration = "AircraftStatus", tag = "6")]
+    pub status: i32,
+    /// Time since the latest telemetry was received (s)
+    #[prost(double, tag = "7")]
+    pub seconds_since_update: f64,
+}
+/// Protocol of received telemetry
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum TelemetrySource {
+    /// ADS-B
+    Adsb = 0,
+    /// Network Remote ID
+    Netrid = 1,
+}
+impl TelemetrySource {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            TelemetrySource::Adsb => "ADSB",
+            TelemetrySource::Netrid => "NETRID",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "ADSB" => Some(Self::Adsb),
+            "NETRID" => Some(Self::Netrid),
+            _ => None,
+        }
+    }
+}
+/// Status of an aircraft
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum AircraftStatus {
+    /// Telemetry was received recently
+    Active = 0,
+    /// No telemetry was received recently
+    Stale = 1,
+}
+impl AircraftStatus {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            AircraftStatus::Active => "ACTIVE",
+            AircraftStatus::Stale => "STALE",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "ACTIVE" => Some(Self::Active),
+            "STALE" => Some(Self::Stale),
+            _ => None,
+        }
+    }
+}
+/// Flight phase of an aircraft
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum FlightPhase {
+    /// Stationary, and not seen airborne since
+    OnGround = 0,
+    /// Climbing
+    Climbing = 1,
+    /// Level flight
+    Cruise = 2,
+    /// Descending
+    Descending = 3,
+    /// Stationary after having been airborne
+    Landed = 4,
+    /// Airborne, and nearly stationary
+    Hover = 5,
+}
+impl FlightPhase {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            FlightPhase::OnGround => "ON_GROUND",
+            FlightPhase::Climbing => "CLIMBING",
+            FlightPhase::Cruise => "CRUISE",
+            FlightPhase::Descending => "DESCENDING",
+            FlightPhase::Landed => "LANDED",
+            FlightPhase::Hover => "HOVER",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "ON_GROUND" => Some(Self::OnGround),
+            "CLIMBING" => Some(Self::Climbing),
+            "CRUISE" => Some(Self::Cruise),
+            "DESCENDING" => Some(Self::Descending),
+            "LANDED" => Some(Self::Landed),
+            "HOVER" => Some(Self::Hover),
+            _ => None,
+        }
+    }
+}
+/// Kind of a live telemetry event
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum TelemetryEventKind {
+    /// Identity of an aircraft
+    Id = 0,
+    /// Position of an aircraft
+    Position = 1,
+    /// Velocity of an aircraft
+    Velocity = 2,
+    /// Intrusion of an aircraft into a restricted zone
+    Intrusion = 3,
+}
+impl TelemetryEventKind {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            TelemetryEventKind::Id => "ID",
+            TelemetryEventKind::Position => "POSITION",
+            TelemetryEventKind::Velocity => "VELOCITY",
+            TelemetryEventKind::Intrusion => "INTRUSION",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "ID" => Some(Self::Id),
+            "POSITION" => Some(Self::Position),
+            "VELOCITY" => Some(Self::Velocity),
+            "INTRUSION" => Some(Self::Intrusion),
+            _ => None,
+        }
+    }
+}
 /// Generated client implementations.
 #[cfg(not(tarpaulin_include))]
 pub mod rpc_service_client {
@@ -121,5 +638,230 @@ pub mod rpc_service_client {
             req.extensions_mut().insert(GrpcMethod::new("grpc.RpcService", "isReady"));
             self.inner.unary(req, path, codec).await
         }
+        /// Latest state of aircraft inside a bounding box
+        pub async fn query_bounding_box(
+            &mut self,
+            request: impl tonic::IntoRequest<super::BoundingBoxRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::AircraftStateList>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.RpcService/queryBoundingBox",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.RpcService", "queryBoundingBox"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// Latest state of aircraft within a radius of a point
+        pub async fn query_radius(
+            &mut self,
+            request: impl tonic::IntoRequest<super::RadiusRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::AircraftStateList>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.RpcService/queryRadius",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.RpcService", "queryRadius"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// Latest state of aircraft inside an altitude band
+        pub async fn query_altitude_band(
+            &mut self,
+            request: impl tonic::IntoRequest<super::AltitudeBand>,
+        ) -> std::result::Result<
+            tonic::Response<super::AircraftStateList>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.RpcService/queryAltitudeBand",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.RpcService", "queryAltitudeBand"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// Decoded track of an aircraft in a time range
+        pub async fn get_history(
+            &mut self,
+            request: impl tonic::IntoRequest<super::HistoryRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::HistoryResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.RpcService/getHistory",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.RpcService", "getHistory"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// Live telemetry events matching a filter, as they are processed
+        pub async fn subscribe_telemetry(
+            &mut self,
+            request: impl tonic::IntoRequest<super::SubscribeRequest>,
+        ) -> std::result::Result<
+            tonic::Response<tonic::codec::Streaming<super::TelemetryEvent>>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.RpcService/SubscribeTelemetry",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.RpcService", "SubscribeTelemetry"));
+            self.inner.server_streaming(req, path, codec).await
+        }
+        /// Process an ADS-B packet received by a reporter, authenticated by its reporter JWT
+        pub async fn push_adsb(
+            &mut self,
+            request: impl tonic::IntoRequest<super::PushAdsbRequest>,
+        ) -> std::result::Result<tonic::Response<super::PushResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.RpcService/PushAdsb",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.RpcService", "PushAdsb"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// Process a remote id packet of an aircraft, authenticated by its JWT
+        pub async fn push_netrid(
+            &mut self,
+            request: impl tonic::IntoRequest<super::PushNetridRequest>,
+        ) -> std::result::Result<tonic::Response<super::PushResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.RpcService/PushNetrid",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.RpcService", "PushNetrid"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// Process a stream of ADS-B and remote id packets of the JWT subject
+        pub async fn push_telemetry_stream(
+            &mut self,
+            request: impl tonic::IntoStreamingRequest<Message = super::TelemetryPacket>,
+        ) -> std::result::Result<
+            tonic::Response<super::PushStreamResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.RpcService/PushTelemetryStream",
+            );
+            let mut req = request.into_streaming_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.RpcService", "PushTelemetryStream"));
+            self.inner.client_streaming(req, path, codec).await
+        }
+        /// Flight status of an aircraft, by aircraft or flight
+        pub async fn get_flight_status(
+            &mut self,
+            request: impl tonic::IntoRequest<super::FlightStatusRequest>,
+        ) -> std::result::Result<tonic::Response<super::FlightStatus>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.RpcService/GetFlightStatus",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.RpcService", "GetFlightStatus"));
+            self.inner.unary(req, path, codec).await
+        }
     }
 }
@@ -11,6 +11,36 @@ where
     type ReadyRequest;
     /// The type expected for ReadyResponse structs.
     type ReadyResponse;
+    /// The type expected for BoundingBoxRequest structs.
+    type BoundingBoxRequest;
+    /// The type expected for RadiusRequest structs.
+    type RadiusRequest;
+    /// The type expected for AltitudeBand structs.
+    type AltitudeBand;
+    /// The type expected for AircraftStateList structs.
+    type AircraftStateList;
+    /// The type expected for HistoryRequest structs.
+    type HistoryRequest;
+    /// The type expected for HistoryResponse structs.
+    type HistoryResponse;
+    /// The type expected for SubscribeRequest structs.
+    type SubscribeRequest;
+    /// The type expected for streams of TelemetryEvent structs.
+    type TelemetryEventStream;
+    /// The type expected for PushAdsbRequest structs.
+    type PushAdsbRequest;
+    /// The type expected for PushNetridRequest structs.
+    type PushNetridRequest;
+    /// The type expected for PushResponse structs.
+    type PushResponse;
+    /// The type expected for streams of TelemetryPacket structs.
+    type TelemetryPacketStream;
+    /// The type expected for PushStreamResponse structs.
+    type PushStreamResponse;
+    /// The type expected for FlightStatusRequest structs.
+    type FlightStatusRequest;
+    /// The type expected for FlightStatus structs.
+    type FlightStatus;
 
     /// Returns a [`tonic::Response`] containing a [`ReadyResponse`](Self::ReadyResponse)
     /// Takes an [`ReadyRequest`](Self::ReadyRequest).
@@ -38,4 +68,391 @@ where
         &self,
         request: Self::ReadyRequest,
     ) -> Result<tonic::Response<Self::ReadyResponse>, tonic::Status>;
+
+    /// Returns a [`tonic::Response`] containing an [`AircraftStateList`](Self::AircraftStateList)
+    /// with the latest state of each aircraft inside a bounding box.
+    /// Takes a [`BoundingBoxRequest`](Self::BoundingBoxRequest) and the JWT of a reporter,
+    /// returned by the REST API `/telemetry/login/reporter` endpoint.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with [`tonic::Code::Unauthenticated`] if the token is missing or invalid.
+    /// Returns [`tonic::Status`] with [`tonic::Code::PermissionDenied`] if the token was not issued to a reporter.
+    /// Returns [`tonic::Status`] with [`tonic::Code::InvalidArgument`] if the box or altitude band is invalid.
+    /// Returns [`tonic::Status`] with [`tonic::Code::Unavailable`] if the cache could not be queried.
+    ///
+    /// # Examples
+    /// ```
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use svc_telemetry_client_grpc::prelude::*;
+    ///
+    /// async fn example (token: &str) -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = TelemetryClient::new_client(&host, port, "telemetry");
+    ///     let response = client
+    ///         .query_bounding_box(
+    ///             telemetry::BoundingBoxRequest {
+    ///                 min_latitude: 52.,
+    ///                 min_longitude: 4.,
+    ///                 max_latitude: 53.,
+    ///                 max_longitude: 5.,
+    ///                 altitude_band: None,
+    ///             },
+    ///             token,
+    ///         )
+    ///         .await?;
+    ///     println!("RESPONSE={:?}", response.into_inner());
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn query_bounding_box(
+        &self,
+        request: Self::BoundingBoxRequest,
+        token: &str,
+    ) -> Result<tonic::Response<Self::AircraftStateList>, tonic::Status>;
+
+    /// Returns a [`tonic::Response`] containing an [`AircraftStateList`](Self::AircraftStateList)
+    /// with the latest state of each aircraft within a radius of a point.
+    /// Takes a [`RadiusRequest`](Self::RadiusRequest) and the JWT of a reporter,
+    /// returned by the REST API `/telemetry/login/reporter` endpoint.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with [`tonic::Code::Unauthenticated`] if the token is missing or invalid.
+    /// Returns [`tonic::Status`] with [`tonic::Code::PermissionDenied`] if the token was not issued to a reporter.
+    /// Returns [`tonic::Status`] with [`tonic::Code::InvalidArgument`] if the point, radius or altitude band is invalid.
+    /// Returns [`tonic::Status`] with [`tonic::Code::Unavailable`] if the cache could not be queried.
+    ///
+    /// # Examples
+    /// ```
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use svc_telemetry_client_grpc::prelude::*;
+    ///
+    /// async fn example (token: &str) -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = TelemetryClient::new_client(&host, port, "telemetry");
+    ///     let response = client
+    ///         .query_radius(
+    ///             telemetry::RadiusRequest {
+    ///                 latitude: 52.5,
+    ///                 longitude: 4.5,
+    ///                 radius_meters: 5000.,
+    ///                 altitude_band: None,
+    ///             },
+    ///             token,
+    ///         )
+    ///         .await?;
+    ///     println!("RESPONSE={:?}", response.into_inner());
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn query_radius(
+        &self,
+        request: Self::RadiusRequest,
+        token: &str,
+    ) -> Result<tonic::Response<Self::AircraftStateList>, tonic::Status>;
+
+    /// Returns a [`tonic::Response`] containing an [`AircraftStateList`](Self::AircraftStateList)
+    /// with the latest state of each aircraft inside an altitude band.
+    /// Takes an [`AltitudeBand`](Self::AltitudeBand) and the JWT of a reporter,
+    /// returned by the REST API `/telemetry/login/reporter` endpoint.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with [`tonic::Code::Unauthenticated`] if the token is missing or invalid.
+    /// Returns [`tonic::Status`] with [`tonic::Code::PermissionDenied`] if the token was not issued to a reporter.
+    /// Returns [`tonic::Status`] with [`tonic::Code::InvalidArgument`] if the altitude band is invalid.
+    /// Returns [`tonic::Status`] with [`tonic::Code::Unavailable`] if the cache could not be queried.
+    ///
+    /// # Examples
+    /// ```
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use svc_telemetry_client_grpc::prelude::*;
+    ///
+    /// async fn example (token: &str) -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = TelemetryClient::new_client(&host, port, "telemetry");
+    ///     let response = client
+    ///         .query_altitude_band(
+    ///             telemetry::AltitudeBand {
+    ///                 min_altitude_meters: Some(0.),
+    ///                 max_altitude_meters: Some(500.),
+    ///             },
+    ///             token,
+    ///         )
+    ///         .await?;
+    ///     println!("RESPONSE={:?}", response.into_inner());
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn query_altitude_band(
+        &self,
+        request: Self::AltitudeBand,
+        token: &str,
+    ) -> Result<tonic::Response<Self::AircraftStateList>, tonic::Status>;
+
+    /// Returns a [`tonic::Response`] containing a [`HistoryResponse`](Self::HistoryResponse)
+    /// with a page of the decoded track of an aircraft in a time range, in time order.
+    /// Takes a [`HistoryRequest`](Self::HistoryRequest) and the JWT of a reporter,
+    /// returned by the REST API `/telemetry/login/reporter` endpoint. The next page is
+    /// requested with the `next` cursor of the response as `after`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with [`tonic::Code::Unauthenticated`] if the token is missing or invalid.
+    /// Returns [`tonic::Status`] with [`tonic::Code::PermissionDenied`] if the token was not issued to a reporter.
+    /// Returns [`tonic::Status`] with [`tonic::Code::InvalidArgument`] if the time range, cursor or limit is invalid.
+    /// Returns [`tonic::Status`] with [`tonic::Code::Unavailable`] if stored telemetry could not be retrieved.
+    ///
+    /// # Examples
+    /// ```
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use std::time::{Duration, SystemTime};
+    /// use svc_telemetry_client_grpc::prelude::*;
+    ///
+    /// async fn example (token: &str) -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = TelemetryClient::new_client(&host, port, "telemetry");
+    ///     let to = SystemTime::now();
+    ///     let response = client
+    ///         .get_history(
+    ///             telemetry::HistoryRequest {
+    ///                 identifier: "4840d6".to_string(),
+    ///                 from: Some((to - Duration::from_secs(1800)).into()),
+    ///                 to: Some(to.into()),
+    ///                 after: None,
+    ///                 limit: None,
+    ///             },
+    ///             token,
+    ///         )
+    ///         .await?;
+    ///     println!("RESPONSE={:?}", response.into_inner());
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn get_history(
+        &self,
+        request: Self::HistoryRequest,
+        token: &str,
+    ) -> Result<tonic::Response<Self::HistoryResponse>, tonic::Status>;
+
+    /// Returns a [`tonic::Response`] containing a [`TelemetryEventStream`](Self::TelemetryEventStream)
+    /// of live telemetry events matching a filter, as they are processed.
+    /// Takes a [`SubscribeRequest`](Self::SubscribeRequest) and the JWT of a reporter,
+    /// returned by the REST API `/telemetry/login/reporter` endpoint.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with [`tonic::Code::Unauthenticated`] if the token is missing or invalid.
+    /// Returns [`tonic::Status`] with [`tonic::Code::PermissionDenied`] if the token was not issued to a reporter.
+    /// Returns [`tonic::Status`] with [`tonic::Code::InvalidArgument`] if an event kind or the bounding box is invalid.
+    ///
+    /// # Examples
+    /// ```
+    /// use futures::StreamExt;
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use svc_telemetry_client_grpc::prelude::*;
+    ///
+    /// async fn example (token: &str) -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = TelemetryClient::new_client(&host, port, "telemetry");
+    ///     let mut events = client
+    ///         .subscribe_telemetry(
+    ///             telemetry::SubscribeRequest {
+    ///                 identifiers: vec![],
+    ///                 kinds: vec![telemetry::TelemetryEventKind::Position as i32],
+    ///                 bounding_box: Some(telemetry::BoundingBox {
+    ///                     min_latitude: 52.,
+    ///                     min_longitude: 4.,
+    ///                     max_latitude: 53.,
+    ///                     max_longitude: 5.,
+    ///                 }),
+    ///             },
+    ///             token,
+    ///         )
+    ///         .await?
+    ///         .into_inner();
+    ///     while let Some(event) = events.next().await {
+    ///         println!("EVENT={:?}", event?);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn subscribe_telemetry(
+        &self,
+        request: Self::SubscribeRequest,
+        token: &str,
+    ) -> Result<tonic::Response<Self::TelemetryEventStream>, tonic::Status>;
+
+    /// Returns a [`tonic::Response`] containing a [`PushResponse`](Self::PushResponse)
+    /// with the number of reporters of an ADS-B packet, after processing it as if it
+    /// was posted to the REST API by the reporter.
+    /// Takes a [`PushAdsbRequest`](Self::PushAdsbRequest) and the reporter JWT of the
+    /// reporter, returned by the REST API `/telemetry/login/reporter` endpoint.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with [`tonic::Code::Unauthenticated`] if the token is missing or invalid.
+    /// Returns [`tonic::Status`] with [`tonic::Code::InvalidArgument`] if the reporter is not a UUID, or the packet is malformed or implausible.
+    /// Returns [`tonic::Status`] with [`tonic::Code::PermissionDenied`] if the token is not of the reporter, or the reporter is quarantined.
+    /// Returns [`tonic::Status`] with [`tonic::Code::Unavailable`] if telemetry can't be processed yet.
+    ///
+    /// # Examples
+    /// ```
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use svc_telemetry_client_grpc::prelude::*;
+    ///
+    /// async fn example (token: &str) -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = TelemetryClient::new_client(&host, port, "telemetry");
+    ///     let response = client
+    ///         .push_adsb(
+    ///             telemetry::PushAdsbRequest {
+    ///                 payload: vec![
+    ///                     0x8D, 0x48, 0x40, 0xD6, 0x20, 0x2C, 0xC3, 0x71, 0xC3, 0x2C, 0xE0, 0x57,
+    ///                     0x60, 0x98,
+    ///                 ],
+    ///                 reporter_id: "ee1c6a8d-0b2a-4d4b-9c5e-5a2c4b0f6d3e".to_string(),
+    ///                 received: None,
+    ///                 rssi: None,
+    ///             },
+    ///             token,
+    ///         )
+    ///         .await?;
+    ///     println!("RESPONSE={:?}", response.into_inner());
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn push_adsb(
+        &self,
+        request: Self::PushAdsbRequest,
+        token: &str,
+    ) -> Result<tonic::Response<Self::PushResponse>, tonic::Status>;
+
+    /// Returns a [`tonic::Response`] containing a [`PushResponse`](Self::PushResponse)
+    /// with the number of reporters of a remote id packet, after processing it as if
+    /// it was posted to the REST API by the aircraft.
+    /// Takes a [`PushNetridRequest`](Self::PushNetridRequest) and the JWT of the
+    /// aircraft, returned by the REST API `/telemetry/login` endpoint.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with [`tonic::Code::Unauthenticated`] if the token is missing or invalid.
+    /// Returns [`tonic::Status`] with [`tonic::Code::PermissionDenied`] if the token is not of the aircraft.
+    /// Returns [`tonic::Status`] with [`tonic::Code::InvalidArgument`] if the packet is malformed or implausible.
+    /// Returns [`tonic::Status`] with [`tonic::Code::Unavailable`] if telemetry can't be processed yet.
+    ///
+    /// # Examples
+    /// ```
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use svc_telemetry_client_grpc::prelude::*;
+    ///
+    /// async fn example (payload: Vec<u8>, token: &str) -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = TelemetryClient::new_client(&host, port, "telemetry");
+    ///     let response = client
+    ///         .push_netrid(
+    ///             telemetry::PushNetridRequest {
+    ///                 payload,
+    ///                 subject: "aircraft".to_string(),
+    ///                 received: None,
+    ///                 rssi: None,
+    ///             },
+    ///             token,
+    ///         )
+    ///         .await?;
+    ///     println!("RESPONSE={:?}", response.into_inner());
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn push_netrid(
+        &self,
+        request: Self::PushNetridRequest,
+        token: &str,
+    ) -> Result<tonic::Response<Self::PushResponse>, tonic::Status>;
+
+    /// Returns a [`tonic::Response`] containing a [`PushStreamResponse`](Self::PushStreamResponse)
+    /// with the number of processed and rejected packets, once the stream ends.
+    /// Takes a [`TelemetryPacketStream`](Self::TelemetryPacketStream) and the JWT of the
+    /// sender, as for [`push_adsb`](Self::push_adsb) and [`push_netrid`](Self::push_netrid).
+    ///
+    /// Packets which are rejected, including packets of other senders than the
+    /// subject of the token, don't end the stream.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with [`tonic::Code::Unauthenticated`] if the token is missing or invalid.
+    /// Returns [`tonic::Status`] with [`tonic::Code::Unavailable`] if telemetry can't be processed yet.
+    ///
+    /// # Examples
+    /// ```
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use svc_telemetry_client_grpc::prelude::*;
+    ///
+    /// async fn example (payloads: Vec<Vec<u8>>, token: &str) -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = TelemetryClient::new_client(&host, port, "telemetry");
+    ///     let packets = payloads.into_iter().map(|payload| telemetry::TelemetryPacket {
+    ///         packet: Some(telemetry::telemetry_packet::Packet::Adsb(
+    ///             telemetry::PushAdsbRequest {
+    ///                 payload,
+    ///                 reporter_id: "ee1c6a8d-0b2a-4d4b-9c5e-5a2c4b0f6d3e".to_string(),
+    ///                 received: None,
+    ///                 rssi: None,
+    ///             },
+    ///         )),
+    ///     });
+    ///     let response = client
+    ///         .push_telemetry_stream(Box::pin(futures::stream::iter(packets)), token)
+    ///         .await?;
+    ///     println!("RESPONSE={:?}", response.into_inner());
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn push_telemetry_stream(
+        &self,
+        request: Self::TelemetryPacketStream,
+        token: &str,
+    ) -> Result<tonic::Response<Self::PushStreamResponse>, tonic::Status>;
+
+    /// Returns a [`tonic::Response`] containing the [`FlightStatus`](Self::FlightStatus)
+    /// of an aircraft, by its canonical identifier or the session ID of its flight.
+    /// Takes a [`FlightStatusRequest`](Self::FlightStatusRequest) and the JWT of a reporter,
+    /// returned by the REST API `/telemetry/login/reporter` endpoint.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with [`tonic::Code::Unauthenticated`] if the token is missing or invalid.
+    /// Returns [`tonic::Status`] with [`tonic::Code::PermissionDenied`] if the token was not issued to a reporter.
+    /// Returns [`tonic::Status`] with [`tonic::Code::InvalidArgument`] if no aircraft or session is provided.
+    /// Returns [`tonic::Status`] with [`tonic::Code::NotFound`] if the aircraft or session is unknown.
+    /// Returns [`tonic::Status`] with [`tonic::Code::Unavailable`] if the aircraft states can't be read.
+    ///
+    /// # Examples
+    /// ```
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use svc_telemetry_client_grpc::prelude::*;
+    ///
+    /// async fn example (token: &str) -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = TelemetryClient::new_client(&host, port, "telemetry");
+    ///     let response = client
+    ///         .get_flight_status(
+    ///             telemetry::FlightStatusRequest {
+    ///                 target: Some(telemetry::flight_status_request::Target::SessionId(
+    ///                     "a2f2b1c0-1d3e-4f5a-8b9c-0d1e2f3a4b5c".to_string(),
+    ///                 )),
+    ///             },
+    ///             token,
+    ///         )
+    ///         .await?;
+    ///     println!("RESPONSE={:?}", response.into_inner());
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn get_flight_status(
+        &self,
+        request: Self::FlightStatusRequest,
+        token: &str,
+    ) -> Result<tonic::Response<Self::FlightStatus>, tonic::Status>;
 }
@@ -4,6 +4,7 @@ use futures_lite::stream::StreamExt;
 use hyper::StatusCode;
 use hyper::{Body, Client, Method, Request};
 use lib_common::grpc::get_endpoint_from_env;
+use svc_telemetry_client_rest::types::ReporterLogin;
 
 async fn mq_listener() -> Result<(), ()> {
     let mq_addr = format!("amqp://rabbitmq:5672");
@@ -48,14 +49,33 @@ async fn mq_listener() -> Result<(), ()> {
     Ok(())
 }
 
-async fn adsb(url: String) {
+async fn adsb(url: String, reporter: u8) {
     let client = Client::builder()
         .pool_idle_timeout(std::time::Duration::from_secs(10))
         .build_http();
 
     let uri = format!("{}/telemetry/adsb", url);
 
-    // TODO(R4): different reporter ID
+    // Each task logs in as a different reporter of reporters.example.json
+    let login = ReporterLogin {
+        reporter_id: format!("00000000-0000-4000-8000-00000000000{reporter}"),
+        secret: format!("example-secret-{reporter}"),
+    };
+
+    let req = Request::builder()
+        .method(Method::GET)
+        .uri(format!("{url}/telemetry/login/reporter"))
+        .header("content-type", "application/json")
+        .body(Body::from(serde_json::to_string(&login).unwrap()))
+        .unwrap();
+
+    let Ok(response) = client.request(req).await else {
+        println!("ERROR: could not login as reporter.");
+        return;
+    };
+
+    let token = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let token: String = serde_json::from_slice(&token).unwrap();
 
     let mut count: u8 = 0;
     let mut odd_flag = 1;
@@ -77,6 +97,7 @@ async fn adsb(url: String) {
             .method(Method::POST)
             .uri(uri.clone())
             .header("content-type", "application/octet-stream")
+            .header("Authorization", format!("Bearer {token}"))
             .body(hyper::body::Bytes::from(payload.to_vec()).into())
             .unwrap();
 
@@ -111,8 +132,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     std::thread::sleep(std::time::Duration::from_secs(5));
 
     let reporters = 3;
-    for _ in 0..reporters {
-        tokio::spawn(adsb(url.clone()));
+    for reporter in 1..=reporters {
+        tokio::spawn(adsb(url.clone(), reporter));
         std::thread::sleep(std::time::Duration::from_millis(225)); // slight lag
     }
 
@@ -2,6 +2,7 @@
 use hyper::{client::connect::HttpConnector, Body, Client, Method, Request, Response};
 use hyper::{Error, StatusCode};
 use lib_common::grpc::get_endpoint_from_env;
+use svc_telemetry_client_rest::types::ReporterLogin;
 
 async fn evaluate(
     response: Result<Response<Body>, Error>,
@@ -34,9 +35,29 @@ async fn evaluate(
     println!("{} (body: {})", status.to_string(), reported_count);
 }
 
+/// Logs in as one of the reporters of reporters.example.json, returns the JWT
+async fn reporter_login(url: &str, client: &Client<HttpConnector>, reporter: u8) -> String {
+    let login = ReporterLogin {
+        reporter_id: format!("00000000-0000-4000-8000-00000000000{reporter}"),
+        secret: format!("example-secret-{reporter}"),
+    };
+
+    let req = Request::builder()
+        .method(Method::GET)
+        .uri(format!("{url}/telemetry/login/reporter"))
+        .header("content-type", "application/json")
+        .body(Body::from(serde_json::to_string(&login).unwrap()))
+        .unwrap();
+
+    let response = client.request(req).await.unwrap();
+    let bytes = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    serde_json::from_slice(&bytes).unwrap()
+}
+
 async fn adsb(url: &str, client: &Client<HttpConnector>) {
     let uri = format!("{}/telemetry/adsb", url);
     let max: u8 = 4;
+    let token = reporter_login(url, client, 1).await;
 
     // POST /telemetry/adsb NOMINAL
     println!(
@@ -54,6 +75,7 @@ async fn adsb(url: &str, client: &Client<HttpConnector>) {
                 .method(Method::POST)
                 .uri(uri.clone())
                 .header("content-type", "application/octet-stream")
+                .header("Authorization", format!("Bearer {token}"))
                 .body(hyper::body::Bytes::from(payload.to_vec()).into())
                 .unwrap();
 
@@ -71,16 +93,35 @@ async fn adsb(url: &str, client: &Client<HttpConnector>) {
     ];
 
     println!(
-        "Send the most recent packet again a few more times, \
+        "Send the most recent packet again from the same reporter, \
+        expect the response body value to stay the same."
+    );
+    {
+        let req = Request::builder()
+            .method(Method::POST)
+            .uri(uri.clone())
+            .header("content-type", "application/octet-stream")
+            .header("Authorization", format!("Bearer {token}"))
+            .body(Body::from(payload.clone().to_vec()))
+            .unwrap();
+
+        let resp = client.request(req).await;
+        evaluate(resp, StatusCode::OK, 1).await;
+    }
+
+    println!(
+        "Send the most recent packet again from a few more reporters, \
         expect incrementing response body values."
     );
-    // Send the last packet (same header) a few more times
-    // expect the return values to be 2, 3, 4, etc. for each repeated packet
+    // Send the last packet (same header) from a few more reporters
+    // expect the return values to be 2, 3, 4, etc. for each new reporter
     for expected_count in 2..=6 {
+        let token = reporter_login(url, client, expected_count as u8).await;
         let req = Request::builder()
             .method(Method::POST)
             .uri(uri.clone())
             .header("content-type", "application/octet-stream")
+            .header("Authorization", format!("Bearer {token}"))
             .body(Body::from(payload.clone().to_vec()))
             .unwrap();
 
@@ -97,6 +138,7 @@ async fn adsb(url: &str, client: &Client<HttpConnector>) {
         .method(Method::POST)
         .uri(uri.clone())
         .header("content-type", "application/octet-stream")
+        .header("Authorization", format!("Bearer {token}"))
         .body(Body::from(payload.clone().to_vec()))
         .unwrap();
 
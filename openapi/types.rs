@@ -1,4 +1,6 @@
 pub use adsb_deku::{Frame as AdsbFrame, DF};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 
 /// A trait for getting a hashed key from a bit-packed frame
 pub trait Keys {
@@ -37,3 +39,468 @@ impl Keys for AdsbFrame {
         self.crc
     }
 }
+
+/// Credentials of a reporter logging in
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct ReporterLogin {
+    /// UUID of the reporter
+    pub reporter_id: String,
+
+    /// Secret provisioned for the reporter
+    pub secret: String,
+}
+
+/// Number of packets ingested from a reporter
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct ReporterCounters {
+    /// Packets received from this reporter
+    pub received: u64,
+
+    /// Packets repeated by this reporter within the deduplication window
+    pub duplicate: u64,
+
+    /// Packets accepted as a result of this reporter's report
+    pub accepted: u64,
+
+    /// Packets this reporter confirmed after they were accepted
+    pub confirmed: u64,
+}
+
+/// Reputation of a reporter
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ReporterReputation {
+    /// Score from 0.0 (untrusted) to 1.0 (trusted)
+    pub score: f32,
+
+    /// If packets from this reporter are rejected
+    pub quarantined: bool,
+
+    /// Unique packets reported
+    pub reports: u64,
+
+    /// Reports corroborated by at least one other reporter
+    pub corroborated: u64,
+
+    /// Reports which decoded to implausible data
+    pub implausible: u64,
+
+    /// Mean clock skew of reports (ms)
+    pub mean_skew_ms: f32,
+}
+
+/// Protocol through which an aircraft's telemetry was received
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TelemetrySource {
+    /// ADS-B, heard by a reporter
+    Adsb,
+
+    /// Network Remote ID
+    Netrid,
+}
+
+/// Status of an aircraft, from the age of its latest telemetry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum AircraftStatus {
+    /// Telemetry was received recently
+    Active,
+
+    /// No telemetry was received for a while
+    Stale,
+}
+
+/// Flight phase of an aircraft
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FlightPhase {
+    /// Stationary, and not seen airborne since
+    OnGround,
+
+    /// Climbing
+    Climbing,
+
+    /// Level flight
+    Cruise,
+
+    /// Descending
+    Descending,
+
+    /// Stationary after having been airborne
+    Landed,
+
+    /// Airborne, and nearly stationary
+    Hover,
+}
+
+/// Latest position of an aircraft
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct AircraftStatePosition {
+    /// Latitude (degrees)
+    pub latitude: f64,
+
+    /// Longitude (degrees)
+    pub longitude: f64,
+
+    /// Altitude (m)
+    pub altitude_meters: f64,
+
+    /// Time the position was received
+    pub timestamp_network: chrono::DateTime<chrono::Utc>,
+
+    /// Time of the position reported by the aircraft, if known
+    pub timestamp_asset: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Latest velocity of an aircraft
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct AircraftStateVelocity {
+    /// Ground speed (m/s)
+    pub velocity_horizontal_ground_mps: f32,
+
+    /// Vertical speed (m/s), positive when climbing
+    pub velocity_vertical_mps: f32,
+
+    /// Track angle (degrees clockwise from true north)
+    pub track_angle_degrees: f32,
+
+    /// Time the velocity was received
+    pub timestamp_network: chrono::DateTime<chrono::Utc>,
+
+    /// Time of the velocity reported by the aircraft, if known
+    pub timestamp_asset: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Latest state of an aircraft
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct AircraftState {
+    /// Canonical identifier of the aircraft
+    pub identifier: String,
+
+    /// Type of the aircraft, if identified
+    pub aircraft_type: Option<String>,
+
+    /// Latest position
+    pub position: Option<AircraftStatePosition>,
+
+    /// Latest velocity
+    pub velocity: Option<AircraftStateVelocity>,
+
+    /// Protocol of the latest telemetry
+    pub source: Option<TelemetrySource>,
+
+    /// Time the latest telemetry was received
+    pub last_update: chrono::DateTime<chrono::Utc>,
+
+    /// Status of the aircraft
+    pub status: AircraftStatus,
+
+    /// Flight phase, if a velocity was received
+    pub phase: Option<FlightPhase>,
+}
+
+/// Altitude band of an aircraft query, unbounded on sides which aren't given
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AltitudeBandQuery {
+    /// Lowest altitude (m)
+    pub min_altitude_meters: Option<f64>,
+
+    /// Highest altitude (m)
+    pub max_altitude_meters: Option<f64>,
+}
+
+/// Bounding box of an aircraft query
+///
+/// Boxes crossing the antimeridian are not supported.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct BoundingBoxQuery {
+    /// Southern edge (degrees)
+    pub min_latitude: f64,
+
+    /// Western edge (degrees)
+    pub min_longitude: f64,
+
+    /// Northern edge (degrees)
+    pub max_latitude: f64,
+
+    /// Eastern edge (degrees)
+    pub max_longitude: f64,
+
+    /// Lowest altitude (m)
+    pub min_altitude_meters: Option<f64>,
+
+    /// Highest altitude (m)
+    pub max_altitude_meters: Option<f64>,
+}
+
+/// Radius around a point of an aircraft query
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct RadiusQuery {
+    /// Latitude of the center (degrees)
+    pub latitude: f64,
+
+    /// Longitude of the center (degrees)
+    pub longitude: f64,
+
+    /// Radius (m)
+    pub radius_meters: f64,
+
+    /// Lowest altitude (m)
+    pub min_altitude_meters: Option<f64>,
+
+    /// Highest altitude (m)
+    pub max_altitude_meters: Option<f64>,
+}
+
+/// Time range and page of a history query
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct HistoryQuery {
+    /// Start of the time range (RFC 3339)
+    pub from: chrono::DateTime<chrono::Utc>,
+
+    /// End of the time range (RFC 3339)
+    pub to: chrono::DateTime<chrono::Utc>,
+
+    /// Only points after this time (RFC 3339), the `next` cursor of the previous page
+    pub after: Option<chrono::DateTime<chrono::Utc>>,
+
+    /// Largest number of points in the page
+    pub limit: Option<u32>,
+}
+
+/// A decoded telemetry packet of an aircraft's track
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct TrackPoint {
+    /// Time the packet was received
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+
+    /// Protocol of the packet
+    pub source: TelemetrySource,
+
+    /// Position, if the packet contained one
+    pub position: Option<AircraftStatePosition>,
+
+    /// Velocity, if the packet contained one
+    pub velocity: Option<AircraftStateVelocity>,
+}
+
+/// A page of an aircraft's track, in time order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct AircraftHistory {
+    /// Canonical identifier of the aircraft
+    pub identifier: String,
+
+    /// Points of the track on this page
+    pub points: Vec<TrackPoint>,
+
+    /// Cursor of the next page, passed as `after`, if more points may follow
+    pub next: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Kind of an emergency
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EmergencyKind {
+    /// Emergency declared by the aircraft, over Remote ID or ADS-B (squawk 7700)
+    Emergency,
+
+    /// System failure reported over Remote ID
+    SystemFailure,
+
+    /// Loss of radio communication (squawk 7600)
+    RadioFailure,
+
+    /// Unlawful interference (squawk 7500)
+    UnlawfulInterference,
+
+    /// Minimum fuel reported over ADS-B
+    MinimumFuel,
+
+    /// Downed aircraft reported over ADS-B
+    DownedAircraft,
+
+    /// Descent faster than an aircraft normally descends
+    AbruptDescent,
+}
+
+/// An active emergency of an aircraft
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Emergency {
+    /// Canonical identifier of the aircraft
+    pub identifier: String,
+
+    /// Kind of emergency
+    pub kind: EmergencyKind,
+
+    /// Protocol through which the emergency was first reported
+    pub source: TelemetrySource,
+
+    /// Latest position of the aircraft when the emergency was reported, if known
+    pub position: Option<AircraftStatePosition>,
+
+    /// Time the emergency was first reported
+    pub started: chrono::DateTime<chrono::Utc>,
+
+    /// Time the emergency was last reported
+    pub last_reported: chrono::DateTime<chrono::Utc>,
+}
+
+/// Kind of a live telemetry event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TelemetryEventKind {
+    /// Identity of an aircraft
+    Id,
+
+    /// Position of an aircraft
+    Position,
+
+    /// Velocity of an aircraft
+    Velocity,
+
+    /// Intrusion of an aircraft into a restricted zone
+    Intrusion,
+}
+
+/// Intrusion of an aircraft into a restricted zone
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ZoneIntrusion {
+    /// Identifier of the zone
+    pub zone_id: String,
+
+    /// Name of the zone
+    pub zone_name: String,
+
+    /// If the aircraft is predicted to enter the zone, rather than inside it
+    pub predicted: bool,
+
+    /// Seconds until the aircraft enters the zone, zero if inside it
+    pub seconds_to_entry: f64,
+}
+
+/// Decoded telemetry of an aircraft, as it is processed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct TelemetryEvent {
+    /// Canonical identifier of the aircraft
+    pub identifier: String,
+
+    /// Kind of telemetry
+    pub kind: TelemetryEventKind,
+
+    /// Protocol of the telemetry
+    pub source: TelemetrySource,
+
+    /// Type of the aircraft, for identity events
+    pub aircraft_type: Option<String>,
+
+    /// Position, for position events
+    pub position: Option<AircraftStatePosition>,
+
+    /// Velocity, for velocity events
+    pub velocity: Option<AircraftStateVelocity>,
+
+    /// Restricted zone, for intrusion events
+    pub intrusion: Option<ZoneIntrusion>,
+}
+
+/// Filters of a live telemetry stream, all events pass if none are given
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct StreamQuery {
+    /// Comma-separated canonical identifiers of aircraft
+    pub identifiers: Option<String>,
+
+    /// Comma-separated kinds of events (`id`, `position`, `velocity`, `intrusion`)
+    pub kinds: Option<String>,
+
+    /// Southern edge of a bounding box (degrees)
+    pub min_latitude: Option<f64>,
+
+    /// Western edge of a bounding box (degrees)
+    pub min_longitude: Option<f64>,
+
+    /// Northern edge of a bounding box (degrees)
+    pub max_latitude: Option<f64>,
+
+    /// Eastern edge of a bounding box (degrees)
+    pub max_longitude: Option<f64>,
+}
+
+/// Bounding box of a live telemetry subscription
+///
+/// Boxes crossing the antimeridian are not supported.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct StreamBoundingBox {
+    /// Southern edge (degrees)
+    pub min_latitude: f64,
+
+    /// Western edge (degrees)
+    pub min_longitude: f64,
+
+    /// Northern edge (degrees)
+    pub max_latitude: f64,
+
+    /// Eastern edge (degrees)
+    pub max_longitude: f64,
+}
+
+/// Message from a client of the live telemetry WebSocket
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum StreamRequest {
+    /// Adds or replaces a subscription, all filters which are given must match
+    Subscribe {
+        /// Name of the subscription, chosen by the client
+        subscription: String,
+
+        /// Canonical identifiers of aircraft, all if empty
+        #[serde(default)]
+        identifiers: Vec<String>,
+
+        /// Kinds of events, all if empty
+        #[serde(default)]
+        kinds: Vec<TelemetryEventKind>,
+
+        /// Area of the aircraft, everywhere if not given
+        bounding_box: Option<StreamBoundingBox>,
+    },
+
+    /// Removes a subscription
+    Unsubscribe {
+        /// Name of the subscription
+        subscription: String,
+    },
+}
+
+/// Message to a client of the live telemetry WebSocket
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum StreamResponse {
+    /// A subscription was added or replaced
+    Subscribed {
+        /// Name of the subscription
+        subscription: String,
+    },
+
+    /// A subscription was removed
+    Unsubscribed {
+        /// Name of the subscription
+        subscription: String,
+    },
+
+    /// A telemetry event matching at least one subscription
+    Event {
+        /// The telemetry event
+        event: TelemetryEvent,
+    },
+
+    /// A message from the client was rejected
+    Error {
+        /// Reason for the rejection
+        message: String,
+    },
+}
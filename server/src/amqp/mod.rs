@@ -34,6 +34,66 @@ pub const ROUTING_KEY_NETRID_POSITION: &str = "netrid:pos";
 /// Routing key for NETRID Velocity messages
 pub const ROUTING_KEY_NETRID_VELOCITY: &str = "netrid:vel";
 
+/// Name of the AMQP queue for rejected position messages
+pub const QUEUE_NAME_REJECTED_POSITION: &str = "rejected_pos";
+
+/// Routing key for position messages rejected by the kinematic filter
+pub const ROUTING_KEY_REJECTED_POSITION: &str = "rejected:pos";
+
+/// Name of the AMQP queue for fused aircraft states
+pub const QUEUE_NAME_FUSED_STATE: &str = "fused_state";
+
+/// Routing key for fused aircraft states
+pub const ROUTING_KEY_FUSED_STATE: &str = "fused:state";
+
+/// Name of the AMQP queue for flight phase changes
+pub const QUEUE_NAME_FLIGHT_PHASE: &str = "flight_phase";
+
+/// Routing key for flight phase changes
+pub const ROUTING_KEY_FLIGHT_PHASE: &str = "flight:phase";
+
+/// Name of the AMQP queue for vertiport geofence crossings
+pub const QUEUE_NAME_GEOFENCE: &str = "vertiport_geofence";
+
+/// Routing key for vertiport geofence crossings
+pub const ROUTING_KEY_GEOFENCE: &str = "vertiport:geofence";
+
+/// Name of the AMQP queue for restricted zone intrusions
+pub const QUEUE_NAME_ZONE_INTRUSION: &str = "zone_intrusion";
+
+/// Routing key for restricted zone intrusions
+pub const ROUTING_KEY_ZONE_INTRUSION: &str = "zone:intrusion";
+
+/// Name of the AMQP queue for conflicts between aircraft
+pub const QUEUE_NAME_CONFLICT: &str = "conflict_alert";
+
+/// Routing key for conflicts between aircraft
+pub const ROUTING_KEY_CONFLICT: &str = "conflict:alert";
+
+/// Name of the AMQP queue for lost and restored aircraft signals
+pub const QUEUE_NAME_LINK: &str = "signal_link";
+
+/// Routing key for lost and restored aircraft signals
+pub const ROUTING_KEY_LINK: &str = "signal:link";
+
+/// Name of the AMQP queue for turbulence and wind grids
+pub const QUEUE_NAME_WEATHER: &str = "weather_grid";
+
+/// Routing key for turbulence and wind grids
+pub const ROUTING_KEY_WEATHER: &str = "weather:grid";
+
+/// Name of the AMQP exchange for emergencies
+pub const EXCHANGE_NAME_EMERGENCY: &str = "emergency";
+
+/// Name of the AMQP priority queue for emergencies
+pub const QUEUE_NAME_EMERGENCY: &str = "emergency";
+
+/// Routing key for emergencies
+pub const ROUTING_KEY_EMERGENCY: &str = "emergency:event";
+
+/// Highest priority of messages in the emergency queue
+pub const EMERGENCY_MAX_PRIORITY: u8 = 9;
+
 /// Custom Error type for MQ errors
 #[derive(Debug, Snafu, Clone, Copy)]
 pub enum AMQPError {
@@ -183,6 +243,110 @@ pub async fn init_mq(config: Config) -> Result<lapin::Channel, AMQPError> {
                 amqp_debug!("(init_mq) error: {:?}", e);
                 AMQPError::CouldNotDeclareQueue
             })?;
+
+        let _ = amqp_channel
+            .queue_declare(
+                QUEUE_NAME_REJECTED_POSITION,
+                lapin::options::QueueDeclareOptions::default(),
+                lapin::types::FieldTable::default(),
+            )
+            .await
+            .map_err(|e| {
+                amqp_error!("(init_mq) could not declare queue '{QUEUE_NAME_REJECTED_POSITION}'.");
+                amqp_debug!("(init_mq) error: {:?}", e);
+                AMQPError::CouldNotDeclareQueue
+            })?;
+
+        let _ = amqp_channel
+            .queue_declare(
+                QUEUE_NAME_FUSED_STATE,
+                lapin::options::QueueDeclareOptions::default(),
+                lapin::types::FieldTable::default(),
+            )
+            .await
+            .map_err(|e| {
+                amqp_error!("(init_mq) could not declare queue '{QUEUE_NAME_FUSED_STATE}'.");
+                amqp_debug!("(init_mq) error: {:?}", e);
+                AMQPError::CouldNotDeclareQueue
+            })?;
+
+        let _ = amqp_channel
+            .queue_declare(
+                QUEUE_NAME_FLIGHT_PHASE,
+                lapin::options::QueueDeclareOptions::default(),
+                lapin::types::FieldTable::default(),
+            )
+            .await
+            .map_err(|e| {
+                amqp_error!("(init_mq) could not declare queue '{QUEUE_NAME_FLIGHT_PHASE}'.");
+                amqp_debug!("(init_mq) error: {:?}", e);
+                AMQPError::CouldNotDeclareQueue
+            })?;
+
+        let _ = amqp_channel
+            .queue_declare(
+                QUEUE_NAME_GEOFENCE,
+                lapin::options::QueueDeclareOptions::default(),
+                lapin::types::FieldTable::default(),
+            )
+            .await
+            .map_err(|e| {
+                amqp_error!("(init_mq) could not declare queue '{QUEUE_NAME_GEOFENCE}'.");
+                amqp_debug!("(init_mq) error: {:?}", e);
+                AMQPError::CouldNotDeclareQueue
+            })?;
+
+        let _ = amqp_channel
+            .queue_declare(
+                QUEUE_NAME_ZONE_INTRUSION,
+                lapin::options::QueueDeclareOptions::default(),
+                lapin::types::FieldTable::default(),
+            )
+            .await
+            .map_err(|e| {
+                amqp_error!("(init_mq) could not declare queue '{QUEUE_NAME_ZONE_INTRUSION}'.");
+                amqp_debug!("(init_mq) error: {:?}", e);
+                AMQPError::CouldNotDeclareQueue
+            })?;
+
+        let _ = amqp_channel
+            .queue_declare(
+                QUEUE_NAME_CONFLICT,
+                lapin::options::QueueDeclareOptions::default(),
+                lapin::types::FieldTable::default(),
+            )
+            .await
+            .map_err(|e| {
+                amqp_error!("(init_mq) could not declare queue '{QUEUE_NAME_CONFLICT}'.");
+                amqp_debug!("(init_mq) error: {:?}", e);
+                AMQPError::CouldNotDeclareQueue
+            })?;
+
+        let _ = amqp_channel
+            .queue_declare(
+                QUEUE_NAME_LINK,
+                lapin::options::QueueDeclareOptions::default(),
+                lapin::types::FieldTable::default(),
+            )
+            .await
+            .map_err(|e| {
+                amqp_error!("(init_mq) could not declare queue '{QUEUE_NAME_LINK}'.");
+                amqp_debug!("(init_mq) error: {:?}", e);
+                AMQPError::CouldNotDeclareQueue
+            })?;
+
+        let _ = amqp_channel
+            .queue_declare(
+                QUEUE_NAME_WEATHER,
+                lapin::options::QueueDeclareOptions::default(),
+                lapin::types::FieldTable::default(),
+            )
+            .await
+            .map_err(|e| {
+                amqp_error!("(init_mq) could not declare queue '{QUEUE_NAME_WEATHER}'.");
+                amqp_debug!("(init_mq) error: {:?}", e);
+                AMQPError::CouldNotDeclareQueue
+            })?;
     }
 
     //
@@ -276,6 +440,198 @@ pub async fn init_mq(config: Config) -> Result<lapin::Channel, AMQPError> {
                 amqp_debug!("(init_mq) error: {:?}", e);
                 AMQPError::CouldNotDeclareQueue
             })?;
+
+        amqp_info!("(init_mq) binding queue '{QUEUE_NAME_REJECTED_POSITION}' to exchange '{EXCHANGE_NAME_TELEMETRY}'...");
+        amqp_channel
+            .queue_bind(
+                QUEUE_NAME_REJECTED_POSITION,
+                EXCHANGE_NAME_TELEMETRY,
+                ROUTING_KEY_REJECTED_POSITION,
+                lapin::options::QueueBindOptions::default(),
+                lapin::types::FieldTable::default(),
+            )
+            .await
+            .map_err(|e| {
+                amqp_error!(
+                    "(init_mq) could not bind queue '{QUEUE_NAME_REJECTED_POSITION}' to exchange."
+                );
+                amqp_debug!("(init_mq) error: {:?}", e);
+                AMQPError::CouldNotDeclareQueue
+            })?;
+
+        amqp_info!("(init_mq) binding queue '{QUEUE_NAME_FUSED_STATE}' to exchange '{EXCHANGE_NAME_TELEMETRY}'...");
+        amqp_channel
+            .queue_bind(
+                QUEUE_NAME_FUSED_STATE,
+                EXCHANGE_NAME_TELEMETRY,
+                ROUTING_KEY_FUSED_STATE,
+                lapin::options::QueueBindOptions::default(),
+                lapin::types::FieldTable::default(),
+            )
+            .await
+            .map_err(|e| {
+                amqp_error!(
+                    "(init_mq) could not bind queue '{QUEUE_NAME_FUSED_STATE}' to exchange."
+                );
+                amqp_debug!("(init_mq) error: {:?}", e);
+                AMQPError::CouldNotDeclareQueue
+            })?;
+
+        amqp_info!("(init_mq) binding queue '{QUEUE_NAME_FLIGHT_PHASE}' to exchange '{EXCHANGE_NAME_TELEMETRY}'...");
+        amqp_channel
+            .queue_bind(
+                QUEUE_NAME_FLIGHT_PHASE,
+                EXCHANGE_NAME_TELEMETRY,
+                ROUTING_KEY_FLIGHT_PHASE,
+                lapin::options::QueueBindOptions::default(),
+                lapin::types::FieldTable::default(),
+            )
+            .await
+            .map_err(|e| {
+                amqp_error!(
+                    "(init_mq) could not bind queue '{QUEUE_NAME_FLIGHT_PHASE}' to exchange."
+                );
+                amqp_debug!("(init_mq) error: {:?}", e);
+                AMQPError::CouldNotDeclareQueue
+            })?;
+
+        amqp_info!("(init_mq) binding queue '{QUEUE_NAME_GEOFENCE}' to exchange '{EXCHANGE_NAME_TELEMETRY}'...");
+        amqp_channel
+            .queue_bind(
+                QUEUE_NAME_GEOFENCE,
+                EXCHANGE_NAME_TELEMETRY,
+                ROUTING_KEY_GEOFENCE,
+                lapin::options::QueueBindOptions::default(),
+                lapin::types::FieldTable::default(),
+            )
+            .await
+            .map_err(|e| {
+                amqp_error!("(init_mq) could not bind queue '{QUEUE_NAME_GEOFENCE}' to exchange.");
+                amqp_debug!("(init_mq) error: {:?}", e);
+                AMQPError::CouldNotDeclareQueue
+            })?;
+
+        amqp_info!("(init_mq) binding queue '{QUEUE_NAME_ZONE_INTRUSION}' to exchange '{EXCHANGE_NAME_TELEMETRY}'...");
+        amqp_channel
+            .queue_bind(
+                QUEUE_NAME_ZONE_INTRUSION,
+                EXCHANGE_NAME_TELEMETRY,
+                ROUTING_KEY_ZONE_INTRUSION,
+                lapin::options::QueueBindOptions::default(),
+                lapin::types::FieldTable::default(),
+            )
+            .await
+            .map_err(|e| {
+                amqp_error!(
+                    "(init_mq) could not bind queue '{QUEUE_NAME_ZONE_INTRUSION}' to exchange."
+                );
+                amqp_debug!("(init_mq) error: {:?}", e);
+                AMQPError::CouldNotDeclareQueue
+            })?;
+
+        amqp_info!("(init_mq) binding queue '{QUEUE_NAME_CONFLICT}' to exchange '{EXCHANGE_NAME_TELEMETRY}'...");
+        amqp_channel
+            .queue_bind(
+                QUEUE_NAME_CONFLICT,
+                EXCHANGE_NAME_TELEMETRY,
+                ROUTING_KEY_CONFLICT,
+                lapin::options::QueueBindOptions::default(),
+                lapin::types::FieldTable::default(),
+            )
+            .await
+            .map_err(|e| {
+                amqp_error!("(init_mq) could not bind queue '{QUEUE_NAME_CONFLICT}' to exchange.");
+                amqp_debug!("(init_mq) error: {:?}", e);
+                AMQPError::CouldNotDeclareQueue
+            })?;
+
+        amqp_info!("(init_mq) binding queue '{QUEUE_NAME_LINK}' to exchange '{EXCHANGE_NAME_TELEMETRY}'...");
+        amqp_channel
+            .queue_bind(
+                QUEUE_NAME_LINK,
+                EXCHANGE_NAME_TELEMETRY,
+                ROUTING_KEY_LINK,
+                lapin::options::QueueBindOptions::default(),
+                lapin::types::FieldTable::default(),
+            )
+            .await
+            .map_err(|e| {
+                amqp_error!("(init_mq) could not bind queue '{QUEUE_NAME_LINK}' to exchange.");
+                amqp_debug!("(init_mq) error: {:?}", e);
+                AMQPError::CouldNotDeclareQueue
+            })?;
+
+        amqp_info!("(init_mq) binding queue '{QUEUE_NAME_WEATHER}' to exchange '{EXCHANGE_NAME_TELEMETRY}'...");
+        amqp_channel
+            .queue_bind(
+                QUEUE_NAME_WEATHER,
+                EXCHANGE_NAME_TELEMETRY,
+                ROUTING_KEY_WEATHER,
+                lapin::options::QueueBindOptions::default(),
+                lapin::types::FieldTable::default(),
+            )
+            .await
+            .map_err(|e| {
+                amqp_error!("(init_mq) could not bind queue '{QUEUE_NAME_WEATHER}' to exchange.");
+                amqp_debug!("(init_mq) error: {:?}", e);
+                AMQPError::CouldNotDeclareQueue
+            })?;
+    }
+
+    //
+    // Declare the emergency exchange, and its priority queue
+    //
+    {
+        let mut arguments = lapin::types::FieldTable::default();
+        arguments.insert(
+            "x-max-priority".into(),
+            lapin::types::AMQPValue::ShortShortUInt(EMERGENCY_MAX_PRIORITY),
+        );
+
+        amqp_info!("(init_mq) creating queue '{QUEUE_NAME_EMERGENCY}'...");
+        let _ = amqp_channel
+            .queue_declare(
+                QUEUE_NAME_EMERGENCY,
+                lapin::options::QueueDeclareOptions::default(),
+                arguments,
+            )
+            .await
+            .map_err(|e| {
+                amqp_error!("(init_mq) could not declare queue '{QUEUE_NAME_EMERGENCY}'.");
+                amqp_debug!("(init_mq) error: {:?}", e);
+                AMQPError::CouldNotDeclareQueue
+            })?;
+
+        amqp_info!("(init_mq) declaring exchange '{EXCHANGE_NAME_EMERGENCY}'...");
+        amqp_channel
+            .exchange_declare(
+                EXCHANGE_NAME_EMERGENCY,
+                lapin::ExchangeKind::Topic,
+                lapin::options::ExchangeDeclareOptions::default(),
+                lapin::types::FieldTable::default(),
+            )
+            .await
+            .map_err(|e| {
+                amqp_error!("(init_mq) could not declare exchange '{EXCHANGE_NAME_EMERGENCY}'.");
+                amqp_debug!("(init_mq) error: {:?}", e);
+                AMQPError::CouldNotDeclareExchange
+            })?;
+
+        amqp_info!("(init_mq) binding queue '{QUEUE_NAME_EMERGENCY}' to exchange '{EXCHANGE_NAME_EMERGENCY}'...");
+        amqp_channel
+            .queue_bind(
+                QUEUE_NAME_EMERGENCY,
+                EXCHANGE_NAME_EMERGENCY,
+                ROUTING_KEY_EMERGENCY,
+                lapin::options::QueueBindOptions::default(),
+                lapin::types::FieldTable::default(),
+            )
+            .await
+            .map_err(|e| {
+                amqp_error!("(init_mq) could not bind queue '{QUEUE_NAME_EMERGENCY}' to exchange.");
+                amqp_debug!("(init_mq) error: {:?}", e);
+                AMQPError::CouldNotDeclareQueue
+            })?;
     }
 
     Ok(amqp_channel)
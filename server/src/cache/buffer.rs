@@ -0,0 +1,260 @@
+//! Ring buffer of aircraft updates waiting to be pushed to svc-gis
+//!
+//! Updates are kept serialized, so that the buffer is bounded by its size
+//!  in bytes. A newer update of an aircraft to the same queue replaces the
+//!  older one, and when the buffer is full the oldest updates are dropped.
+
+use std::collections::VecDeque;
+use svc_gis_client_grpc::prelude::types::*;
+
+/// An update of an aircraft pushed to svc-gis
+pub trait GisItem {
+    /// Identifier under which updates are coalesced, if any
+    fn coalesce_key(&self) -> Option<String>;
+}
+
+impl GisItem for AircraftId {
+    fn coalesce_key(&self) -> Option<String> {
+        self.identifier.clone().or_else(|| self.session_id.clone())
+    }
+}
+
+impl GisItem for AircraftPosition {
+    fn coalesce_key(&self) -> Option<String> {
+        Some(self.identifier.clone())
+    }
+}
+
+impl GisItem for AircraftVelocity {
+    fn coalesce_key(&self) -> Option<String> {
+        Some(self.identifier.clone())
+    }
+}
+
+/// A serialized update waiting in the buffer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BufferEntry {
+    /// Key of the queue the update is pushed to
+    pub queue_key: String,
+
+    /// Identifier under which updates are coalesced, if any
+    pub coalesce_key: Option<String>,
+
+    /// Serialized update
+    pub data: Vec<u8>,
+}
+
+impl BufferEntry {
+    /// True if the entries are updates of the same aircraft to the same queue
+    fn supersedes(&self, other: &BufferEntry) -> bool {
+        self.coalesce_key.is_some()
+            && self.coalesce_key == other.coalesce_key
+            && self.queue_key == other.queue_key
+    }
+}
+
+/// Updates of a queue pushed together in one message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Batch {
+    /// Key of the queue the updates are pushed to
+    pub queue_key: String,
+
+    /// The updates, oldest first
+    pub entries: Vec<BufferEntry>,
+}
+
+/// Bounded buffer of serialized updates, oldest first
+#[derive(Debug, Default)]
+pub struct RingBuffer {
+    capacity_bytes: usize,
+    used_bytes: usize,
+    entries: VecDeque<BufferEntry>,
+}
+
+impl RingBuffer {
+    /// Creates an empty buffer holding up to `capacity_bytes` of updates
+    pub fn new(capacity_bytes: usize) -> Self {
+        RingBuffer {
+            capacity_bytes,
+            used_bytes: 0,
+            entries: VecDeque::new(),
+        }
+    }
+
+    /// Number of updates in the buffer
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// True if the buffer has no updates
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Adds an update, replacing the previous update of the aircraft to
+    ///  the same queue
+    ///
+    /// Returns the number of older updates dropped to make room, or the
+    ///  entry if it is larger than the buffer.
+    pub fn push(&mut self, entry: BufferEntry) -> Result<usize, BufferEntry> {
+        if entry.data.len() > self.capacity_bytes {
+            return Err(entry);
+        }
+
+        if let Some(index) = self.entries.iter().position(|old| entry.supersedes(old)) {
+            if let Some(old) = self.entries.remove(index) {
+                self.used_bytes -= old.data.len();
+            }
+        }
+
+        let mut dropped = 0;
+        while self.used_bytes + entry.data.len() > self.capacity_bytes {
+            let Some(old) = self.entries.pop_front() else {
+                break;
+            };
+
+            self.used_bytes -= old.data.len();
+            dropped += 1;
+        }
+
+        self.used_bytes += entry.data.len();
+        self.entries.push_back(entry);
+        Ok(dropped)
+    }
+
+    /// Puts back updates which could not be pushed, ahead of newer
+    ///  updates, unless they were superseded or there is no room left
+    ///
+    /// Returns the number of updates put back.
+    pub fn requeue(&mut self, entries: Vec<BufferEntry>) -> usize {
+        let mut requeued = 0;
+        for entry in entries.into_iter().rev() {
+            if self.used_bytes + entry.data.len() > self.capacity_bytes
+                || self.entries.iter().any(|new| new.supersedes(&entry))
+            {
+                continue;
+            }
+
+            self.used_bytes += entry.data.len();
+            self.entries.push_front(entry);
+            requeued += 1;
+        }
+
+        requeued
+    }
+
+    /// Removes all updates, grouped into batches per queue of at most
+    ///  `max_message_bytes` each
+    ///
+    /// An update larger than `max_message_bytes` is sent alone.
+    pub fn drain_batches(&mut self, max_message_bytes: usize) -> Vec<Batch> {
+        self.used_bytes = 0;
+
+        let mut batches: Vec<(Batch, usize)> = vec![];
+        for entry in self.entries.drain(..) {
+            let size = entry.data.len();
+            let open = batches
+                .iter_mut()
+                .rev()
+                .find(|(batch, _)| batch.queue_key == entry.queue_key);
+            match open {
+                Some((batch, bytes)) if *bytes + size <= max_message_bytes => {
+                    *bytes += size;
+                    batch.entries.push(entry);
+                }
+                _ => batches.push((
+                    Batch {
+                        queue_key: entry.queue_key.clone(),
+                        entries: vec![entry],
+                    },
+                    size,
+                )),
+            }
+        }
+
+        batches.into_iter().map(|(batch, _)| batch).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(queue_key: &str, identifier: Option<&str>, size: usize) -> BufferEntry {
+        BufferEntry {
+            queue_key: queue_key.to_string(),
+            coalesce_key: identifier.map(String::from),
+            data: vec![0; size],
+        }
+    }
+
+    #[test]
+    fn test_coalesce() {
+        let mut buffer = RingBuffer::new(100);
+        buffer.push(entry("position", Some("a"), 10)).unwrap();
+        buffer.push(entry("velocity", Some("a"), 10)).unwrap();
+        buffer.push(entry("position", Some("b"), 10)).unwrap();
+        buffer.push(entry("position", Some("a"), 20)).unwrap();
+        buffer.push(entry("id", None, 10)).unwrap();
+        buffer.push(entry("id", None, 10)).unwrap();
+        assert_eq!(buffer.len(), 5);
+        assert_eq!(buffer.used_bytes, 60);
+
+        // The newer update moves behind the others
+        let batches = buffer.drain_batches(100);
+        assert_eq!(batches.len(), 3);
+        assert_eq!(batches[1].queue_key, "position");
+        assert_eq!(batches[1].entries[0].coalesce_key.as_deref(), Some("b"));
+        assert_eq!(batches[1].entries[1].data.len(), 20);
+        assert!(buffer.is_empty());
+        assert_eq!(buffer.used_bytes, 0);
+    }
+
+    #[test]
+    fn test_drops_oldest() {
+        let mut buffer = RingBuffer::new(30);
+        assert_eq!(buffer.push(entry("position", Some("a"), 10)), Ok(0));
+        assert_eq!(buffer.push(entry("position", Some("b"), 10)), Ok(0));
+        assert_eq!(buffer.push(entry("position", Some("c"), 15)), Ok(1));
+        assert_eq!(buffer.len(), 2);
+
+        let large = entry("position", Some("d"), 31);
+        assert_eq!(buffer.push(large.clone()), Err(large));
+        assert_eq!(buffer.len(), 2);
+    }
+
+    #[test]
+    fn test_batches() {
+        let mut buffer = RingBuffer::new(1000);
+        for identifier in ["a", "b", "c", "d"] {
+            buffer
+                .push(entry("position", Some(identifier), 40))
+                .unwrap();
+        }
+        buffer.push(entry("velocity", Some("a"), 200)).unwrap();
+
+        let batches = buffer.drain_batches(100);
+        let sizes: Vec<_> = batches
+            .iter()
+            .map(|batch| (batch.queue_key.as_str(), batch.entries.len()))
+            .collect();
+        assert_eq!(sizes, [("position", 2), ("position", 2), ("velocity", 1)]);
+    }
+
+    #[test]
+    fn test_requeue() {
+        let mut buffer = RingBuffer::new(30);
+        buffer.push(entry("position", Some("a"), 10)).unwrap();
+        buffer.push(entry("position", Some("b"), 10)).unwrap();
+        let failed = buffer.drain_batches(100).remove(0).entries;
+
+        // A newer update of b arrived while pushing
+        buffer.push(entry("position", Some("b"), 15)).unwrap();
+        assert_eq!(buffer.requeue(failed), 1);
+        assert_eq!(buffer.used_bytes, 25);
+
+        let batches = buffer.drain_batches(100);
+        assert_eq!(batches[0].entries[0].coalesce_key.as_deref(), Some("a"));
+        assert_eq!(batches[0].entries[1].data.len(), 15);
+    }
+}
@@ -3,6 +3,7 @@
 
 #[macro_use]
 pub mod macros;
+pub mod buffer;
 pub mod pool;
 
 /// Wrapper struct for our Redis Pools
@@ -12,6 +13,10 @@ pub struct TelemetryPools {
     pub netrid: pool::TelemetryPool,
     /// ADSB pool
     pub adsb: pool::TelemetryPool,
+    /// Reporter statistics pool
+    pub reporters: pool::TelemetryPool,
+    /// Per-aircraft tracking pool
+    pub aircraft: pool::TelemetryPool,
 }
 
 /// Convert bytes to a key
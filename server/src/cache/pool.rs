@@ -1,9 +1,12 @@
 //! Redis connection pool implementation
 
+use super::buffer::{Batch, BufferEntry, GisItem, RingBuffer};
 use core::fmt::{Debug, Formatter};
 use deadpool_redis::{redis, Pool, Runtime};
-use serde::Serialize;
+use serde::{de::DeserializeOwned, Serialize};
 use snafu::prelude::Snafu;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 
 /// Represents a pool of connections to a Redis server.
 ///
@@ -19,10 +22,21 @@ pub struct TelemetryPool {
 }
 
 /// Represents a pool of connections to a Redis server for GIS-related data
+///
+/// Updates are buffered, and pushed to their queues in batches by
+///  [`GisPool::flush_loop`].
+///
+/// TODO(R5): Push updates over gRPC, with a fallback to the Redis queues,
+///  once svc-gis has RPCs to update aircraft. Its client only offers
+///  `is_ready` for now.
 #[derive(Clone)]
 pub struct GisPool {
     /// The underlying pool of Redis connections.
     pool: Pool,
+    /// Updates waiting to be pushed.
+    buffer: Arc<Mutex<RingBuffer>>,
+    /// Largest size of the updates pushed in one message, in bytes.
+    max_message_size_bytes: usize,
 }
 
 impl Debug for TelemetryPool {
@@ -35,7 +49,9 @@ impl Debug for TelemetryPool {
 
 impl Debug for GisPool {
     fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
-        f.debug_struct("GisPool").finish()
+        f.debug_struct("GisPool")
+            .field("max_message_size_bytes", &self.max_message_size_bytes)
+            .finish()
     }
 }
 
@@ -55,6 +71,20 @@ pub enum CacheError {
     OperationFailed,
 }
 
+/// Number of times [`TelemetryPool::update_json`] retries an update
+///  which raced with another update of the same key
+const UPDATE_JSON_ATTEMPTS: u8 = 5;
+
+/// Number of unique reporters of a packet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReporterCount {
+    /// True if this reporter had not reported this packet before
+    pub is_new_reporter: bool,
+
+    /// Number of unique reporters of this packet, including this one
+    pub count: u32,
+}
+
 impl GisPool {
     /// Create a new GisPool
     pub async fn new(config: crate::config::Config) -> Result<Self, ()> {
@@ -69,7 +99,13 @@ impl GisPool {
         match cfg.create_pool(Some(Runtime::Tokio1)) {
             Ok(pool) => {
                 cache_info!("(GisPool new) pool created.");
-                Ok(GisPool { pool })
+                Ok(GisPool {
+                    pool,
+                    buffer: Arc::new(Mutex::new(RingBuffer::new(
+                        config.ringbuffer_size_bytes as usize,
+                    ))),
+                    max_message_size_bytes: config.gis_max_message_size_bytes as usize,
+                })
             }
             Err(e) => {
                 cache_error!("(GisPool new) could not create pool: {}", e);
@@ -78,53 +114,139 @@ impl GisPool {
         }
     }
 
-    /// Push items onto a redis queue
+    /// Buffers an item to be pushed onto a redis queue
+    ///
+    /// Replaces an item of the same aircraft waiting to be pushed onto the
+    ///  queue. When the buffer is full, the oldest items are dropped.
     pub async fn push<T>(&mut self, item: T, queue_key: &str) -> Result<(), ()>
     where
-        T: Serialize + Debug,
+        T: Serialize + Debug + GisItem,
     {
         let Ok(serialized) = serde_json::to_vec(&item) else {
             cache_error!("(push) could not serialize item: {:?}", item);
             return Err(());
         };
 
+        let entry = BufferEntry {
+            queue_key: queue_key.to_string(),
+            coalesce_key: item.coalesce_key(),
+            data: serialized,
+        };
+
+        let Ok(mut buffer) = self.buffer.lock() else {
+            cache_error!("(push) could not lock buffer.");
+            return Err(());
+        };
+
+        match buffer.push(entry) {
+            Ok(0) => Ok(()),
+            Ok(dropped) => {
+                cache_warn!("(push) buffer full, dropped {dropped} oldest items.");
+                Ok(())
+            }
+            Err(entry) => {
+                cache_error!(
+                    "(push) item of {} bytes is larger than the buffer.",
+                    entry.data.len()
+                );
+                Err(())
+            }
+        }
+    }
+
+    /// Pushes the buffered items onto their redis queues, in batches
+    ///
+    /// Items which could not be pushed are put back into the buffer.
+    pub async fn flush(&mut self) -> Result<(), ()> {
+        let batches = match self.buffer.lock() {
+            Ok(mut buffer) => buffer.drain_batches(self.max_message_size_bytes),
+            Err(_) => {
+                cache_error!("(flush) could not lock buffer.");
+                return Err(());
+            }
+        };
+
+        if batches.is_empty() {
+            return Ok(());
+        }
+
         let mut connection = match self.pool.get().await {
             Ok(connection) => connection,
             Err(e) => {
-                cache_error!("(push_multiple) could not connect to redis deadpool: {e}");
+                cache_error!("(flush) could not connect to redis deadpool: {e}");
+                self.requeue(batches);
                 return Err(());
             }
         };
 
-        let result = redis::pipe()
-            .atomic()
-            .lpush(queue_key, serialized)
-            .query_async(&mut connection)
-            .await;
+        let mut failed = vec![];
+        for batch in batches {
+            let mut command = redis::cmd("LPUSH");
+            command.arg(&batch.queue_key);
+            for entry in &batch.entries {
+                command.arg(entry.data.as_slice());
+            }
 
-        match result {
-            Ok(redis::Value::Bulk(values)) => {
-                if values.len() == 1 {
-                    Ok(())
-                } else {
+            let result: Result<redis::Value, redis::RedisError> =
+                command.query_async(&mut connection).await;
+
+            match result {
+                Ok(redis::Value::Int(_)) => (),
+                Ok(value) => {
                     cache_error!(
-                        "(push) Operation failed, unexpected redis response: {:?}",
-                        values
+                        "(flush) Operation failed, unexpected redis response: {:?}",
+                        value
                     );
-                    Err(())
+                    failed.push(batch);
+                }
+                Err(e) => {
+                    cache_error!("(flush) Operation failed, redis error: {}", e);
+                    failed.push(batch);
                 }
             }
-            Ok(value) => {
-                cache_error!(
-                    "(push_multiple) Operation failed, unexpected redis response: {:?}",
-                    value
-                );
-                Err(())
-            }
-            Err(e) => {
-                cache_error!("(push_multiple) Operation failed, redis error: {}", e);
-                Err(())
-            }
+        }
+
+        if failed.is_empty() {
+            return Ok(());
+        }
+
+        self.requeue(failed);
+        Err(())
+    }
+
+    /// Puts back batches which could not be pushed
+    fn requeue(&self, batches: Vec<Batch>) {
+        let entries: Vec<BufferEntry> = batches
+            .into_iter()
+            .flat_map(|batch| batch.entries)
+            .collect();
+
+        let count = entries.len();
+        let Ok(mut buffer) = self.buffer.lock() else {
+            cache_error!("(requeue) could not lock buffer, dropped {count} items.");
+            return;
+        };
+
+        let requeued = buffer.requeue(entries);
+        if requeued < count {
+            cache_warn!(
+                "(requeue) dropped {} superseded or overflowing items.",
+                count - requeued
+            );
+        }
+    }
+
+    /// Pushes the buffered items onto their redis queues at a cadence
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: Needs running backends to work.
+    pub async fn flush_loop(mut self, cadence_ms: u16) {
+        cache_info!("(flush_loop) pushing buffered items every {cadence_ms} ms.");
+
+        let mut interval =
+            tokio::time::interval(std::time::Duration::from_millis(cadence_ms.max(1) as u64));
+        loop {
+            interval.tick().await;
+            let _ = self.flush().await;
         }
     }
 }
@@ -164,76 +286,896 @@ impl TelemetryPool {
         }
     }
 
+    /// Adds a reporter to the unique reporters of a key, along with the
+    ///  details of its report (e.g. time of receipt).
+    /// If the key didn't exist, creates it with an expiration time, which
+    ///  later reports don't extend.
+    ///
+    /// Repeated reports from the same reporter don't increase the count
+    ///  or overwrite the details of the first report, so a single
+    ///  misbehaving reporter can't inflate the number of confirmations
+    ///  for a packet.
+    ///
+    /// Returns the number of unique reporters for this key (1 for first time).
+    pub async fn add_reporter<T>(
+        &mut self,
+        key: &str,
+        reporter_id: &str,
+        report: &T,
+        expiration_ms: u32,
+    ) -> Result<ReporterCount, CacheError>
+    where
+        T: Serialize + Debug,
+    {
+        let key = format!("{}:{}", &self.key_folder, key);
+        cache_info!("(add_reporter) entry with key {}.", &key);
+
+        let Ok(report) = serde_json::to_string(report) else {
+            cache_error!("(add_reporter) could not serialize report: {:?}", report);
+            return Err(CacheError::OperationFailed);
+        };
+
+        let mut connection = match self.pool.get().await {
+            Ok(connection) => connection,
+            Err(e) => {
+                cache_error!("(add_reporter) could not connect to redis deadpool: {e}");
+                return Err(CacheError::CouldNotConnect);
+            }
+        };
+
+        // The expiration time is only set when the key is created, so that
+        //  repeated reports can't keep the window of a packet open
+        let result: Result<(i64, i64), redis::RedisError> = redis::Script::new(
+            r"
+            local created = redis.call('EXISTS', KEYS[1]) == 0
+            local added = redis.call('HSETNX', KEYS[1], ARGV[1], ARGV[2])
+            if created then
+                redis.call('PEXPIRE', KEYS[1], ARGV[3])
+            end
+            return {added, redis.call('HLEN', KEYS[1])}
+            ",
+        )
+        .key(&key)
+        .arg(reporter_id)
+        .arg(report)
+        .arg(expiration_ms)
+        .invoke_async(&mut connection)
+        .await;
+
+        let (added, count) = match result {
+            Ok(result) => result,
+            Err(e) => {
+                cache_error!("(add_reporter) Operation failed, redis error: {}", e);
+                return Err(CacheError::OperationFailed);
+            }
+        };
+
+        // Received value should be greater than 0, return a u32 type
+        if count < 1 {
+            cache_error!(
+                "(add_reporter) operation failed, unexpected value: {:?}",
+                count
+            );
+            return Err(CacheError::OperationFailed);
+        }
+
+        Ok(ReporterCount {
+            is_new_reporter: added > 0,
+            count: count as u32,
+        })
+    }
+
+    /// Get the details of each unique reporter of a key
+    pub async fn get_reporters<T>(&mut self, key: &str) -> Result<Vec<T>, CacheError>
+    where
+        T: DeserializeOwned,
+    {
+        let key = format!("{}:{}", &self.key_folder, key);
+        cache_info!("(get_reporters) entry with key {}.", &key);
+
+        let mut connection = match self.pool.get().await {
+            Ok(connection) => connection,
+            Err(e) => {
+                cache_error!("(get_reporters) could not connect to redis deadpool: {e}");
+                return Err(CacheError::CouldNotConnect);
+            }
+        };
+
+        let values = match redis::cmd("HVALS")
+            .arg(&key)
+            .query_async::<_, Vec<String>>(&mut connection)
+            .await
+        {
+            Ok(values) => values,
+            Err(e) => {
+                cache_error!("(get_reporters) Operation failed, redis error: {}", e);
+                return Err(CacheError::OperationFailed);
+            }
+        };
+
+        Ok(values
+            .iter()
+            .filter_map(|value| match serde_json::from_str::<T>(value) {
+                Ok(report) => Some(report),
+                Err(e) => {
+                    cache_warn!("(get_reporters) could not deserialize report: {e}");
+                    None
+                }
+            })
+            .collect())
+    }
+
+    /// Adds to the total weight of the reporters of a key.
     /// If the key didn't exist, inserts the key with an expiration time.
-    /// If the key exists, increments the key and doesn't extend the expiration time.
     ///
-    /// Returns the order in which this specific key was received (1 for first time).
-    pub async fn increment(&mut self, key: &str, expiration_ms: u32) -> Result<u32, CacheError> {
+    /// Returns the total weight of the reporters of this key, including this one.
+    pub async fn add_weight(
+        &mut self,
+        key: &str,
+        weight: f32,
+        expiration_ms: u32,
+    ) -> Result<f32, CacheError> {
+        let key = format!("{}:{}:weight", &self.key_folder, key);
+        cache_info!("(add_weight) entry with key {}.", &key);
+
+        let mut connection = match self.pool.get().await {
+            Ok(connection) => connection,
+            Err(e) => {
+                cache_error!("(add_weight) could not connect to redis deadpool: {e}");
+                return Err(CacheError::CouldNotConnect);
+            }
+        };
+
+        // The expiration time is only set when the key is created, so that
+        //  repeated reports can't keep the window of a packet open
+        let result: Result<String, redis::RedisError> = redis::Script::new(
+            r"
+            local created = redis.call('EXISTS', KEYS[1]) == 0
+            local total = redis.call('INCRBYFLOAT', KEYS[1], ARGV[1])
+            if created then
+                redis.call('PEXPIRE', KEYS[1], ARGV[2])
+            end
+            return total
+            ",
+        )
+        .key(&key)
+        .arg(weight)
+        .arg(expiration_ms)
+        .invoke_async(&mut connection)
+        .await;
+
+        let total = match result {
+            Ok(total) => total,
+            Err(e) => {
+                cache_error!("(add_weight) Operation failed, redis error: {}", e);
+                return Err(CacheError::OperationFailed);
+            }
+        };
+
+        let Ok(total) = total.parse::<f32>() else {
+            cache_error!("(add_weight) Operation failed, could not parse redis response.");
+            return Err(CacheError::OperationFailed);
+        };
+
+        Ok(total)
+    }
+
+    /// Increments a named counter stored under a key by the given amount.
+    /// Counters don't expire.
+    pub async fn increment_counter(
+        &mut self,
+        key: &str,
+        field: &str,
+        increment: u64,
+    ) -> Result<(), CacheError> {
+        let key = format!("{}:{}", &self.key_folder, key);
+        cache_debug!(
+            "(increment_counter) entry with key {}, field {}.",
+            &key,
+            field
+        );
+
+        let mut connection = match self.pool.get().await {
+            Ok(connection) => connection,
+            Err(e) => {
+                cache_error!("(increment_counter) could not connect to redis deadpool: {e}");
+                return Err(CacheError::CouldNotConnect);
+            }
+        };
+
+        match redis::cmd("HINCRBY")
+            .arg(&key)
+            .arg(field)
+            .arg(increment)
+            .query_async::<_, redis::Value>(&mut connection)
+            .await
+        {
+            Ok(redis::Value::Int(_)) => Ok(()),
+            Ok(value) => {
+                cache_error!(
+                    "(increment_counter) Operation failed, unexpected redis response: {:?}",
+                    value
+                );
+                Err(CacheError::OperationFailed)
+            }
+            Err(e) => {
+                cache_error!("(increment_counter) Operation failed, redis error: {}", e);
+                Err(CacheError::OperationFailed)
+            }
+        }
+    }
+
+    /// Get all named counters stored under a key
+    pub async fn get_counters(&mut self, key: &str) -> Result<HashMap<String, u64>, CacheError> {
+        let key = format!("{}:{}", &self.key_folder, key);
+        cache_debug!("(get_counters) entry with key {}.", &key);
+
+        let mut connection = match self.pool.get().await {
+            Ok(connection) => connection,
+            Err(e) => {
+                cache_error!("(get_counters) could not connect to redis deadpool: {e}");
+                return Err(CacheError::CouldNotConnect);
+            }
+        };
+
+        redis::cmd("HGETALL")
+            .arg(&key)
+            .query_async::<_, HashMap<String, u64>>(&mut connection)
+            .await
+            .map_err(|e| {
+                cache_error!("(get_counters) Operation failed, redis error: {}", e);
+                CacheError::OperationFailed
+            })
+    }
+
+    /// Increments a named counter stored under a key by the given amount.
+    /// The key expires `expiration_ms` after its last increment.
+    pub async fn increment_expiring_counter(
+        &mut self,
+        key: &str,
+        field: &str,
+        increment: u64,
+        expiration_ms: u32,
+    ) -> Result<(), CacheError> {
         let key = format!("{}:{}", &self.key_folder, key);
-        cache_info!("(increment) entry with key {}.", &key);
+        cache_debug!(
+            "(increment_expiring_counter) entry with key {}, field {}.",
+            &key,
+            field
+        );
 
         let mut connection = match self.pool.get().await {
             Ok(connection) => connection,
             Err(e) => {
-                cache_error!("(increment) could not connect to redis deadpool: {e}");
+                cache_error!(
+                    "(increment_expiring_counter) could not connect to redis deadpool: {e}"
+                );
                 return Err(CacheError::CouldNotConnect);
             }
         };
 
-        let mut result = match redis::pipe()
+        redis::pipe()
             .atomic()
-            // Return the value of this increment (1 if key didn't exist before)
-            .cmd("INCR")
-            .arg(&[&key])
-            // Set the expiration time
+            .cmd("HINCRBY")
+            .arg(&key)
+            .arg(field)
+            .arg(increment)
+            .ignore()
             .cmd("PEXPIRE")
-            .arg(key)
+            .arg(&key)
             .arg(expiration_ms)
-            // .arg("NX") // only if it didn't have one before
-            // (not implemented in `redis` crate yet: https://redis.io/commands/pexpire/)
             .ignore()
-            .query_async::<_, _>(&mut connection)
+            .query_async::<_, ()>(&mut connection)
             .await
-        {
-            Ok(redis::Value::Bulk(val)) => val,
-            Ok(value) => {
+            .map_err(|e| {
                 cache_error!(
-                    "(increment) Operation failed, unexpected redis response: {:?}",
-                    value
+                    "(increment_expiring_counter) Operation failed, redis error: {}",
+                    e
                 );
-                return Err(CacheError::OperationFailed);
+                CacheError::OperationFailed
+            })
+    }
+
+    /// Get the sum of the named counters stored under several keys
+    pub async fn sum_counters(
+        &mut self,
+        keys: &[String],
+    ) -> Result<HashMap<String, u64>, CacheError> {
+        cache_debug!("(sum_counters) entry with {} keys.", keys.len());
+
+        let mut connection = match self.pool.get().await {
+            Ok(connection) => connection,
+            Err(e) => {
+                cache_error!("(sum_counters) could not connect to redis deadpool: {e}");
+                return Err(CacheError::CouldNotConnect);
             }
+        };
+
+        let mut pipe = redis::pipe();
+        for key in keys {
+            pipe.cmd("HGETALL")
+                .arg(format!("{}:{}", &self.key_folder, key));
+        }
+
+        let all_counters = pipe
+            .query_async::<_, Vec<HashMap<String, u64>>>(&mut connection)
+            .await
+            .map_err(|e| {
+                cache_error!("(sum_counters) Operation failed, redis error: {}", e);
+                CacheError::OperationFailed
+            })?;
+
+        let mut sum: HashMap<String, u64> = HashMap::new();
+        for (field, value) in all_counters.into_iter().flatten() {
+            *sum.entry(field).or_default() += value;
+        }
+
+        Ok(sum)
+    }
+
+    /// Set the value of a key to a serialized item, with an expiration time
+    pub async fn set_json<T>(
+        &mut self,
+        key: &str,
+        item: &T,
+        expiration_ms: u32,
+    ) -> Result<(), CacheError>
+    where
+        T: Serialize + Debug,
+    {
+        let key = format!("{}:{}", &self.key_folder, key);
+        cache_debug!("(set_json) entry with key {}.", &key);
+
+        let Ok(serialized) = serde_json::to_string(item) else {
+            cache_error!("(set_json) could not serialize item: {:?}", item);
+            return Err(CacheError::OperationFailed);
+        };
+
+        let mut connection = match self.pool.get().await {
+            Ok(connection) => connection,
             Err(e) => {
-                cache_error!("(increment) Operation failed, redis error: {}", e);
-                return Err(CacheError::OperationFailed);
+                cache_error!("(set_json) could not connect to redis deadpool: {e}");
+                return Err(CacheError::CouldNotConnect);
             }
         };
 
-        let new_value = match result.pop() {
-            Some(redis::Value::Int(new_value)) => new_value,
-            Some(value) => {
+        match redis::cmd("PSETEX")
+            .arg(&key)
+            .arg(expiration_ms)
+            .arg(serialized)
+            .query_async::<_, redis::Value>(&mut connection)
+            .await
+        {
+            Ok(redis::Value::Okay) => Ok(()),
+            Ok(value) => {
                 cache_error!(
-                    "(increment) Operation failed, unexpected redis response: {:?}",
+                    "(set_json) Operation failed, unexpected redis response: {:?}",
                     value
                 );
+                Err(CacheError::OperationFailed)
+            }
+            Err(e) => {
+                cache_error!("(set_json) Operation failed, redis error: {}", e);
+                Err(CacheError::OperationFailed)
+            }
+        }
+    }
+
+    /// Atomically update the deserialized value of a key
+    ///
+    /// `update` is given the current value (if any), and returns the new
+    ///  value to store (or `None` to leave the key unchanged) along with the
+    ///  result of the update. The new value is only stored if the key wasn't
+    ///  changed since it was read, otherwise the update is retried with the
+    ///  value which was written in the meantime.
+    pub async fn update_json<T, R, F>(
+        &mut self,
+        key: &str,
+        expiration_ms: u32,
+        mut update: F,
+    ) -> Result<R, CacheError>
+    where
+        T: Serialize + DeserializeOwned + Debug,
+        F: FnMut(Option<T>) -> (Option<T>, R),
+    {
+        let key = format!("{}:{}", &self.key_folder, key);
+        cache_debug!("(update_json) entry with key {}.", &key);
+
+        let mut connection = match self.pool.get().await {
+            Ok(connection) => connection,
+            Err(e) => {
+                cache_error!("(update_json) could not connect to redis deadpool: {e}");
+                return Err(CacheError::CouldNotConnect);
+            }
+        };
+
+        // An empty string stands for a missing key, which can't be valid JSON
+        let script = redis::Script::new(
+            r"
+            local current = redis.call('GET', KEYS[1])
+            if current == false then
+                current = ''
+            end
+            if current ~= ARGV[1] then
+                return 0
+            end
+            redis.call('SET', KEYS[1], ARGV[2], 'PX', ARGV[3])
+            return 1
+            ",
+        );
+
+        for _ in 0..UPDATE_JSON_ATTEMPTS {
+            let current = redis::cmd("GET")
+                .arg(&key)
+                .query_async::<_, Option<String>>(&mut connection)
+                .await
+                .map_err(|e| {
+                    cache_error!("(update_json) Operation failed, redis error: {}", e);
+                    CacheError::OperationFailed
+                })?;
+
+            let value = match &current {
+                None => None,
+                Some(current) => match serde_json::from_str::<T>(current) {
+                    Ok(value) => Some(value),
+                    Err(e) => {
+                        cache_error!("(update_json) could not deserialize value: {e}");
+                        return Err(CacheError::OperationFailed);
+                    }
+                },
+            };
+
+            let (value, result) = update(value);
+            let Some(value) = value else {
+                return Ok(result);
+            };
+
+            let Ok(serialized) = serde_json::to_string(&value) else {
+                cache_error!("(update_json) could not serialize item: {:?}", value);
                 return Err(CacheError::OperationFailed);
+            };
+
+            let stored = script
+                .key(&key)
+                .arg(current.unwrap_or_default())
+                .arg(serialized)
+                .arg(expiration_ms)
+                .invoke_async::<_, i64>(&mut connection)
+                .await
+                .map_err(|e| {
+                    cache_error!("(update_json) Operation failed, redis error: {}", e);
+                    CacheError::OperationFailed
+                })?;
+
+            if stored == 1 {
+                return Ok(result);
+            }
+
+            cache_debug!(
+                "(update_json) key {} changed during update, retrying.",
+                &key
+            );
+        }
+
+        cache_warn!(
+            "(update_json) could not update key {} after {} attempts.",
+            &key,
+            UPDATE_JSON_ATTEMPTS
+        );
+
+        Err(CacheError::OperationFailed)
+    }
+
+    /// Get the deserialized value of a key, if the key exists
+    pub async fn get_json<T>(&mut self, key: &str) -> Result<Option<T>, CacheError>
+    where
+        T: DeserializeOwned,
+    {
+        let key = format!("{}:{}", &self.key_folder, key);
+        cache_debug!("(get_json) entry with key {}.", &key);
+
+        let mut connection = match self.pool.get().await {
+            Ok(connection) => connection,
+            Err(e) => {
+                cache_error!("(get_json) could not connect to redis deadpool: {e}");
+                return Err(CacheError::CouldNotConnect);
             }
-            None => {
-                cache_error!("(increment) Operation failed, empty redis response array.");
+        };
+
+        let value = match redis::cmd("GET")
+            .arg(&key)
+            .query_async::<_, Option<String>>(&mut connection)
+            .await
+        {
+            Ok(value) => value,
+            Err(e) => {
+                cache_error!("(get_json) Operation failed, redis error: {}", e);
                 return Err(CacheError::OperationFailed);
             }
         };
 
-        // Received value should be greater than 0, return a u32 type
-        if new_value < 1 {
-            cache_error!(
-                "(increment) operation failed, unexpected value: {:?}",
-                new_value
-            );
-            return Err(CacheError::OperationFailed);
+        let Some(value) = value else {
+            return Ok(None);
+        };
+
+        match serde_json::from_str::<T>(&value) {
+            Ok(item) => Ok(Some(item)),
+            Err(e) => {
+                cache_error!("(get_json) could not deserialize value: {e}");
+                Err(CacheError::OperationFailed)
+            }
+        }
+    }
+
+    /// Set fields of a hash, and reset the expiration time of the hash
+    pub async fn set_fields(
+        &mut self,
+        key: &str,
+        fields: Vec<(&str, String)>,
+        expiration_ms: u32,
+    ) -> Result<(), CacheError> {
+        let key = format!("{}:{}", &self.key_folder, key);
+        cache_debug!("(set_fields) entry with key {}.", &key);
+
+        if fields.is_empty() {
+            return Ok(());
+        }
+
+        let mut connection = match self.pool.get().await {
+            Ok(connection) => connection,
+            Err(e) => {
+                cache_error!("(set_fields) could not connect to redis deadpool: {e}");
+                return Err(CacheError::CouldNotConnect);
+            }
+        };
+
+        redis::pipe()
+            .atomic()
+            .cmd("HSET")
+            .arg(&key)
+            .arg(fields)
+            .ignore()
+            .cmd("PEXPIRE")
+            .arg(&key)
+            .arg(expiration_ms)
+            .ignore()
+            .query_async::<_, ()>(&mut connection)
+            .await
+            .map_err(|e| {
+                cache_error!("(set_fields) Operation failed, redis error: {}", e);
+                CacheError::OperationFailed
+            })
+    }
+
+    /// Remove fields from a hash
+    pub async fn remove_fields(&mut self, key: &str, fields: &[String]) -> Result<(), CacheError> {
+        let key = format!("{}:{}", &self.key_folder, key);
+        cache_debug!("(remove_fields) entry with key {}.", &key);
+
+        if fields.is_empty() {
+            return Ok(());
         }
 
-        Ok(new_value as u32)
+        let mut connection = match self.pool.get().await {
+            Ok(connection) => connection,
+            Err(e) => {
+                cache_error!("(remove_fields) could not connect to redis deadpool: {e}");
+                return Err(CacheError::CouldNotConnect);
+            }
+        };
+
+        redis::cmd("HDEL")
+            .arg(&key)
+            .arg(fields)
+            .query_async::<_, ()>(&mut connection)
+            .await
+            .map_err(|e| {
+                cache_error!("(remove_fields) Operation failed, redis error: {}", e);
+                CacheError::OperationFailed
+            })
+    }
+
+    /// Get all fields of a hash
+    pub async fn get_fields(&mut self, key: &str) -> Result<HashMap<String, String>, CacheError> {
+        let key = format!("{}:{}", &self.key_folder, key);
+        cache_debug!("(get_fields) entry with key {}.", &key);
+
+        let mut connection = match self.pool.get().await {
+            Ok(connection) => connection,
+            Err(e) => {
+                cache_error!("(get_fields) could not connect to redis deadpool: {e}");
+                return Err(CacheError::CouldNotConnect);
+            }
+        };
+
+        redis::cmd("HGETALL")
+            .arg(&key)
+            .query_async::<_, HashMap<String, String>>(&mut connection)
+            .await
+            .map_err(|e| {
+                cache_error!("(get_fields) Operation failed, redis error: {}", e);
+                CacheError::OperationFailed
+            })
+    }
+
+    /// Add a member to a sorted index, or update its score
+    pub async fn index_add(
+        &mut self,
+        key: &str,
+        member: &str,
+        score: i64,
+    ) -> Result<(), CacheError> {
+        let key = format!("{}:{}", &self.key_folder, key);
+        cache_debug!("(index_add) entry with key {}.", &key);
+
+        let mut connection = match self.pool.get().await {
+            Ok(connection) => connection,
+            Err(e) => {
+                cache_error!("(index_add) could not connect to redis deadpool: {e}");
+                return Err(CacheError::CouldNotConnect);
+            }
+        };
+
+        redis::cmd("ZADD")
+            .arg(&key)
+            .arg(score)
+            .arg(member)
+            .query_async::<_, ()>(&mut connection)
+            .await
+            .map_err(|e| {
+                cache_error!("(index_add) Operation failed, redis error: {}", e);
+                CacheError::OperationFailed
+            })
+    }
+
+    /// Get the members of a sorted index with a score of at least `min_score`,
+    ///  removing all members with a lower score
+    pub async fn index_members_since(
+        &mut self,
+        key: &str,
+        min_score: i64,
+    ) -> Result<Vec<String>, CacheError> {
+        let key = format!("{}:{}", &self.key_folder, key);
+        cache_debug!("(index_members_since) entry with key {}.", &key);
+
+        let mut connection = match self.pool.get().await {
+            Ok(connection) => connection,
+            Err(e) => {
+                cache_error!("(index_members_since) could not connect to redis deadpool: {e}");
+                return Err(CacheError::CouldNotConnect);
+            }
+        };
+
+        let (members,): (Vec<String>,) = redis::pipe()
+            .atomic()
+            .cmd("ZREMRANGEBYSCORE")
+            .arg(&key)
+            .arg("-inf")
+            .arg(format!("({min_score}"))
+            .ignore()
+            .cmd("ZRANGEBYSCORE")
+            .arg(&key)
+            .arg(min_score)
+            .arg("+inf")
+            .query_async(&mut connection)
+            .await
+            .map_err(|e| {
+                cache_error!("(index_members_since) Operation failed, redis error: {}", e);
+                CacheError::OperationFailed
+            })?;
+
+        Ok(members)
+    }
+
+    /// Remove members from a sorted index
+    pub async fn index_remove(&mut self, key: &str, members: &[String]) -> Result<(), CacheError> {
+        let key = format!("{}:{}", &self.key_folder, key);
+        cache_debug!("(index_remove) entry with key {}.", &key);
+
+        if members.is_empty() {
+            return Ok(());
+        }
+
+        let mut connection = match self.pool.get().await {
+            Ok(connection) => connection,
+            Err(e) => {
+                cache_error!("(index_remove) could not connect to redis deadpool: {e}");
+                return Err(CacheError::CouldNotConnect);
+            }
+        };
+
+        redis::cmd("ZREM")
+            .arg(&key)
+            .arg(members)
+            .query_async::<_, ()>(&mut connection)
+            .await
+            .map_err(|e| {
+                cache_error!("(index_remove) Operation failed, redis error: {}", e);
+                CacheError::OperationFailed
+            })
+    }
+
+    /// Add a member to a sorted timeline, removing members older than
+    ///  `retention_ms` before its score (a time in milliseconds)
+    pub async fn timeline_add(
+        &mut self,
+        key: &str,
+        member: &str,
+        score: i64,
+        retention_ms: u32,
+    ) -> Result<(), CacheError> {
+        let key = format!("{}:{}", &self.key_folder, key);
+        cache_debug!("(timeline_add) entry with key {}.", &key);
+
+        let mut connection = match self.pool.get().await {
+            Ok(connection) => connection,
+            Err(e) => {
+                cache_error!("(timeline_add) could not connect to redis deadpool: {e}");
+                return Err(CacheError::CouldNotConnect);
+            }
+        };
+
+        redis::pipe()
+            .atomic()
+            .cmd("ZADD")
+            .arg(&key)
+            .arg(score)
+            .arg(member)
+            .ignore()
+            .cmd("ZREMRANGEBYSCORE")
+            .arg(&key)
+            .arg("-inf")
+            .arg(format!("({}", score - retention_ms as i64))
+            .ignore()
+            .cmd("PEXPIRE")
+            .arg(&key)
+            .arg(retention_ms)
+            .ignore()
+            .query_async::<_, ()>(&mut connection)
+            .await
+            .map_err(|e| {
+                cache_error!("(timeline_add) Operation failed, redis error: {}", e);
+                CacheError::OperationFailed
+            })
+    }
+
+    /// Get at most `limit` members of a sorted timeline with a score between
+    ///  `min_score` and `max_score`, in score order
+    pub async fn timeline_range(
+        &mut self,
+        key: &str,
+        min_score: i64,
+        max_score: i64,
+        limit: u32,
+    ) -> Result<Vec<String>, CacheError> {
+        let key = format!("{}:{}", &self.key_folder, key);
+        cache_debug!("(timeline_range) entry with key {}.", &key);
+
+        let mut connection = match self.pool.get().await {
+            Ok(connection) => connection,
+            Err(e) => {
+                cache_error!("(timeline_range) could not connect to redis deadpool: {e}");
+                return Err(CacheError::CouldNotConnect);
+            }
+        };
+
+        redis::cmd("ZRANGEBYSCORE")
+            .arg(&key)
+            .arg(min_score)
+            .arg(max_score)
+            .arg("LIMIT")
+            .arg(0)
+            .arg(limit)
+            .query_async::<_, Vec<String>>(&mut connection)
+            .await
+            .map_err(|e| {
+                cache_error!("(timeline_range) Operation failed, redis error: {}", e);
+                CacheError::OperationFailed
+            })
+    }
+
+    /// Add a member to a geospatial index, or update its location
+    pub async fn geo_add(
+        &mut self,
+        key: &str,
+        member: &str,
+        latitude: f64,
+        longitude: f64,
+    ) -> Result<(), CacheError> {
+        let key = format!("{}:{}", &self.key_folder, key);
+        cache_debug!("(geo_add) entry with key {}.", &key);
+
+        let mut connection = match self.pool.get().await {
+            Ok(connection) => connection,
+            Err(e) => {
+                cache_error!("(geo_add) could not connect to redis deadpool: {e}");
+                return Err(CacheError::CouldNotConnect);
+            }
+        };
+
+        redis::cmd("GEOADD")
+            .arg(&key)
+            .arg(longitude)
+            .arg(latitude)
+            .arg(member)
+            .query_async::<_, ()>(&mut connection)
+            .await
+            .map_err(|e| {
+                cache_error!("(geo_add) Operation failed, redis error: {}", e);
+                CacheError::OperationFailed
+            })
+    }
+
+    /// Get the members of a geospatial index within a radius (m) of a point
+    pub async fn geo_search_radius(
+        &mut self,
+        key: &str,
+        latitude: f64,
+        longitude: f64,
+        radius_meters: f64,
+    ) -> Result<Vec<String>, CacheError> {
+        let key = format!("{}:{}", &self.key_folder, key);
+        cache_debug!("(geo_search_radius) entry with key {}.", &key);
+
+        let mut connection = match self.pool.get().await {
+            Ok(connection) => connection,
+            Err(e) => {
+                cache_error!("(geo_search_radius) could not connect to redis deadpool: {e}");
+                return Err(CacheError::CouldNotConnect);
+            }
+        };
+
+        redis::cmd("GEOSEARCH")
+            .arg(&key)
+            .arg("FROMLONLAT")
+            .arg(longitude)
+            .arg(latitude)
+            .arg("BYRADIUS")
+            .arg(radius_meters)
+            .arg("m")
+            .query_async::<_, Vec<String>>(&mut connection)
+            .await
+            .map_err(|e| {
+                cache_error!("(geo_search_radius) Operation failed, redis error: {}", e);
+                CacheError::OperationFailed
+            })
+    }
+
+    /// Get the members of a geospatial index within a box (m) centered on a point
+    pub async fn geo_search_box(
+        &mut self,
+        key: &str,
+        latitude: f64,
+        longitude: f64,
+        width_meters: f64,
+        height_meters: f64,
+    ) -> Result<Vec<String>, CacheError> {
+        let key = format!("{}:{}", &self.key_folder, key);
+        cache_debug!("(geo_search_box) entry with key {}.", &key);
+
+        let mut connection = match self.pool.get().await {
+            Ok(connection) => connection,
+            Err(e) => {
+                cache_error!("(geo_search_box) could not connect to redis deadpool: {e}");
+                return Err(CacheError::CouldNotConnect);
+            }
+        };
+
+        redis::cmd("GEOSEARCH")
+            .arg(&key)
+            .arg("FROMLONLAT")
+            .arg(longitude)
+            .arg(latitude)
+            .arg("BYBOX")
+            .arg(width_meters)
+            .arg(height_meters)
+            .arg("m")
+            .query_async::<_, Vec<String>>(&mut connection)
+            .await
+            .map_err(|e| {
+                cache_error!("(geo_search_box) Operation failed, redis error: {}", e);
+                CacheError::OperationFailed
+            })
     }
 
     ///
@@ -29,11 +29,11 @@ pub struct Config {
     pub redis: deadpool_redis::Config,
     /// path to log configuration YAML file
     pub log_config: String,
-    /// Ring buffer size
+    /// Size of the ring buffer of updates waiting to be pushed to svc-gis
     pub ringbuffer_size_bytes: u16,
     /// Cadence for pushes to svc-gis
     pub gis_push_cadence_ms: u16,
-    /// Maximum message size for gRPC message to svc-gis
+    /// Maximum size of a batch of updates pushed to svc-gis
     pub gis_max_message_size_bytes: u16,
     /// Rate limit - requests per second for REST requests
     pub rest_request_limit_per_second: u8,
@@ -42,6 +42,37 @@ pub struct Config {
     /// Full url (including port number) to be allowed as request origin for
     /// REST requests
     pub rest_cors_allowed_origin: String,
+    /// Number of unique reporters needed before an ADS-B packet is accepted,
+    /// at least 1
+    pub adsb_reporters_needed: u32,
+    /// Path to a JSON file of reporter UUIDs and SHA-256 digests of their
+    /// secrets, no reporter can login if empty
+    pub reporter_credentials_file: String,
+    /// Interval between fused aircraft states pushed to svc-gis and published
+    /// to RabbitMQ
+    pub fusion_interval_ms: u32,
+    /// Path to a JSON file of vertiport geofences, none if empty
+    pub vertiport_geofences_file: String,
+    /// Path to a GeoJSON file of restricted zones, none if empty
+    pub restricted_zones_file: String,
+    /// Seconds ahead an aircraft is checked for entering a restricted zone
+    pub zone_lookahead_seconds: u32,
+    /// Seconds ahead aircraft are checked for conflicts
+    pub conflict_horizon_seconds: u32,
+    /// Smallest horizontal distance between aircraft, in meters
+    pub conflict_horizontal_separation_meters: u32,
+    /// Smallest vertical distance between aircraft, in meters
+    pub conflict_vertical_separation_meters: u32,
+    /// Time without ADS-B from an aircraft before its signal is lost
+    pub adsb_signal_timeout_ms: u32,
+    /// Time without Remote ID from an aircraft before its signal is lost
+    pub netrid_signal_timeout_ms: u32,
+    /// Size of the cells of the weather grid north to south, in meters
+    pub weather_cell_size_meters: u32,
+    /// Time after which the weight of a weather sample is halved, in seconds
+    pub weather_half_life_seconds: u32,
+    /// Interval between publications of the weather grid
+    pub weather_publish_interval_ms: u32,
 }
 
 impl Default for Config {
@@ -78,6 +109,20 @@ impl Config {
             rest_request_limit_per_second: 2,
             rest_concurrency_limit_per_service: 5,
             rest_cors_allowed_origin: String::from("http://localhost:3000"),
+            adsb_reporters_needed: 1,
+            reporter_credentials_file: String::new(),
+            fusion_interval_ms: 1000,
+            vertiport_geofences_file: String::new(),
+            restricted_zones_file: String::new(),
+            zone_lookahead_seconds: 30,
+            conflict_horizon_seconds: 60,
+            conflict_horizontal_separation_meters: 500,
+            conflict_vertical_separation_meters: 100,
+            adsb_signal_timeout_ms: 10000,
+            netrid_signal_timeout_ms: 5000,
+            weather_cell_size_meters: 1000,
+            weather_half_life_seconds: 600,
+            weather_publish_interval_ms: 60000,
         }
     }
 
@@ -87,7 +132,7 @@ impl Config {
         dotenv().ok();
         let default_config = Config::default();
 
-        config::Config::builder()
+        let config: Config = config::Config::builder()
             .set_default("docker_port_grpc", default_config.docker_port_grpc)?
             .set_default("docker_port_rest", default_config.docker_port_rest)?
             .set_default("log_config", default_config.log_config)?
@@ -112,9 +157,78 @@ impl Config {
                 "gis_max_message_size_bytes",
                 default_config.gis_max_message_size_bytes,
             )?
+            .set_default(
+                "adsb_reporters_needed",
+                default_config.adsb_reporters_needed,
+            )?
+            .set_default(
+                "reporter_credentials_file",
+                default_config.reporter_credentials_file,
+            )?
+            .set_default("fusion_interval_ms", default_config.fusion_interval_ms)?
+            .set_default(
+                "vertiport_geofences_file",
+                default_config.vertiport_geofences_file,
+            )?
+            .set_default(
+                "restricted_zones_file",
+                default_config.restricted_zones_file,
+            )?
+            .set_default(
+                "zone_lookahead_seconds",
+                default_config.zone_lookahead_seconds,
+            )?
+            .set_default(
+                "conflict_horizon_seconds",
+                default_config.conflict_horizon_seconds,
+            )?
+            .set_default(
+                "conflict_horizontal_separation_meters",
+                default_config.conflict_horizontal_separation_meters,
+            )?
+            .set_default(
+                "conflict_vertical_separation_meters",
+                default_config.conflict_vertical_separation_meters,
+            )?
+            .set_default(
+                "adsb_signal_timeout_ms",
+                default_config.adsb_signal_timeout_ms,
+            )?
+            .set_default(
+                "netrid_signal_timeout_ms",
+                default_config.netrid_signal_timeout_ms,
+            )?
+            .set_default(
+                "weather_cell_size_meters",
+                default_config.weather_cell_size_meters,
+            )?
+            .set_default(
+                "weather_half_life_seconds",
+                default_config.weather_half_life_seconds,
+            )?
+            .set_default(
+                "weather_publish_interval_ms",
+                default_config.weather_publish_interval_ms,
+            )?
             .add_source(Environment::default().separator("__"))
             .build()?
-            .try_deserialize()
+            .try_deserialize()?;
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Check values which the service cannot run with
+    fn validate(&self) -> Result<(), ConfigError> {
+        // With no reporters needed, every packet would be counted as
+        //  already accepted and none would be processed
+        if self.adsb_reporters_needed < 1 {
+            return Err(ConfigError::Message(
+                "adsb_reporters_needed must be at least 1".to_string(),
+            ));
+        }
+
+        Ok(())
     }
 }
 
@@ -150,6 +264,20 @@ mod tests {
             config.rest_cors_allowed_origin,
             String::from("http://localhost:3000")
         );
+        assert_eq!(config.adsb_reporters_needed, 1);
+        assert_eq!(config.reporter_credentials_file, String::new());
+        assert_eq!(config.fusion_interval_ms, 1000);
+        assert_eq!(config.vertiport_geofences_file, String::new());
+        assert_eq!(config.restricted_zones_file, String::new());
+        assert_eq!(config.zone_lookahead_seconds, 30);
+        assert_eq!(config.conflict_horizon_seconds, 60);
+        assert_eq!(config.conflict_horizontal_separation_meters, 500);
+        assert_eq!(config.conflict_vertical_separation_meters, 100);
+        assert_eq!(config.adsb_signal_timeout_ms, 10000);
+        assert_eq!(config.netrid_signal_timeout_ms, 5000);
+        assert_eq!(config.weather_cell_size_meters, 1000);
+        assert_eq!(config.weather_half_life_seconds, 600);
+        assert_eq!(config.weather_publish_interval_ms, 60000);
         ut_info!("(test_config_from_default) Success.");
     }
 
@@ -182,6 +310,20 @@ mod tests {
             "REST_CORS_ALLOWED_ORIGIN",
             "https://allowed.origin.host:443",
         );
+        std::env::set_var("ADSB_REPORTERS_NEEDED", "3");
+        std::env::set_var("REPORTER_CREDENTIALS_FILE", "reporters.json");
+        std::env::set_var("FUSION_INTERVAL_MS", "500");
+        std::env::set_var("VERTIPORT_GEOFENCES_FILE", "geofences.json");
+        std::env::set_var("RESTRICTED_ZONES_FILE", "zones.geojson");
+        std::env::set_var("ZONE_LOOKAHEAD_SECONDS", "60");
+        std::env::set_var("CONFLICT_HORIZON_SECONDS", "120");
+        std::env::set_var("CONFLICT_HORIZONTAL_SEPARATION_METERS", "1000");
+        std::env::set_var("CONFLICT_VERTICAL_SEPARATION_METERS", "150");
+        std::env::set_var("ADSB_SIGNAL_TIMEOUT_MS", "20000");
+        std::env::set_var("NETRID_SIGNAL_TIMEOUT_MS", "3000");
+        std::env::set_var("WEATHER_CELL_SIZE_METERS", "2000");
+        std::env::set_var("WEATHER_HALF_LIFE_SECONDS", "300");
+        std::env::set_var("WEATHER_PUBLISH_INTERVAL_MS", "30000");
         let config = Config::try_from_env();
         assert!(config.is_ok());
         let config = config.unwrap();
@@ -201,6 +343,26 @@ mod tests {
             config.rest_cors_allowed_origin,
             String::from("https://allowed.origin.host:443")
         );
+        assert_eq!(config.adsb_reporters_needed, 3);
+        assert_eq!(
+            config.reporter_credentials_file,
+            String::from("reporters.json")
+        );
+        assert_eq!(config.fusion_interval_ms, 500);
+        assert_eq!(
+            config.vertiport_geofences_file,
+            String::from("geofences.json")
+        );
+        assert_eq!(config.restricted_zones_file, String::from("zones.geojson"));
+        assert_eq!(config.zone_lookahead_seconds, 60);
+        assert_eq!(config.conflict_horizon_seconds, 120);
+        assert_eq!(config.conflict_horizontal_separation_meters, 1000);
+        assert_eq!(config.conflict_vertical_separation_meters, 150);
+        assert_eq!(config.adsb_signal_timeout_ms, 20000);
+        assert_eq!(config.netrid_signal_timeout_ms, 3000);
+        assert_eq!(config.weather_cell_size_meters, 2000);
+        assert_eq!(config.weather_half_life_seconds, 300);
+        assert_eq!(config.weather_publish_interval_ms, 30000);
         assert_eq!(
             config.amqp.url,
             Some(String::from("amqp://test_rabbitmq:5672"))
@@ -214,4 +376,18 @@ mod tests {
 
         ut_info!("(test_config_from_env) Success.");
     }
+
+    #[tokio::test]
+    async fn test_config_validate() {
+        crate::get_log_handle().await;
+        ut_info!("(test_config_validate) Start.");
+
+        let mut config = Config::default();
+        assert!(config.validate().is_ok());
+
+        config.adsb_reporters_needed = 0;
+        assert!(config.validate().is_err());
+
+        ut_info!("(test_config_validate) Success.");
+    }
 }
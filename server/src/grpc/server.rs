@@ -4,20 +4,477 @@ pub mod grpc_server {
     #![allow(unused_qualifications, missing_docs)]
     tonic::include_proto!("grpc");
 }
+pub use grpc_server::flight_status_request::Target;
 pub use grpc_server::rpc_service_server::{RpcService, RpcServiceServer};
-pub use grpc_server::{ReadyRequest, ReadyResponse};
+pub use grpc_server::telemetry_packet::Packet;
+pub use grpc_server::{
+    AircraftState, AircraftStateList, AircraftStatePosition, AircraftStateVelocity, AircraftStatus,
+    AltitudeBand, BoundingBox, BoundingBoxRequest, FlightPhase, FlightStatus, FlightStatusRequest,
+    HistoryRequest, HistoryResponse, PushAdsbRequest, PushNetridRequest, PushResponse,
+    PushStreamResponse, RadiusRequest, ReadyRequest, ReadyResponse, SubscribeRequest,
+    TelemetryEvent, TelemetryEventKind, TelemetryPacket, TelemetrySource, TrackPoint,
+    ZoneIntrusion,
+};
 
+use crate::rest::api::reporter::ReporterReceipt;
+use crate::rest::api::rest_types;
 use crate::shutdown_signal;
+use crate::tracking::events::EventFilter;
+use crate::tracking::spatial;
 use crate::Config;
 
+use chrono::{DateTime, Utc};
+use futures::stream::Stream;
 use std::fmt::Debug;
 use std::net::SocketAddr;
+use std::pin::Pin;
+use std::time::SystemTime;
 use tonic::transport::Server;
 use tonic::{Request, Response, Status};
+use uuid::Uuid;
+
+use crate::rest::api::ingest::Ingest;
+
+#[cfg(not(feature = "stub_server"))]
+use crate::cache::pool::TelemetryPool;
+#[cfg(not(feature = "stub_server"))]
+use crate::rest::api::jwt::{Claim, Role};
+#[cfg(not(feature = "stub_server"))]
+use crate::rest::api::{adsb, netrid};
+#[cfg(not(feature = "stub_server"))]
+use crate::tracking::events::get_event_bus;
+#[cfg(not(feature = "stub_server"))]
+use crate::tracking::history::{self, HistoryError};
+#[cfg(not(feature = "stub_server"))]
+use crate::tracking::identity::{Identifier, IdentifierKind, IdentityRegistry};
+#[cfg(not(feature = "stub_server"))]
+use crate::tracking::spatial::QueryError;
+#[cfg(not(feature = "stub_server"))]
+use crate::tracking::state;
+#[cfg(not(feature = "stub_server"))]
+use futures::stream::StreamExt;
+#[cfg(not(feature = "stub_server"))]
+use hyper::StatusCode;
+
+/// Stream of live telemetry events sent to a subscriber
+type TelemetryEventStream = Pin<Box<dyn Stream<Item = Result<TelemetryEvent, Status>> + Send>>;
+
+/// Converts the status of a rejected packet to a gRPC status
+#[cfg(not(feature = "stub_server"))]
+fn ingest_status(code: StatusCode) -> Status {
+    match code {
+        StatusCode::BAD_REQUEST => {
+            Status::invalid_argument("Malformed packet, or implausible position.")
+        }
+        StatusCode::FORBIDDEN => Status::permission_denied("Reporter is quarantined."),
+        StatusCode::NOT_IMPLEMENTED => Status::unimplemented("Unsupported message."),
+        StatusCode::SERVICE_UNAVAILABLE => {
+            Status::unavailable("Dependencies of svc-telemetry were down.")
+        }
+        _ => Status::internal("Could not process telemetry."),
+    }
+}
+
+/// Creates the receipt of a pushed packet
+fn receipt(
+    reporter_id: &str,
+    received: Option<prost_types::Timestamp>,
+    rssi: Option<f32>,
+) -> Result<ReporterReceipt, Status> {
+    if reporter_id.is_empty() {
+        return Err(Status::invalid_argument("Missing reporter."));
+    }
+
+    let received = match received.map(SystemTime::try_from) {
+        Some(Ok(received)) => received.into(),
+        Some(Err(_)) => return Err(Status::invalid_argument("Invalid time of receipt.")),
+        None => Utc::now(),
+    };
+
+    Ok(ReporterReceipt {
+        reporter_id: reporter_id.to_string(),
+        received,
+        rssi,
+    })
+}
+
+/// Creates the receipt of a pushed ADS-B packet
+///
+/// Reporters are identified by UUID, the same as reporters logging in to
+///  the REST API.
+fn adsb_receipt(request: &PushAdsbRequest) -> Result<ReporterReceipt, Status> {
+    if Uuid::parse_str(&request.reporter_id).is_err() {
+        return Err(Status::invalid_argument("Reporter is not a UUID."));
+    }
+
+    receipt(&request.reporter_id, request.received.clone(), request.rssi)
+}
+
+/// Decodes the JWT in the `authorization` metadata of a request
+#[cfg(not(feature = "stub_server"))]
+fn authenticate<T>(request: &Request<T>) -> Result<Claim, Status> {
+    let Some(token) = request
+        .metadata()
+        .get("authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+    else {
+        grpc_warn!("(authenticate) missing token.");
+        return Err(Status::unauthenticated("Missing token."));
+    };
+
+    Claim::decode(token.to_string()).map_err(|e| {
+        grpc_warn!("(authenticate) could not decode token: {e}");
+        Status::unauthenticated("Invalid token.")
+    })
+}
+
+/// Checks that a packet is pushed by the subject of the token, in the
+///  given role
+#[cfg(not(feature = "stub_server"))]
+fn authorize(claim: &Claim, role: Role, subject: &str) -> Result<(), Status> {
+    if claim.role != role || claim.sub != subject {
+        grpc_warn!(
+            "(authorize) {:?} {} cannot push packets of {:?} {subject}.",
+            claim.role,
+            claim.sub,
+            role
+        );
+        return Err(Status::permission_denied("Token does not match sender."));
+    }
+
+    Ok(())
+}
+
+/// Decodes the JWT of a request reading telemetry, which only provisioned
+///  reporters may do, the same as over REST
+#[cfg(not(feature = "stub_server"))]
+fn authenticate_reader<T>(request: &Request<T>) -> Result<Claim, Status> {
+    let claim = authenticate(request)?;
+    if claim.role != Role::Reporter {
+        grpc_warn!(
+            "(authenticate_reader) {:?} {} cannot read telemetry.",
+            claim.role,
+            claim.sub
+        );
+        return Err(Status::permission_denied(
+            "Token was not issued to a reporter.",
+        ));
+    }
+
+    Ok(claim)
+}
+
+/// Processes a pushed ADS-B packet, returning the number of its reporters
+#[cfg(not(feature = "stub_server"))]
+async fn ingest_adsb(
+    ingest: &Ingest,
+    claim: &Claim,
+    request: PushAdsbRequest,
+) -> Result<u32, Status> {
+    let receipt = adsb_receipt(&request)?;
+    authorize(claim, Role::Reporter, &receipt.reporter_id)?;
+    adsb::process_adsb(ingest, receipt, &request.payload)
+        .await
+        .map_err(ingest_status)
+}
+
+/// Processes a pushed remote id packet, returning the number of its reporters
+#[cfg(not(feature = "stub_server"))]
+async fn ingest_netrid(
+    ingest: &Ingest,
+    claim: &Claim,
+    request: PushNetridRequest,
+) -> Result<u32, Status> {
+    let receipt = receipt(&request.subject, request.received, request.rssi)?;
+    authorize(claim, Role::Aircraft, &receipt.reporter_id)?;
+    netrid::process_netrid(ingest, receipt, &request.payload)
+        .await
+        .map_err(ingest_status)
+}
+
+/// Converts a time to a protobuf timestamp
+fn timestamp(time: DateTime<Utc>) -> prost_types::Timestamp {
+    SystemTime::from(time).into()
+}
+
+impl From<AltitudeBand> for spatial::AltitudeBand {
+    fn from(band: AltitudeBand) -> Self {
+        spatial::AltitudeBand {
+            min_altitude_meters: band.min_altitude_meters,
+            max_altitude_meters: band.max_altitude_meters,
+        }
+    }
+}
+
+impl From<rest_types::TelemetrySource> for TelemetrySource {
+    fn from(source: rest_types::TelemetrySource) -> Self {
+        match source {
+            rest_types::TelemetrySource::Adsb => TelemetrySource::Adsb,
+            rest_types::TelemetrySource::Netrid => TelemetrySource::Netrid,
+        }
+    }
+}
+
+impl From<rest_types::AircraftStatePosition> for AircraftStatePosition {
+    fn from(position: rest_types::AircraftStatePosition) -> Self {
+        AircraftStatePosition {
+            latitude: position.latitude,
+            longitude: position.longitude,
+            altitude_meters: position.altitude_meters,
+            timestamp_network: Some(timestamp(position.timestamp_network)),
+            timestamp_asset: position.timestamp_asset.map(timestamp),
+        }
+    }
+}
+
+impl From<rest_types::AircraftStateVelocity> for AircraftStateVelocity {
+    fn from(velocity: rest_types::AircraftStateVelocity) -> Self {
+        AircraftStateVelocity {
+            velocity_horizontal_ground_mps: velocity.velocity_horizontal_ground_mps,
+            velocity_vertical_mps: velocity.velocity_vertical_mps,
+            track_angle_degrees: velocity.track_angle_degrees,
+            timestamp_network: Some(timestamp(velocity.timestamp_network)),
+            timestamp_asset: velocity.timestamp_asset.map(timestamp),
+        }
+    }
+}
+
+impl From<rest_types::AircraftStatus> for AircraftStatus {
+    fn from(status: rest_types::AircraftStatus) -> Self {
+        match status {
+            rest_types::AircraftStatus::Active => AircraftStatus::Active,
+            rest_types::AircraftStatus::Stale => AircraftStatus::Stale,
+        }
+    }
+}
+
+impl From<rest_types::FlightPhase> for FlightPhase {
+    fn from(phase: rest_types::FlightPhase) -> Self {
+        match phase {
+            rest_types::FlightPhase::OnGround => FlightPhase::OnGround,
+            rest_types::FlightPhase::Climbing => FlightPhase::Climbing,
+            rest_types::FlightPhase::Cruise => FlightPhase::Cruise,
+            rest_types::FlightPhase::Descending => FlightPhase::Descending,
+            rest_types::FlightPhase::Landed => FlightPhase::Landed,
+            rest_types::FlightPhase::Hover => FlightPhase::Hover,
+        }
+    }
+}
+
+impl From<rest_types::AircraftState> for AircraftState {
+    fn from(state: rest_types::AircraftState) -> Self {
+        AircraftState {
+            identifier: state.identifier,
+            aircraft_type: state.aircraft_type,
+            position: state.position.map(Into::into),
+            velocity: state.velocity.map(Into::into),
+            source: state
+                .source
+                .map(|source| TelemetrySource::from(source) as i32),
+            last_update: Some(timestamp(state.last_update)),
+            status: AircraftStatus::from(state.status) as i32,
+            phase: state.phase.map(|phase| FlightPhase::from(phase) as i32),
+        }
+    }
+}
+
+/// Flight status of an aircraft, from its latest state
+fn flight_status(state: rest_types::AircraftState, now: DateTime<Utc>) -> FlightStatus {
+    let seconds_since_update = (now - state.last_update).num_milliseconds().max(0) as f64 / 1000.;
+    FlightStatus {
+        identifier: state.identifier,
+        last_seen: Some(timestamp(state.last_update)),
+        phase: state.phase.map(|phase| FlightPhase::from(phase) as i32),
+        position: state.position.map(Into::into),
+        velocity: state.velocity.map(Into::into),
+        status: AircraftStatus::from(state.status) as i32,
+        seconds_since_update,
+    }
+}
+
+impl From<rest_types::TrackPoint> for TrackPoint {
+    fn from(point: rest_types::TrackPoint) -> Self {
+        TrackPoint {
+            timestamp: Some(timestamp(point.timestamp)),
+            source: TelemetrySource::from(point.source) as i32,
+            position: point.position.map(Into::into),
+            velocity: point.velocity.map(Into::into),
+        }
+    }
+}
+
+impl From<rest_types::AircraftHistory> for HistoryResponse {
+    fn from(history: rest_types::AircraftHistory) -> Self {
+        HistoryResponse {
+            identifier: history.identifier,
+            points: history.points.into_iter().map(Into::into).collect(),
+            next: history.next.map(timestamp),
+        }
+    }
+}
+
+impl TryFrom<&HistoryRequest> for rest_types::HistoryQuery {
+    type Error = Status;
+
+    fn try_from(request: &HistoryRequest) -> Result<Self, Self::Error> {
+        let (Some(from), Some(to)) = (request.from.clone(), request.to.clone()) else {
+            return Err(Status::invalid_argument("Missing time range."));
+        };
+
+        let (Ok(from), Ok(to)) = (SystemTime::try_from(from), SystemTime::try_from(to)) else {
+            return Err(Status::invalid_argument("Invalid time range."));
+        };
+
+        let after = match request.after.clone().map(SystemTime::try_from) {
+            None => None,
+            Some(Ok(after)) => Some(after.into()),
+            Some(Err(_)) => return Err(Status::invalid_argument("Invalid cursor.")),
+        };
+
+        Ok(rest_types::HistoryQuery {
+            from: from.into(),
+            to: to.into(),
+            after,
+            limit: request.limit,
+        })
+    }
+}
+
+impl From<TelemetryEventKind> for rest_types::TelemetryEventKind {
+    fn from(kind: TelemetryEventKind) -> Self {
+        match kind {
+            TelemetryEventKind::Id => rest_types::TelemetryEventKind::Id,
+            TelemetryEventKind::Position => rest_types::TelemetryEventKind::Position,
+            TelemetryEventKind::Velocity => rest_types::TelemetryEventKind::Velocity,
+            TelemetryEventKind::Intrusion => rest_types::TelemetryEventKind::Intrusion,
+        }
+    }
+}
+
+impl From<rest_types::TelemetryEventKind> for TelemetryEventKind {
+    fn from(kind: rest_types::TelemetryEventKind) -> Self {
+        match kind {
+            rest_types::TelemetryEventKind::Id => TelemetryEventKind::Id,
+            rest_types::TelemetryEventKind::Position => TelemetryEventKind::Position,
+            rest_types::TelemetryEventKind::Velocity => TelemetryEventKind::Velocity,
+            rest_types::TelemetryEventKind::Intrusion => TelemetryEventKind::Intrusion,
+        }
+    }
+}
+
+impl From<BoundingBox> for spatial::BoundingBox {
+    fn from(bounding_box: BoundingBox) -> Self {
+        spatial::BoundingBox {
+            min_latitude: bounding_box.min_latitude,
+            min_longitude: bounding_box.min_longitude,
+            max_latitude: bounding_box.max_latitude,
+            max_longitude: bounding_box.max_longitude,
+        }
+    }
+}
+
+impl From<rest_types::TelemetryEvent> for TelemetryEvent {
+    fn from(event: rest_types::TelemetryEvent) -> Self {
+        TelemetryEvent {
+            identifier: event.identifier,
+            kind: TelemetryEventKind::from(event.kind) as i32,
+            source: TelemetrySource::from(event.source) as i32,
+            aircraft_type: event.aircraft_type,
+            position: event.position.map(Into::into),
+            velocity: event.velocity.map(Into::into),
+            intrusion: event.intrusion.map(Into::into),
+        }
+    }
+}
+
+impl From<rest_types::ZoneIntrusion> for ZoneIntrusion {
+    fn from(intrusion: rest_types::ZoneIntrusion) -> Self {
+        ZoneIntrusion {
+            zone_id: intrusion.zone_id,
+            zone_name: intrusion.zone_name,
+            predicted: intrusion.predicted,
+            seconds_to_entry: intrusion.seconds_to_entry,
+        }
+    }
+}
+
+impl TryFrom<SubscribeRequest> for EventFilter {
+    type Error = Status;
+
+    fn try_from(request: SubscribeRequest) -> Result<Self, Self::Error> {
+        let kinds = request
+            .kinds
+            .iter()
+            .map(|kind| TelemetryEventKind::try_from(*kind).map(Into::into))
+            .collect::<Result<Vec<rest_types::TelemetryEventKind>, _>>()
+            .map_err(|_| Status::invalid_argument("Unknown event kind."))?;
+
+        EventFilter::new(
+            request.identifiers,
+            kinds,
+            request.bounding_box.map(Into::into),
+        )
+        .ok_or_else(|| Status::invalid_argument("Invalid bounding box."))
+    }
+}
+
+/// Converts the result of a spatial query to a response
+#[cfg(not(feature = "stub_server"))]
+fn query_response(
+    result: Result<Vec<rest_types::AircraftState>, QueryError>,
+) -> Result<Response<AircraftStateList>, Status> {
+    match result {
+        Ok(states) => Ok(Response::new(AircraftStateList {
+            list: states.into_iter().map(AircraftState::from).collect(),
+        })),
+        Err(QueryError::Invalid) => Err(Status::invalid_argument("Invalid query.")),
+        Err(QueryError::Cache(e)) => {
+            grpc_error!("(query_response) could not query aircraft states: {e}.");
+            Err(Status::unavailable("Could not query cache."))
+        }
+    }
+}
 
 /// struct to implement the gRPC server functions
-#[derive(Debug, Default, Copy, Clone)]
-pub struct ServerImpl {}
+#[derive(Default, Clone)]
+pub struct ServerImpl {
+    /// Resources of the ingest handlers, shared with the REST server
+    #[cfg_attr(feature = "stub_server", allow(dead_code))]
+    ingest: Option<Ingest>,
+}
+
+impl Debug for ServerImpl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ServerImpl")
+            .field("ingest", &self.ingest.is_some())
+            .finish()
+    }
+}
+
+impl ServerImpl {
+    /// Creates a server processing telemetry with the given resources
+    pub fn new(ingest: Ingest) -> Self {
+        ServerImpl {
+            ingest: Some(ingest),
+        }
+    }
+
+    /// Returns the resources of the ingest handlers
+    #[cfg(not(feature = "stub_server"))]
+    fn ingest(&self) -> Result<&Ingest, Status> {
+        self.ingest.as_ref().ok_or_else(|| {
+            grpc_error!("(ingest) ingest resources were not set.");
+            Status::unavailable("Telemetry processing is not ready.")
+        })
+    }
+
+    /// Returns the per-aircraft tracking pool
+    #[cfg(not(feature = "stub_server"))]
+    fn aircraft_pool(&self) -> Result<TelemetryPool, Status> {
+        Ok(self.ingest()?.tlm_pools.aircraft.clone())
+    }
+}
 
 #[cfg(not(feature = "stub_server"))]
 #[tonic::async_trait]
@@ -32,21 +489,229 @@ impl RpcService for ServerImpl {
         let response = ReadyResponse { ready: true };
         Ok(Response::new(response))
     }
+
+    /// Returns the latest state of aircraft inside a bounding box
+    async fn query_bounding_box(
+        &self,
+        request: Request<BoundingBoxRequest>,
+    ) -> Result<Response<AircraftStateList>, Status> {
+        grpc_info!("(query_bounding_box) telemetry server.");
+        grpc_debug!("(query_bounding_box) request: {:?}", request);
+        authenticate_reader(&request)?;
+        let request = request.into_inner();
+        let bounding_box = spatial::BoundingBox {
+            min_latitude: request.min_latitude,
+            min_longitude: request.min_longitude,
+            max_latitude: request.max_latitude,
+            max_longitude: request.max_longitude,
+        };
+
+        let band = request.altitude_band.map(Into::into).unwrap_or_default();
+        let mut pool = self.aircraft_pool()?;
+        query_response(spatial::within_bounding_box(&mut pool, bounding_box, band).await)
+    }
+
+    /// Returns the latest state of aircraft within a radius of a point
+    async fn query_radius(
+        &self,
+        request: Request<RadiusRequest>,
+    ) -> Result<Response<AircraftStateList>, Status> {
+        grpc_info!("(query_radius) telemetry server.");
+        grpc_debug!("(query_radius) request: {:?}", request);
+        authenticate_reader(&request)?;
+        let request = request.into_inner();
+        let band = request.altitude_band.map(Into::into).unwrap_or_default();
+        let mut pool = self.aircraft_pool()?;
+        query_response(
+            spatial::within_radius(
+                &mut pool,
+                request.latitude,
+                request.longitude,
+                request.radius_meters,
+                band,
+            )
+            .await,
+        )
+    }
+
+    /// Returns the latest state of aircraft inside an altitude band
+    async fn query_altitude_band(
+        &self,
+        request: Request<AltitudeBand>,
+    ) -> Result<Response<AircraftStateList>, Status> {
+        grpc_info!("(query_altitude_band) telemetry server.");
+        grpc_debug!("(query_altitude_band) request: {:?}", request);
+        authenticate_reader(&request)?;
+        let band = request.into_inner().into();
+        let mut pool = self.aircraft_pool()?;
+        query_response(spatial::within_altitude_band(&mut pool, band).await)
+    }
+
+    /// Returns a page of the decoded track of an aircraft in a time range
+    async fn get_history(
+        &self,
+        request: Request<HistoryRequest>,
+    ) -> Result<Response<HistoryResponse>, Status> {
+        grpc_info!("(get_history) telemetry server.");
+        grpc_debug!("(get_history) request: {:?}", request);
+        authenticate_reader(&request)?;
+        let request = request.into_inner();
+        let query = rest_types::HistoryQuery::try_from(&request)?;
+        let ingest = self.ingest()?;
+        let mut tlm_pools = ingest.tlm_pools.clone();
+        match history::get_history(
+            &ingest.grpc_clients,
+            &mut tlm_pools.aircraft,
+            &mut tlm_pools.netrid,
+            &request.identifier,
+            query,
+        )
+        .await
+        {
+            Ok(history) => Ok(Response::new(history.into())),
+            Err(HistoryError::Invalid) => Err(Status::invalid_argument(
+                "Invalid time range, cursor or limit.",
+            )),
+            Err(HistoryError::Storage) => {
+                grpc_error!(
+                    "(get_history) could not get history of aircraft {}.",
+                    request.identifier
+                );
+                Err(Status::unavailable("Could not retrieve stored telemetry."))
+            }
+        }
+    }
+
+    type SubscribeTelemetryStream = TelemetryEventStream;
+
+    /// Returns a stream of live telemetry events matching a filter
+    async fn subscribe_telemetry(
+        &self,
+        request: Request<SubscribeRequest>,
+    ) -> Result<Response<Self::SubscribeTelemetryStream>, Status> {
+        grpc_info!("(subscribe_telemetry) telemetry server.");
+        grpc_debug!("(subscribe_telemetry) request: {:?}", request);
+        authenticate_reader(&request)?;
+        let filter = EventFilter::try_from(request.into_inner())?;
+        let events = get_event_bus()
+            .subscribe(filter)
+            .map(|event| Ok(TelemetryEvent::from(event)));
+
+        Ok(Response::new(Box::pin(events)))
+    }
+
+    /// Processes an ADS-B packet received by a trusted reporter
+    async fn push_adsb(
+        &self,
+        request: Request<PushAdsbRequest>,
+    ) -> Result<Response<PushResponse>, Status> {
+        grpc_info!("(push_adsb) telemetry server.");
+        grpc_debug!("(push_adsb) request: {:?}", request);
+        let claim = authenticate(&request)?;
+        let count = ingest_adsb(self.ingest()?, &claim, request.into_inner()).await?;
+        Ok(Response::new(PushResponse { count }))
+    }
+
+    /// Processes a remote id packet of an aircraft
+    async fn push_netrid(
+        &self,
+        request: Request<PushNetridRequest>,
+    ) -> Result<Response<PushResponse>, Status> {
+        grpc_info!("(push_netrid) telemetry server.");
+        grpc_debug!("(push_netrid) request: {:?}", request);
+        let claim = authenticate(&request)?;
+        let count = ingest_netrid(self.ingest()?, &claim, request.into_inner()).await?;
+        Ok(Response::new(PushResponse { count }))
+    }
+
+    /// Processes a stream of ADS-B and remote id packets
+    ///
+    /// Rejected packets are counted, and don't end the stream.
+    async fn push_telemetry_stream(
+        &self,
+        request: Request<tonic::Streaming<TelemetryPacket>>,
+    ) -> Result<Response<PushStreamResponse>, Status> {
+        grpc_info!("(push_telemetry_stream) telemetry server.");
+        let ingest = self.ingest()?;
+        let claim = authenticate(&request)?;
+        let mut packets = request.into_inner();
+        let mut response = PushStreamResponse::default();
+        while let Some(packet) = packets.message().await? {
+            let result = match packet.packet {
+                Some(Packet::Adsb(request)) => ingest_adsb(ingest, &claim, request).await,
+                Some(Packet::Netrid(request)) => ingest_netrid(ingest, &claim, request).await,
+                None => Err(Status::invalid_argument("Empty packet.")),
+            };
+
+            match result {
+                Ok(_) => response.processed += 1,
+                Err(e) => {
+                    grpc_debug!("(push_telemetry_stream) rejected packet: {e}.");
+                    response.rejected += 1;
+                }
+            }
+        }
+
+        grpc_debug!("(push_telemetry_stream) response: {:?}", response);
+        Ok(Response::new(response))
+    }
+
+    /// Returns the flight status of an aircraft, by aircraft or Remote ID session
+    async fn get_flight_status(
+        &self,
+        request: Request<FlightStatusRequest>,
+    ) -> Result<Response<FlightStatus>, Status> {
+        grpc_info!("(get_flight_status) telemetry server.");
+        grpc_debug!("(get_flight_status) request: {:?}", request);
+        authenticate_reader(&request)?;
+        let mut pool = self.aircraft_pool()?;
+        let identifier = match request.into_inner().target {
+            Some(Target::Identifier(identifier)) if !identifier.is_empty() => identifier,
+            Some(Target::SessionId(session_id)) if !session_id.is_empty() => {
+                let mut registry =
+                    IdentityRegistry::new(pool.clone(), self.ingest()?.grpc_clients.clone());
+                let session = Identifier::new(IdentifierKind::SessionId, &session_id);
+                registry
+                    .lookup(&session)
+                    .await
+                    .ok_or_else(|| Status::not_found("Unknown session."))?
+            }
+            _ => return Err(Status::invalid_argument("No aircraft or session.")),
+        };
+
+        match state::get_state(&mut pool, &identifier).await {
+            Ok(Some(state)) => Ok(Response::new(flight_status(state, Utc::now()))),
+            Ok(None) => Err(Status::not_found("Unknown aircraft.")),
+            Err(e) => {
+                grpc_error!("(get_flight_status) could not get state of {identifier}: {e}.");
+                Err(Status::unavailable("Could not query cache."))
+            }
+        }
+    }
 }
 
 /// Starts the grpc servers for this microservice using the provided configuration
 ///
 /// # Example:
 /// ```
+/// use svc_telemetry::grpc::client::GrpcClients;
 /// use svc_telemetry::grpc::server::grpc_server;
+/// use svc_telemetry::rest::api::ingest::Ingest;
 /// use svc_telemetry::Config;
-/// async fn example() -> Result<(), tokio::task::JoinError> {
+/// async fn example() -> Result<(), ()> {
 ///     let config = Config::default();
-///     tokio::spawn(grpc_server(config, None)).await;
+///     let grpc_clients = GrpcClients::default(config.clone());
+///     let ingest = Ingest::new(config.clone(), grpc_clients).await?;
+///     svc_telemetry::rest::api::jwt::init_secret();
+///     tokio::spawn(grpc_server(config, ingest, None));
 ///     Ok(())
 /// }
 /// ```
-pub async fn grpc_server(config: Config, shutdown_rx: Option<tokio::sync::oneshot::Receiver<()>>) {
+pub async fn grpc_server(
+    config: Config,
+    ingest: Ingest,
+    shutdown_rx: Option<tokio::sync::oneshot::Receiver<()>>,
+) {
     grpc_debug!("(grpc_server) entry.");
 
     // Grpc Server
@@ -59,7 +724,7 @@ pub async fn grpc_server(config: Config, shutdown_rx: Option<tokio::sync::onesho
         }
     };
 
-    let imp = ServerImpl::default();
+    let imp = ServerImpl::new(ingest);
     let (mut health_reporter, health_service) = tonic_health::server::health_reporter();
     health_reporter
         .set_serving::<RpcServiceServer<ServerImpl>>()
@@ -95,6 +760,125 @@ impl RpcService for ServerImpl {
         let response = ReadyResponse { ready: true };
         Ok(Response::new(response))
     }
+
+    async fn query_bounding_box(
+        &self,
+        request: Request<BoundingBoxRequest>,
+    ) -> Result<Response<AircraftStateList>, Status> {
+        grpc_warn!("(query_bounding_box MOCK) telemetry server.");
+        grpc_debug!("(query_bounding_box MOCK) request: {:?}", request);
+        Ok(Response::new(AircraftStateList { list: vec![] }))
+    }
+
+    async fn query_radius(
+        &self,
+        request: Request<RadiusRequest>,
+    ) -> Result<Response<AircraftStateList>, Status> {
+        grpc_warn!("(query_radius MOCK) telemetry server.");
+        grpc_debug!("(query_radius MOCK) request: {:?}", request);
+        Ok(Response::new(AircraftStateList { list: vec![] }))
+    }
+
+    async fn query_altitude_band(
+        &self,
+        request: Request<AltitudeBand>,
+    ) -> Result<Response<AircraftStateList>, Status> {
+        grpc_warn!("(query_altitude_band MOCK) telemetry server.");
+        grpc_debug!("(query_altitude_band MOCK) request: {:?}", request);
+        Ok(Response::new(AircraftStateList { list: vec![] }))
+    }
+
+    async fn get_history(
+        &self,
+        request: Request<HistoryRequest>,
+    ) -> Result<Response<HistoryResponse>, Status> {
+        grpc_warn!("(get_history MOCK) telemetry server.");
+        grpc_debug!("(get_history MOCK) request: {:?}", request);
+        let request = request.into_inner();
+        rest_types::HistoryQuery::try_from(&request)?;
+        Ok(Response::new(HistoryResponse {
+            identifier: request.identifier,
+            points: vec![],
+            next: None,
+        }))
+    }
+
+    type SubscribeTelemetryStream = TelemetryEventStream;
+
+    async fn subscribe_telemetry(
+        &self,
+        request: Request<SubscribeRequest>,
+    ) -> Result<Response<Self::SubscribeTelemetryStream>, Status> {
+        grpc_warn!("(subscribe_telemetry MOCK) telemetry server.");
+        grpc_debug!("(subscribe_telemetry MOCK) request: {:?}", request);
+        EventFilter::try_from(request.into_inner())?;
+        Ok(Response::new(Box::pin(futures::stream::empty())))
+    }
+
+    async fn push_adsb(
+        &self,
+        request: Request<PushAdsbRequest>,
+    ) -> Result<Response<PushResponse>, Status> {
+        grpc_warn!("(push_adsb MOCK) telemetry server.");
+        grpc_debug!("(push_adsb MOCK) request: {:?}", request);
+        adsb_receipt(&request.into_inner())?;
+        Ok(Response::new(PushResponse { count: 1 }))
+    }
+
+    async fn push_netrid(
+        &self,
+        request: Request<PushNetridRequest>,
+    ) -> Result<Response<PushResponse>, Status> {
+        grpc_warn!("(push_netrid MOCK) telemetry server.");
+        grpc_debug!("(push_netrid MOCK) request: {:?}", request);
+        let request = request.into_inner();
+        receipt(&request.subject, request.received, request.rssi)?;
+        Ok(Response::new(PushResponse { count: 1 }))
+    }
+
+    async fn push_telemetry_stream(
+        &self,
+        request: Request<tonic::Streaming<TelemetryPacket>>,
+    ) -> Result<Response<PushStreamResponse>, Status> {
+        grpc_warn!("(push_telemetry_stream MOCK) telemetry server.");
+        let mut packets = request.into_inner();
+        let mut response = PushStreamResponse::default();
+        while packets.message().await?.is_some() {
+            response.processed += 1;
+        }
+
+        Ok(Response::new(response))
+    }
+
+    async fn get_flight_status(
+        &self,
+        request: Request<FlightStatusRequest>,
+    ) -> Result<Response<FlightStatus>, Status> {
+        grpc_warn!("(get_flight_status MOCK) telemetry server.");
+        grpc_debug!("(get_flight_status MOCK) request: {:?}", request);
+        let identifier = match request.into_inner().target {
+            Some(Target::Identifier(identifier)) | Some(Target::SessionId(identifier))
+                if !identifier.is_empty() =>
+            {
+                identifier
+            }
+            _ => return Err(Status::invalid_argument("No aircraft or session.")),
+        };
+
+        let now = Utc::now();
+        let state = rest_types::AircraftState {
+            identifier,
+            aircraft_type: None,
+            position: None,
+            velocity: None,
+            source: None,
+            last_update: now,
+            status: rest_types::AircraftStatus::Active,
+            phase: Some(rest_types::FlightPhase::OnGround),
+        };
+
+        Ok(Response::new(flight_status(state, now)))
+    }
 }
 
 #[cfg(test)]
@@ -109,4 +893,227 @@ mod tests {
         let result: ReadyResponse = result.unwrap().into_inner();
         assert_eq!(result.ready, true);
     }
+
+    #[test]
+    fn test_aircraft_state_from() {
+        let now = Utc::now();
+        let state = rest_types::AircraftState {
+            identifier: "aircraft".to_string(),
+            aircraft_type: None,
+            position: Some(rest_types::AircraftStatePosition {
+                latitude: 52.,
+                longitude: 4.,
+                altitude_meters: 100.,
+                timestamp_network: now,
+                timestamp_asset: None,
+            }),
+            velocity: None,
+            source: Some(rest_types::TelemetrySource::Netrid),
+            last_update: now,
+            status: rest_types::AircraftStatus::Stale,
+            phase: Some(rest_types::FlightPhase::Landed),
+        };
+
+        let state = AircraftState::from(state);
+        assert_eq!(state.identifier, "aircraft");
+        assert_eq!(state.source, Some(TelemetrySource::Netrid as i32));
+        assert_eq!(state.status, AircraftStatus::Stale as i32);
+        assert_eq!(state.last_update, Some(timestamp(now)));
+        assert_eq!(state.phase, Some(FlightPhase::Landed as i32));
+
+        let position = state.position.unwrap();
+        assert_eq!(position.altitude_meters, 100.);
+        assert_eq!(position.timestamp_network, Some(timestamp(now)));
+        assert_eq!(position.timestamp_asset, None);
+        assert!(state.velocity.is_none());
+    }
+
+    #[test]
+    fn test_history_query_try_from() {
+        let from = Utc::now();
+        let to = from + chrono::Duration::minutes(30);
+        let mut request = HistoryRequest {
+            identifier: "aircraft".to_string(),
+            from: Some(timestamp(from)),
+            to: Some(timestamp(to)),
+            after: Some(timestamp(from)),
+            limit: None,
+        };
+
+        let query = rest_types::HistoryQuery::try_from(&request).unwrap();
+        assert_eq!(query.from, from);
+        assert_eq!(query.to, to);
+        assert_eq!(query.after, Some(from));
+        assert_eq!(query.limit, None);
+
+        request.to = None;
+        let result = rest_types::HistoryQuery::try_from(&request);
+        assert_eq!(result.unwrap_err().code(), tonic::Code::InvalidArgument);
+    }
+
+    #[test]
+    fn test_event_filter_try_from() {
+        let request = SubscribeRequest {
+            identifiers: vec!["aircraft".to_string()],
+            kinds: vec![TelemetryEventKind::Position as i32],
+            bounding_box: Some(BoundingBox {
+                min_latitude: 52.,
+                min_longitude: 4.,
+                max_latitude: 53.,
+                max_longitude: 5.,
+            }),
+        };
+
+        assert!(EventFilter::try_from(request.clone()).is_ok());
+
+        let unknown_kind = SubscribeRequest {
+            kinds: vec![7],
+            ..request.clone()
+        };
+
+        let result = EventFilter::try_from(unknown_kind);
+        assert_eq!(result.unwrap_err().code(), tonic::Code::InvalidArgument);
+
+        let inverted = SubscribeRequest {
+            bounding_box: Some(BoundingBox {
+                min_latitude: 53.,
+                min_longitude: 4.,
+                max_latitude: 52.,
+                max_longitude: 5.,
+            }),
+            ..request
+        };
+
+        let result = EventFilter::try_from(inverted);
+        assert_eq!(result.unwrap_err().code(), tonic::Code::InvalidArgument);
+    }
+
+    #[test]
+    fn test_telemetry_event_from() {
+        let event = rest_types::TelemetryEvent {
+            identifier: "aircraft".to_string(),
+            kind: rest_types::TelemetryEventKind::Id,
+            source: rest_types::TelemetrySource::Adsb,
+            aircraft_type: Some("Rotorcraft".to_string()),
+            position: None,
+            velocity: None,
+            intrusion: None,
+        };
+
+        let event = TelemetryEvent::from(event);
+        assert_eq!(event.identifier, "aircraft");
+        assert_eq!(event.kind, TelemetryEventKind::Id as i32);
+        assert_eq!(event.source, TelemetrySource::Adsb as i32);
+        assert_eq!(event.aircraft_type, Some("Rotorcraft".to_string()));
+        assert!(event.position.is_none());
+    }
+
+    #[test]
+    fn test_receipt() {
+        let received = Utc::now() - chrono::Duration::seconds(1);
+        let result = receipt("reporter", Some(timestamp(received)), Some(-60.)).unwrap();
+        assert_eq!(result.reporter_id, "reporter");
+        assert_eq!(result.received, received);
+        assert_eq!(result.rssi, Some(-60.));
+
+        let result = receipt("reporter", None, None).unwrap();
+        assert!(result.received > received);
+
+        let result = receipt("", None, None);
+        assert_eq!(result.unwrap_err().code(), tonic::Code::InvalidArgument);
+    }
+
+    #[test]
+    fn test_adsb_receipt() {
+        let mut request = PushAdsbRequest {
+            reporter_id: Uuid::nil().to_string(),
+            payload: vec![],
+            received: None,
+            rssi: None,
+        };
+
+        assert!(adsb_receipt(&request).is_ok());
+
+        request.reporter_id = "reporter".to_string();
+        let result = adsb_receipt(&request);
+        assert_eq!(result.unwrap_err().code(), tonic::Code::InvalidArgument);
+    }
+
+    #[cfg(not(feature = "stub_server"))]
+    #[test]
+    fn test_authenticate() {
+        crate::rest::api::jwt::JWT_SECRET.get_or_init(|| "test".to_string());
+
+        let request = Request::new(());
+        let result = authenticate(&request);
+        assert_eq!(result.unwrap_err().code(), tonic::Code::Unauthenticated);
+
+        let mut request = Request::new(());
+        request
+            .metadata_mut()
+            .insert("authorization", "Bearer invalid".parse().unwrap());
+        let result = authenticate(&request);
+        assert_eq!(result.unwrap_err().code(), tonic::Code::Unauthenticated);
+
+        let reporter_id = Uuid::nil().to_string();
+        let token = Claim::create(reporter_id.clone(), Role::Reporter).unwrap();
+        let mut request = Request::new(());
+        request
+            .metadata_mut()
+            .insert("authorization", format!("Bearer {token}").parse().unwrap());
+        let claim = authenticate(&request).unwrap();
+
+        assert!(authorize(&claim, Role::Reporter, &reporter_id).is_ok());
+        let result = authorize(&claim, Role::Reporter, "other");
+        assert_eq!(result.unwrap_err().code(), tonic::Code::PermissionDenied);
+        let result = authorize(&claim, Role::Aircraft, &reporter_id);
+        assert_eq!(result.unwrap_err().code(), tonic::Code::PermissionDenied);
+    }
+
+    #[cfg(not(feature = "stub_server"))]
+    #[test]
+    fn test_authenticate_reader() {
+        crate::rest::api::jwt::JWT_SECRET.get_or_init(|| "test".to_string());
+
+        let request = Request::new(());
+        let result = authenticate_reader(&request);
+        assert_eq!(result.unwrap_err().code(), tonic::Code::Unauthenticated);
+
+        for (role, expected) in [
+            (Role::Reporter, tonic::Code::Ok),
+            (Role::Aircraft, tonic::Code::PermissionDenied),
+        ] {
+            let token = Claim::create("subject".to_string(), role).unwrap();
+            let mut request = Request::new(());
+            request
+                .metadata_mut()
+                .insert("authorization", format!("Bearer {token}").parse().unwrap());
+            let code = match authenticate_reader(&request) {
+                Ok(_) => tonic::Code::Ok,
+                Err(e) => e.code(),
+            };
+            assert_eq!(code, expected);
+        }
+    }
+
+    #[test]
+    fn test_flight_status() {
+        let now = Utc::now();
+        let state = rest_types::AircraftState {
+            identifier: "aircraft".to_string(),
+            aircraft_type: None,
+            position: None,
+            velocity: None,
+            source: None,
+            last_update: now - chrono::Duration::milliseconds(2500),
+            status: rest_types::AircraftStatus::Active,
+            phase: Some(rest_types::FlightPhase::Cruise),
+        };
+
+        let status = flight_status(state, now);
+        assert_eq!(status.identifier, "aircraft");
+        assert_eq!(status.phase, Some(FlightPhase::Cruise as i32));
+        assert_eq!(status.status, AircraftStatus::Active as i32);
+        assert_eq!(status.seconds_since_update, 2.5);
+    }
 }
@@ -11,7 +11,9 @@ pub mod cache;
 pub mod config;
 pub mod grpc;
 pub mod msg;
+pub mod reputation;
 pub mod rest;
+pub mod tracking;
 
 pub use crate::config::Config;
 pub use clap::Parser;
@@ -31,15 +31,23 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let grpc_clients = grpc::client::GrpcClients::default(config.clone());
 
+    // Resources shared by the REST and gRPC servers
+    let Ok(ingest) = rest::api::ingest::Ingest::new(config.clone(), grpc_clients).await else {
+        panic!("(main) could not create ingest resources.");
+    };
+
+    // Both servers decode the same tokens
+    rest::api::jwt::init_secret();
+
     // REST Server
     tokio::spawn(rest::server::rest_server(
         config.clone(),
-        grpc_clients,
+        ingest.clone(),
         None,
     ));
 
     // GRPC Server
-    tokio::spawn(grpc::server::grpc_server(config, None)).await?;
+    tokio::spawn(grpc::server::grpc_server(config, ingest, None)).await?;
 
     info!("(main) server shutdown.");
     Ok(())
@@ -613,6 +613,22 @@ impl LocationMessage {
         ((altitude + 1000.0) * 2.0) as u16
     }
 
+    /// Decode the height above takeoff or ground (see Height Type)
+    pub fn decode_height(&self) -> Result<f32, LocationDecodeError> {
+        let height = (self.height as f32 * 0.5) - 1000.0;
+
+        if height == -1000.0 {
+            return Err(LocationDecodeError::UnknownAltitude);
+        }
+
+        Ok(height)
+    }
+
+    /// Encode the height above takeoff or ground
+    pub fn encode_height(height: f32) -> u16 {
+        ((height + 1000.0) * 2.0) as u16
+    }
+
     /// Decode the speed in meters per second
     pub fn decode_speed(&self) -> Result<f32, LocationDecodeError> {
         // Speed addition is added when the speed multiplier is 0.75
@@ -814,6 +830,7 @@ mod tests {
         let actual_speed = 30.0;
         let actual_vertical_speed = 0.0;
         let actual_altitude = 102.0;
+        let actual_height = 35.5;
 
         let actual_timestamp = Utc::now();
         let actual_track_direction = 190;
@@ -825,6 +842,7 @@ mod tests {
         let latitude = LocationMessage::encode_latitude(actual_latitude);
         let longitude = LocationMessage::encode_longitude(actual_longitude);
         let pressure_altitude = LocationMessage::encode_altitude(actual_altitude);
+        let height = LocationMessage::encode_height(actual_height);
         let timestamp = LocationMessage::encode_timestamp(actual_timestamp).unwrap();
 
         let msg = LocationMessage {
@@ -840,7 +858,7 @@ mod tests {
             longitude,
             pressure_altitude,
             geodetic_altitude: 0,
-            height: 0,
+            height,
             vertical_accuracy: VerticalAccuracyMeters::Lt1,
             horizontal_accuracy: HorizontalAccuracyMeters::Lt1,
             barometric_altitude_accuracy: VerticalAccuracyMeters::Lt1,
@@ -857,6 +875,7 @@ mod tests {
         assert_eq!(msg.decode_latitude(), actual_latitude);
         assert_eq!(msg.decode_longitude(), actual_longitude);
         assert_eq!(msg.decode_altitude(), Ok(actual_altitude));
+        assert_eq!(msg.decode_height(), Ok(actual_height));
         assert!(
             msg.decode_timestamp().unwrap() - actual_timestamp
                 < Duration::try_milliseconds(10).unwrap()
@@ -0,0 +1,346 @@
+//! Reputation of telemetry reporters
+//!
+//! Crowdsourced reporters may send stale, replayed or fabricated packets.
+//!  Each reporter is scored by how often its packets are corroborated by
+//!  other reporters within the deduplication window, how often its packets
+//!  decode to implausible positions, and the skew of its clock.
+//!
+//! The score weights the reporter's contribution towards accepting a packet.
+//!  Reporters with a low score are quarantined.
+//!
+//! Statistics are counted in hourly buckets and only the last day of buckets
+//!  is scored, so a reporter's past behavior doesn't outweigh its recent
+//!  behavior and a quarantined reporter can recover.
+
+#[macro_use]
+pub mod macros;
+
+use crate::cache::pool::TelemetryPool;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+/// Duration of a bucket of reporter statistics (seconds)
+const STATS_BUCKET_S: i64 = 60 * 60;
+
+/// Number of the most recent buckets which are scored
+const STATS_WINDOW_BUCKETS: i64 = 24;
+
+/// Buckets expire once they leave the scored window
+const STATS_EXPIRE_MS: u32 = (STATS_BUCKET_S * STATS_WINDOW_BUCKETS * 1000) as u32;
+
+/// Minimum number of reports before a reporter is scored.
+/// New reporters are given the benefit of the doubt.
+const MIN_REPORTS_FOR_SCORE: u64 = 20;
+
+/// Reporters scoring below this value are quarantined
+pub const QUARANTINE_SCORE: f32 = 0.2;
+
+/// Reporters scoring at or above this value have the full weight of
+///  a single reporter
+const TRUSTED_SCORE: f32 = 0.5;
+
+/// Mean clock skew at which a reporter gets no credit for its clock
+const MAX_MEAN_SKEW_MS: f32 = 5000.;
+
+/// Largest clock skew recorded for a single report, so that a single
+///  bad timestamp doesn't ruin a reporter's reputation
+const MAX_RECORDED_SKEW_MS: u64 = 60_000;
+
+/// Contribution of the corroboration rate to the score
+const SCORE_WEIGHT_CORROBORATION: f32 = 0.4;
+
+/// Contribution of the plausibility rate to the score
+const SCORE_WEIGHT_PLAUSIBILITY: f32 = 0.4;
+
+/// Contribution of clock accuracy to the score
+const SCORE_WEIGHT_CLOCK: f32 = 0.2;
+
+/// Tolerance when comparing accumulated weights
+const WEIGHT_EPSILON: f32 = 0.001;
+
+/// Lowest plausible altitude in meters (below the Dead Sea)
+const MIN_PLAUSIBLE_ALTITUDE_METERS: f64 = -500.;
+
+/// Highest plausible altitude in meters
+const MAX_PLAUSIBLE_ALTITUDE_METERS: f64 = 20_000.;
+
+/// Statistics of a reporter, used to compute its reputation
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReporterStats {
+    /// Unique packets reported
+    pub reports: u64,
+
+    /// Reports corroborated by at least one other reporter
+    pub corroborated: u64,
+
+    /// Reports which decoded to implausible data
+    pub implausible: u64,
+
+    /// Sum of the clock skew of all reports (ms)
+    pub skew_ms: u64,
+}
+
+/// Outcome of a new reporter's report of a packet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Acceptance {
+    /// Not enough reporters (by weight) have reported this packet yet
+    Pending,
+
+    /// This report made the packet acceptable
+    Accepted,
+
+    /// The packet was accepted by an earlier report
+    AlreadyAccepted,
+}
+
+impl ReporterStats {
+    // Name of each statistic in the cache
+    const FIELD_REPORTS: &'static str = "reports";
+    const FIELD_CORROBORATED: &'static str = "corroborated";
+    const FIELD_IMPLAUSIBLE: &'static str = "implausible";
+    const FIELD_SKEW_MS: &'static str = "skew_ms";
+
+    /// Create statistics from counters stored in the cache
+    pub fn from_counters(counters: &HashMap<String, u64>) -> Self {
+        let get = |field: &str| counters.get(field).copied().unwrap_or(0);
+        ReporterStats {
+            reports: get(Self::FIELD_REPORTS),
+            corroborated: get(Self::FIELD_CORROBORATED),
+            implausible: get(Self::FIELD_IMPLAUSIBLE),
+            skew_ms: get(Self::FIELD_SKEW_MS),
+        }
+    }
+
+    /// Mean clock skew of the reporter's reports (ms)
+    pub fn mean_skew_ms(&self) -> f32 {
+        if self.reports == 0 {
+            return 0.;
+        }
+
+        self.skew_ms as f32 / self.reports as f32
+    }
+
+    /// Score of the reporter, from 0.0 (untrusted) to 1.0 (trusted)
+    pub fn score(&self) -> f32 {
+        if self.reports < MIN_REPORTS_FOR_SCORE {
+            return 1.;
+        }
+
+        let reports = self.reports as f32;
+        let corroboration_rate = (self.corroborated as f32 / reports).min(1.);
+        let plausibility_rate = 1. - (self.implausible as f32 / reports).min(1.);
+        let clock_accuracy = 1. - (self.mean_skew_ms() / MAX_MEAN_SKEW_MS).min(1.);
+
+        SCORE_WEIGHT_CORROBORATION * corroboration_rate
+            + SCORE_WEIGHT_PLAUSIBILITY * plausibility_rate
+            + SCORE_WEIGHT_CLOCK * clock_accuracy
+    }
+
+    /// If the reporter's packets should be rejected
+    pub fn is_quarantined(&self) -> bool {
+        self.score() < QUARANTINE_SCORE
+    }
+
+    /// Contribution of a single report from this reporter towards the
+    ///  number of reporters needed to accept a packet
+    pub fn weight(&self) -> f32 {
+        if self.is_quarantined() {
+            return 0.;
+        }
+
+        (self.score() / TRUSTED_SCORE).min(1.)
+    }
+}
+
+/// Determines if a new report makes a packet acceptable
+///
+/// The total weight includes the weight of this report. Since the total
+///  weight is incremented atomically, only one report can cross the
+///  threshold of reporters needed.
+pub fn acceptance(total_weight: f32, weight: f32, n_reporters_needed: f32) -> Acceptance {
+    let previous_weight = total_weight - weight;
+    if total_weight + WEIGHT_EPSILON < n_reporters_needed {
+        Acceptance::Pending
+    } else if previous_weight + WEIGHT_EPSILON >= n_reporters_needed {
+        Acceptance::AlreadyAccepted
+    } else {
+        Acceptance::Accepted
+    }
+}
+
+/// Checks if a decoded position could belong to a real aircraft
+pub fn is_plausible_position(latitude: f64, longitude: f64, altitude_meters: f64) -> bool {
+    (-90. ..=90.).contains(&latitude)
+        && (-180. ..=180.).contains(&longitude)
+        && (MIN_PLAUSIBLE_ALTITUDE_METERS..=MAX_PLAUSIBLE_ALTITUDE_METERS)
+            .contains(&altitude_meters)
+}
+
+/// Bucket of reporter statistics containing the given time
+fn stats_bucket(time: DateTime<Utc>) -> i64 {
+    time.timestamp().div_euclid(STATS_BUCKET_S)
+}
+
+/// Key of a bucket of a reporter's statistics in the cache
+fn stats_key(reporter_id: &str, bucket: i64) -> String {
+    format!("{reporter_id}:reputation:{bucket}")
+}
+
+/// Keys of the buckets of a reporter's statistics in the scored window
+fn window_keys(reporter_id: &str, now: DateTime<Utc>) -> Vec<String> {
+    let current = stats_bucket(now);
+    (current - STATS_WINDOW_BUCKETS + 1..=current)
+        .map(|bucket| stats_key(reporter_id, bucket))
+        .collect()
+}
+
+/// Get the statistics of a reporter over the scored window
+///
+/// If the statistics can't be retrieved, the reporter is treated as a new reporter.
+pub async fn get_stats(pool: &mut TelemetryPool, reporter_id: &str) -> ReporterStats {
+    match pool
+        .sum_counters(&window_keys(reporter_id, Utc::now()))
+        .await
+    {
+        Ok(counters) => ReporterStats::from_counters(&counters),
+        Err(e) => {
+            reputation_warn!("(get_stats) could not get stats of reporter {reporter_id}: {e}.");
+            ReporterStats::default()
+        }
+    }
+}
+
+/// Increments a statistic of a reporter in the current bucket
+async fn increment(pool: &mut TelemetryPool, reporter_id: &str, field: &str, increment: u64) {
+    let key = stats_key(reporter_id, stats_bucket(Utc::now()));
+    if let Err(e) = pool
+        .increment_expiring_counter(&key, field, increment, STATS_EXPIRE_MS)
+        .await
+    {
+        reputation_warn!("(increment) could not increment {field} of reporter {reporter_id}: {e}.");
+    }
+}
+
+/// Records a new unique report from a reporter, along with the skew
+///  between the reporter's clock and our own
+pub async fn record_report(pool: &mut TelemetryPool, reporter_id: &str, received: DateTime<Utc>) {
+    let skew_ms = (Utc::now() - received)
+        .num_milliseconds()
+        .unsigned_abs()
+        .min(MAX_RECORDED_SKEW_MS);
+
+    reputation_debug!("(record_report) reporter {reporter_id} clock skew: {skew_ms} ms.");
+    increment(pool, reporter_id, ReporterStats::FIELD_REPORTS, 1).await;
+    increment(pool, reporter_id, ReporterStats::FIELD_SKEW_MS, skew_ms).await;
+}
+
+/// Records that a reporter's report was corroborated by another reporter
+pub async fn record_corroboration(pool: &mut TelemetryPool, reporter_id: &str) {
+    increment(pool, reporter_id, ReporterStats::FIELD_CORROBORATED, 1).await;
+}
+
+/// Records that a reporter's report decoded to implausible data
+pub async fn record_implausible(pool: &mut TelemetryPool, reporter_id: &str) {
+    reputation_info!("(record_implausible) implausible report from reporter {reporter_id}.");
+    increment(pool, reporter_id, ReporterStats::FIELD_IMPLAUSIBLE, 1).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_reporter_is_trusted() {
+        let stats = ReporterStats {
+            reports: MIN_REPORTS_FOR_SCORE - 1,
+            ..Default::default()
+        };
+
+        assert_eq!(stats.score(), 1.);
+        assert_eq!(stats.weight(), 1.);
+        assert!(!stats.is_quarantined());
+    }
+
+    #[test]
+    fn test_lone_reporter_is_trusted() {
+        // No other reporters to corroborate, but plausible data and an accurate clock
+        let stats = ReporterStats {
+            reports: 100,
+            corroborated: 0,
+            implausible: 0,
+            skew_ms: 100 * 50,
+        };
+
+        assert!(stats.score() >= TRUSTED_SCORE);
+        assert_eq!(stats.weight(), 1.);
+    }
+
+    #[test]
+    fn test_bad_reporter_is_quarantined() {
+        let stats = ReporterStats {
+            reports: 100,
+            corroborated: 0,
+            implausible: 90,
+            skew_ms: 100 * MAX_RECORDED_SKEW_MS,
+        };
+
+        assert!(stats.is_quarantined());
+        assert_eq!(stats.weight(), 0.);
+    }
+
+    #[test]
+    fn test_stats_from_counters() {
+        let counters = HashMap::from([
+            ("reports".to_string(), 10),
+            ("corroborated".to_string(), 5),
+            ("implausible".to_string(), 1),
+        ]);
+
+        let stats = ReporterStats::from_counters(&counters);
+        assert_eq!(stats.reports, 10);
+        assert_eq!(stats.corroborated, 5);
+        assert_eq!(stats.implausible, 1);
+        assert_eq!(stats.skew_ms, 0);
+    }
+
+    #[test]
+    fn test_acceptance() {
+        assert_eq!(acceptance(0.5, 0.5, 1.), Acceptance::Pending);
+        assert_eq!(acceptance(1., 0.5, 1.), Acceptance::Accepted);
+        assert_eq!(acceptance(1., 1., 1.), Acceptance::Accepted);
+        assert_eq!(acceptance(2., 1., 1.), Acceptance::AlreadyAccepted);
+        assert_eq!(acceptance(0., 0., 1.), Acceptance::Pending);
+    }
+
+    #[test]
+    fn test_window_keys() {
+        let now = DateTime::parse_from_rfc3339("2024-01-01T12:30:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        let keys = window_keys("reporter", now);
+        let current = stats_bucket(now);
+        assert_eq!(keys.len(), STATS_WINDOW_BUCKETS as usize);
+        assert_eq!(keys.last(), Some(&stats_key("reporter", current)));
+        assert_eq!(
+            keys.first(),
+            Some(&stats_key("reporter", current - STATS_WINDOW_BUCKETS + 1))
+        );
+
+        // The same hour shares a bucket
+        let later = now + chrono::Duration::minutes(29);
+        assert_eq!(stats_bucket(later), current);
+        assert_eq!(
+            stats_bucket(later + chrono::Duration::minutes(1)),
+            current + 1
+        );
+    }
+
+    #[test]
+    fn test_is_plausible_position() {
+        assert!(is_plausible_position(52.25, 3.91, 1000.));
+        assert!(!is_plausible_position(91., 3.91, 1000.));
+        assert!(!is_plausible_position(52.25, -181., 1000.));
+        assert!(!is_plausible_position(52.25, 3.91, 30_000.));
+    }
+}
@@ -1,26 +1,41 @@
 //! Endpoints for updating aircraft positions
 
+use super::ingest::Ingest;
+use super::rest_types::{EmergencyKind, TelemetrySource};
 use crate::cache::pool::{GisPool, TelemetryPool};
 use crate::cache::TelemetryPools;
-use crate::grpc::client::GrpcClients;
 use crate::msg::adsb::{
     decode_altitude, decode_cpr, decode_speed_direction, decode_vertical_speed,
     get_adsb_icao_address, get_adsb_message_type, ADSB_SIZE_BYTES,
 };
+use crate::reputation::{self, Acceptance};
+use crate::rest::api::jwt::Claim;
+use crate::rest::api::reporter::{
+    increment_counter, push_confirmations, record_corroborations, ReporterCounter, ReporterReceipt,
+    N_CONFIRMATIONS_MAX,
+};
+use crate::tracking::emergency;
+use crate::tracking::events;
+use crate::tracking::fusion::TrackSource;
+use crate::tracking::identity::{Identifier, IdentifierKind, IdentityRegistry};
+use crate::tracking::phase::{self, ReportedState};
+use crate::tracking::{kinematics, state};
 use adsb_deku::adsb::ME::AirbornePositionBaroAltitude as AirbornePosition;
 use adsb_deku::adsb::ME::AirborneVelocity as Velocity;
 use adsb_deku::adsb::ME::AircraftIdentification as Identification;
-use adsb_deku::adsb::{AirborneVelocitySubType, GroundSpeedDecoding, TypeCoding};
+use adsb_deku::adsb::ME::AircraftStatus as Status;
+use adsb_deku::adsb::{
+    AirborneVelocitySubType, AircraftStatus, EmergencyState, GroundSpeedDecoding, TypeCoding,
+};
 use adsb_deku::deku::DekuContainerRead;
 use adsb_deku::{CPRFormat, Sign};
 use svc_gis_client_grpc::prelude::types::*;
 use svc_storage_client_grpc::prelude::*;
 use svc_storage_client_grpc::resources::adsb;
 
-use axum::{body::Bytes, extract::Extension, Json};
+use axum::{body::Bytes, extract::Extension, http::HeaderMap, Json};
 use chrono::Utc;
 use hyper::StatusCode;
-use std::cmp::Ordering;
 
 /// ADSB entries in the cache will expire after 60 seconds
 const CACHE_EXPIRE_MS_ADSB: u32 = 10000;
@@ -28,12 +43,20 @@ const CACHE_EXPIRE_MS_ADSB: u32 = 10000;
 /// CPR lat/lon entries in the cache will expire after 1 second
 const CACHE_EXPIRE_MS_AIRCRAFT_CPR: u32 = 1000;
 
-/// Number of times a packet must be received
-///  from unique senders before it is considered valid
-const N_REPORTERS_NEEDED: u32 = 1;
+/// Possible errors accepting a position telemetry message
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PositionError {
+    /// The decoded position could not belong to a real aircraft,
+    ///  or the aircraft could not have reached it since its last position
+    Implausible,
+
+    /// The position could not be decoded
+    Failed,
+}
 
 /// Data structure of encoded position data
 struct GisPositionData {
+    identifier: String,
     icao: u32,
     lat_cpr: u32,
     lon_cpr: u32,
@@ -43,7 +66,7 @@ struct GisPositionData {
 
 /// Data structure of encoded velocity data
 struct GisVelocityData {
-    icao: u32,
+    identifier: String,
     st: u8,
     ew_sign: Sign,
     ew_vel: u16,
@@ -57,12 +80,15 @@ struct GisVelocityData {
 }
 
 /// Pushes an aircraft identifier message to the queue
+///
+/// Returns the pushed identifier.
 async fn gis_identifier_push(
     identifier: String,
     type_coding: TypeCoding,
     aircraft_category: u8,
+    mut aircraft_pool: TelemetryPool,
     mut gis_pool: GisPool,
-) -> Result<(), ()> {
+) -> Result<AircraftId, ()> {
     let aircraft_type: AircraftType = match (type_coding, aircraft_category) {
         (TypeCoding::D, _) => AircraftType::Other,
         (_, 0) => AircraftType::Other,
@@ -83,6 +109,9 @@ async fn gis_identifier_push(
         _ => AircraftType::Other,
     };
 
+    kinematics::set_aircraft_type(&mut aircraft_pool, &identifier, aircraft_type).await;
+    state::update_identity(&mut aircraft_pool, &identifier, aircraft_type).await;
+
     let item = AircraftId {
         identifier: Some(identifier),
         session_id: None,
@@ -92,21 +121,26 @@ async fn gis_identifier_push(
     };
 
     gis_pool
-        .push::<AircraftId>(item, REDIS_KEY_AIRCRAFT_ID)
-        .await
+        .push::<AircraftId>(item.clone(), REDIS_KEY_AIRCRAFT_ID)
+        .await?;
+
+    Ok(item)
 }
 
 ///
-/// Pushes a position telemetry message to the queue
+/// Decodes a position telemetry message and checks that it is plausible
 ///
-async fn gis_position_push(
+/// Returns the accepted position, or `None` if the message doesn't
+///  complete a position on its own. Accepted positions reach svc-gis
+///  through track fusion.
+async fn accept_position(
     data: GisPositionData,
-    mut tlm_pool: TelemetryPool,
-    mut gis_pool: GisPool,
-) -> Result<(), ()> {
+    mut tlm_pools: TelemetryPools,
+    mq_channel: &lapin::Channel,
+) -> Result<Option<AircraftPosition>, PositionError> {
     if data.odd_flag == CPRFormat::Odd {
-        rest_info!("(gis_position_push) received an odd flag CPR format message.");
-        return Ok(()); // ignore even CPR format messages
+        rest_info!("(accept_position) received an odd flag CPR format message.");
+        return Ok(None); // ignore even CPR format messages
     }
 
     // Get the even packet from the cache
@@ -116,43 +150,72 @@ async fn gis_position_push(
     ];
 
     let n_expected_results = keys.len();
-    let Ok(results) = tlm_pool.multiple_get::<u32>(keys).await else {
-        rest_warn!("(gis_position_push) could not get packet from cache.");
-        return Err(());
+    let Ok(results) = tlm_pools.adsb.multiple_get::<u32>(keys).await else {
+        rest_warn!("(accept_position) could not get packet from cache.");
+        return Err(PositionError::Failed);
     };
 
     if results.len() != n_expected_results {
-        rest_warn!("(gis_position_push) unexpected result from cache.");
-        return Err(());
+        rest_warn!("(accept_position) unexpected result from cache.");
+        return Err(PositionError::Failed);
     }
 
     let (e_lat_cpr, e_lon_cpr) = (results[0], results[1]);
 
     let Ok((latitude, longitude)) = decode_cpr(e_lat_cpr, e_lon_cpr, data.lat_cpr, data.lon_cpr)
     else {
-        rest_warn!("(gis_position_push) could not decode CPR.");
-        return Err(());
+        rest_warn!("(accept_position) could not decode CPR.");
+        return Err(PositionError::Failed);
     };
 
-    let identifier = format!("{:x}", data.icao);
+    let altitude_meters = decode_altitude(data.alt) as f64;
+    if !reputation::is_plausible_position(latitude, longitude, altitude_meters) {
+        rest_warn!(
+            "(accept_position) implausible position: {latitude}, {longitude}, {altitude_meters} m."
+        );
+        return Err(PositionError::Implausible);
+    }
+
     let item = AircraftPosition {
-        identifier: identifier.clone(),
+        identifier: data.identifier,
         position: Position {
             latitude,
             longitude,
-            altitude_meters: decode_altitude(data.alt) as f64,
+            altitude_meters,
         },
         timestamp_network: Utc::now(),
         timestamp_asset: None,
     };
 
-    gis_pool
-        .push::<AircraftPosition>(item, REDIS_KEY_AIRCRAFT_POSITION)
-        .await
+    //
+    // Reject positions which the aircraft couldn't physically reach
+    //
+    if let Err(reason) = kinematics::check_position(&mut tlm_pools.aircraft, &item).await {
+        kinematics::publish_rejected(mq_channel, item, reason).await;
+        return Err(PositionError::Implausible);
+    }
+
+    Ok(Some(item))
+}
+
+/// Emergency reported in an aircraft status message, by its emergency
+///  state or else its squawk
+fn status_emergency(status: &AircraftStatus) -> Option<EmergencyKind> {
+    match status.emergency_state {
+        EmergencyState::General => Some(EmergencyKind::Emergency),
+        EmergencyState::MinimumFuel => Some(EmergencyKind::MinimumFuel),
+        EmergencyState::NoCommunication => Some(EmergencyKind::RadioFailure),
+        EmergencyState::UnlawfulInterference => Some(EmergencyKind::UnlawfulInterference),
+        EmergencyState::DownedAircraft => Some(EmergencyKind::DownedAircraft),
+        _ => None,
+    }
+    .or_else(|| emergency::from_squawk(status.squawk))
 }
 
-/// Pushes a velocity telemetry message to the queue
-async fn gis_velocity_push(data: GisVelocityData, mut gis_pool: GisPool) -> Result<(), ()> {
+/// Decodes a velocity telemetry message
+///
+/// Accepted velocities reach svc-gis through track fusion.
+fn decode_velocity(data: GisVelocityData) -> Result<AircraftVelocity, ()> {
     let Ok((velocity_horizontal_ground_mps, track_angle_degrees)) = decode_speed_direction(
         data.st,
         data.ew_sign,
@@ -170,7 +233,7 @@ async fn gis_velocity_push(data: GisVelocityData, mut gis_pool: GisPool) -> Resu
     };
 
     let item = AircraftVelocity {
-        identifier: format!("{:x}", data.icao),
+        identifier: data.identifier,
         velocity_horizontal_ground_mps,
         velocity_horizontal_air_mps: None,
         velocity_vertical_mps,
@@ -179,78 +242,148 @@ async fn gis_velocity_push(data: GisVelocityData, mut gis_pool: GisPool) -> Resu
         timestamp_network: Utc::now(),
     };
 
-    gis_pool
-        .push::<AircraftVelocity>(item, REDIS_KEY_AIRCRAFT_VELOCITY)
-        .await
+    Ok(item)
 }
 
-/// Post ADS-B Telemetry
-/// Min 8 bytes, max 263 bytes
-#[utoipa::path(
-    post,
-    path = "/telemetry/adsb",
-    tag = "svc-telemetry",
-    request_body = Vec<u8>,
-    responses(
-        (status = 200, description = "Telemetry received."),
-        (status = 400, description = "Malformed packet."),
-        (status = 500, description = "Something went wrong."),
-        (status = 503, description = "Dependencies of svc-telemetry were down."),
-    )
-)]
-pub async fn adsb(
-    Extension(mut tlm_pools): Extension<TelemetryPools>,
-    Extension(gis_pool): Extension<GisPool>,
-    Extension(mq_channel): Extension<lapin::Channel>,
-    Extension(grpc_clients): Extension<GrpcClients>,
-    payload: Bytes,
-) -> Result<Json<u32>, StatusCode> {
-    rest_info!("(adsb) entry.");
+/// Processes an ADS-B packet received by a reporter
+///
+/// Returns the number of reporters of the packet.
+pub async fn process_adsb(
+    ingest: &Ingest,
+    receipt: ReporterReceipt,
+    payload: &[u8],
+) -> Result<u32, StatusCode> {
+    let Ingest {
+        mut tlm_pools,
+        gis_pool,
+        mq_channel,
+        grpc_clients,
+        config,
+        fusion,
+        event_bus,
+        ..
+    } = ingest.clone();
+
     //
     // ADS-B messages are 14 bytes long, small enough for a unique key
     // If the key is not in the cache, add it
-    // If the key is in the cache, increment the count
+    // If the key is in the cache, add the reporter to the set of
+    //  unique reporters for this packet
     //
-    let payload = <[u8; ADSB_SIZE_BYTES]>::try_from(payload.as_ref()).map_err(|_| {
-        rest_error!("(adsb) received ads-b message not {ADSB_SIZE_BYTES} bytes.");
+    let payload = <[u8; ADSB_SIZE_BYTES]>::try_from(payload).map_err(|_| {
+        rest_error!("(process_adsb) received ads-b message not {ADSB_SIZE_BYTES} bytes.");
         StatusCode::BAD_REQUEST
     })?;
 
+    let reporter_id = receipt.reporter_id.clone();
+    increment_counter(
+        tlm_pools.reporters.clone(),
+        &reporter_id,
+        ReporterCounter::Received,
+    )
+    .await;
+
+    //
+    // Reporters with a poor reputation are quarantined
+    //
+    let stats = reputation::get_stats(&mut tlm_pools.reporters, &reporter_id).await;
+    if stats.is_quarantined() {
+        rest_warn!(
+            "(process_adsb) reporter {reporter_id} is quarantined (score: {}).",
+            stats.score()
+        );
+        return Err(StatusCode::FORBIDDEN);
+    }
+
     let key = crate::cache::bytes_to_key(&payload);
-    let count = tlm_pools
+    let reporters = tlm_pools
         .adsb
-        .increment(&key, CACHE_EXPIRE_MS_ADSB)
+        .add_reporter(&key, &reporter_id, &receipt, CACHE_EXPIRE_MS_ADSB)
         .await
         .map_err(|e| {
-            rest_error!("(adsb) {e}");
+            rest_error!("(process_adsb) {e}");
             StatusCode::INTERNAL_SERVER_ERROR
         })?;
 
-    match count.cmp(&N_REPORTERS_NEEDED) {
-        Ordering::Less => {
-            rest_error!("(adsb) ADS-B reporter count should be impossible: {count}.");
-            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+    let count = reporters.count;
+    if !reporters.is_new_reporter {
+        rest_info!("(process_adsb) repeated ADS-B packet from reporter {reporter_id}, ignoring.");
+        increment_counter(
+            tlm_pools.reporters.clone(),
+            &reporter_id,
+            ReporterCounter::Duplicate,
+        )
+        .await;
+        return Ok(count);
+    }
+
+    //
+    // Corroborated reports improve the reputation of their reporters
+    //
+    reputation::record_report(&mut tlm_pools.reporters, &reporter_id, receipt.received).await;
+    record_corroborations(
+        &mut tlm_pools.adsb,
+        &mut tlm_pools.reporters,
+        &key,
+        &reporter_id,
+        count,
+    )
+    .await;
+
+    //
+    // Reports are weighted by the reputation of their reporter
+    //
+    let weight = stats.weight();
+    let total_weight = tlm_pools
+        .adsb
+        .add_weight(&key, weight, CACHE_EXPIRE_MS_ADSB)
+        .await
+        .map_err(|e| {
+            rest_error!("(process_adsb) {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let n_reporters_needed = config.adsb_reporters_needed;
+    match reputation::acceptance(total_weight, weight, n_reporters_needed as f32) {
+        Acceptance::Pending => {
+            rest_info!(
+                "(process_adsb) ADS-B reporter weight is less than needed: {total_weight}/{n_reporters_needed}."
+            );
+            return Ok(count);
         }
-        Ordering::Greater => {
-            rest_info!("(adsb) ADS-B reporter count is greater than needed: {count}.");
+        Acceptance::AlreadyAccepted => {
+            rest_info!(
+                "(process_adsb) ADS-B packet was already accepted, reporter count: {count}."
+            );
+
+            // The packet was already accepted, record this reporter's confirmation
+            increment_counter(
+                tlm_pools.reporters.clone(),
+                &reporter_id,
+                ReporterCounter::Confirmed,
+            )
+            .await;
+
+            if count <= N_CONFIRMATIONS_MAX {
+                push_confirmations(&key, vec![receipt], tlm_pools.adsb.clone());
+            }
 
-            // TODO(R4) push up to N reporter confirmations to svc-storage with user_ids
-            return Ok(Json(count));
+            return Ok(count);
         }
-        _ => (), // continue
+        Acceptance::Accepted => (), // continue
     }
 
     //
     // Deconstruct Packet
     //
     let frame = adsb_deku::Frame::from_bytes((&payload, 0)).map_err(|e| {
-        rest_info!("(adsb) could not parse ads-b message: {e}");
+        rest_info!("(process_adsb) could not parse ads-b message: {e}");
         StatusCode::BAD_REQUEST
     })?;
 
     let frame = frame.1;
     let adsb_deku::DF::ADSB(msg) = &frame.df else {
-        rest_info!("(adsb) received a non-ADSB format message.");
+        rest_info!("(process_adsb) received a non-ADSB format message.");
         return Err(StatusCode::BAD_REQUEST);
     };
 
@@ -264,13 +397,24 @@ pub async fn adsb(
     // The odd/even flag is used to differentiate between two packets
     //  that are part of the same message.
     let icao = get_adsb_icao_address(&msg.icao.0);
+    let icao_identifier = Identifier::new(IdentifierKind::Icao, &format!("{:x}", icao));
+    let mut registry = IdentityRegistry::new(tlm_pools.aircraft.clone(), grpc_clients.clone());
 
     match &msg.me {
         Identification(adsb_deku::adsb::Identification { tc, ca, cn }) => {
-            match gis_identifier_push(cn.clone(), *tc, *ca, gis_pool).await {
-                Ok(_) => rest_info!("(adsb) pushed position to queue."),
+            let callsign = Identifier::new(IdentifierKind::Callsign, cn);
+            let identifier = registry.link(&icao_identifier, &callsign).await;
+            match gis_identifier_push(identifier, *tc, *ca, tlm_pools.aircraft.clone(), gis_pool)
+                .await
+            {
+                Ok(item) => {
+                    rest_info!("(process_adsb) pushed aircraft id to queue.");
+                    if let Some(event) = events::identity_event(&item, TelemetrySource::Adsb) {
+                        event_bus.publish(event);
+                    }
+                }
                 Err(_) => {
-                    rest_error!("(adsb) could not push position to queue.");
+                    rest_error!("(process_adsb) could not push position to queue.");
                     return Err(StatusCode::INTERNAL_SERVER_ERROR);
                 }
             }
@@ -283,7 +427,7 @@ pub async fn adsb(
             ..
         }) => {
             let Some(alt) = alt else {
-                rest_info!("(adsb) no altitude in packet.");
+                rest_info!("(process_adsb) no altitude in packet.");
                 return Err(StatusCode::BAD_REQUEST);
             };
 
@@ -303,14 +447,15 @@ pub async fn adsb(
                 .multiple_set(keyvals, CACHE_EXPIRE_MS_AIRCRAFT_CPR)
                 .await
             {
-                Ok(_) => rest_info!("(adsb) added lat/lon to cache."),
+                Ok(_) => rest_info!("(process_adsb) added lat/lon to cache."),
                 Err(e) => {
-                    rest_error!("(adsb) could not add lat/lon to cache: {}.", e);
+                    rest_error!("(process_adsb) could not add lat/lon to cache: {}.", e);
                     return Err(StatusCode::INTERNAL_SERVER_ERROR);
                 }
             }
 
             let data = GisPositionData {
+                identifier: registry.resolve(&icao_identifier).await,
                 icao,
                 lat_cpr: *lat_cpr,
                 lon_cpr: *lon_cpr,
@@ -318,10 +463,36 @@ pub async fn adsb(
                 odd_flag: *odd_flag,
             };
 
-            match gis_position_push(data, tlm_pools.adsb, gis_pool).await {
-                Ok(_) => rest_info!("(adsb) pushed position to queue."),
-                Err(_) => {
-                    rest_error!("(adsb) could not push position to queue.");
+            match accept_position(data, tlm_pools.clone(), &mq_channel).await {
+                Ok(Some(position)) => {
+                    rest_info!("(process_adsb) accepted position.");
+                    let source = TrackSource::new(TelemetrySource::Adsb, &reporter_id);
+                    fusion.update_position(&position, source);
+                    event_bus.publish(events::position_event(&position, TelemetrySource::Adsb));
+                    state::update_position(
+                        &mut tlm_pools.aircraft,
+                        &position,
+                        TelemetrySource::Adsb,
+                    )
+                    .await;
+
+                    ingest
+                        .on_accepted(
+                            &position.identifier,
+                            TelemetrySource::Adsb,
+                            Some(&position),
+                            None,
+                            None,
+                        )
+                        .await;
+                }
+                Ok(None) => (),
+                Err(PositionError::Implausible) => {
+                    reputation::record_implausible(&mut tlm_pools.reporters, &reporter_id).await;
+                    return Err(StatusCode::BAD_REQUEST);
+                }
+                Err(PositionError::Failed) => {
+                    rest_error!("(process_adsb) could not decode position.");
                     return Err(StatusCode::INTERNAL_SERVER_ERROR);
                 }
             }
@@ -344,12 +515,12 @@ pub async fn adsb(
                 ns_vel,
             }) = sub_type
             else {
-                rest_info!("(adsb) no ground speed in packet.");
+                rest_info!("(process_adsb) no ground speed in packet.");
                 return Err(StatusCode::NOT_IMPLEMENTED);
             };
 
             let data = GisVelocityData {
-                icao,
+                identifier: registry.resolve(&icao_identifier).await,
                 st: *st,
                 ew_sign: *ew_sign,
                 ew_vel: *ew_vel,
@@ -362,17 +533,55 @@ pub async fn adsb(
                 // gnss_baro_diff: *gnss_baro_diff,
             };
 
-            match gis_velocity_push(data, gis_pool).await {
-                Ok(_) => rest_info!("(adsb) pushed velocity to queue."),
+            match decode_velocity(data) {
+                Ok(velocity) => {
+                    rest_info!("(process_adsb) accepted velocity.");
+                    let source = TrackSource::new(TelemetrySource::Adsb, &reporter_id);
+                    fusion.update_velocity(&velocity, source);
+                    event_bus.publish(events::velocity_event(&velocity, TelemetrySource::Adsb));
+                    let change = state::update_velocity(
+                        &mut tlm_pools.aircraft,
+                        &velocity,
+                        TelemetrySource::Adsb,
+                        ReportedState::default(),
+                    )
+                    .await;
+
+                    if let Some(change) = change {
+                        phase::publish_phase_change(&mq_channel, &change).await;
+                    }
+
+                    ingest
+                        .on_accepted(
+                            &velocity.identifier,
+                            TelemetrySource::Adsb,
+                            None,
+                            Some(&velocity),
+                            None,
+                        )
+                        .await;
+                }
                 Err(_) => {
-                    rest_error!("(adsb) could not push velocity to queue.");
-                    return Err(StatusCode::INTERNAL_SERVER_ERROR);
+                    rest_warn!("(process_adsb) could not decode velocity.");
+                    return Err(StatusCode::BAD_REQUEST);
                 }
             }
         }
+        Status(status) => {
+            let identifier = registry.resolve(&icao_identifier).await;
+            ingest
+                .on_accepted(
+                    &identifier,
+                    TelemetrySource::Adsb,
+                    None,
+                    None,
+                    status_emergency(status),
+                )
+                .await;
+        }
         _ => {
             // for now, reject non-position messages
-            rest_info!("(adsb) received an unrecognized message.");
+            rest_info!("(process_adsb) received an unrecognized message.");
             return Err(StatusCode::BAD_REQUEST);
         }
     };
@@ -391,8 +600,8 @@ pub async fn adsb(
         .await;
 
     match result {
-        Ok(_) => rest_info!("(adsb) telemetry pushed to RabbitMQ."),
-        Err(e) => rest_error!("(adsb) telemetry push to RabbitMQ failed: {e}."),
+        Ok(_) => rest_info!("(process_adsb) telemetry pushed to RabbitMQ."),
+        Err(e) => rest_error!("(process_adsb) telemetry push to RabbitMQ failed: {e}."),
     }
 
     //
@@ -410,12 +619,58 @@ pub async fn adsb(
     let client = &grpc_clients.storage.adsb;
 
     match client.insert(request).await {
-        Ok(_) => rest_info!("(adsb) telemetry pushed to svc-storage."),
+        Ok(_) => rest_info!("(process_adsb) telemetry pushed to svc-storage."),
         Err(e) => {
-            rest_error!("(adsb) telemetry push to svc-storage failed: {}.", e);
+            rest_error!(
+                "(process_adsb) telemetry push to svc-storage failed: {}.",
+                e
+            );
             return Err(StatusCode::INTERNAL_SERVER_ERROR);
         }
     }
 
-    Ok(Json(count))
+    increment_counter(
+        tlm_pools.reporters.clone(),
+        &reporter_id,
+        ReporterCounter::Accepted,
+    )
+    .await;
+
+    //
+    // Record the reporters that confirmed this packet
+    //
+    match tlm_pools.adsb.get_reporters::<ReporterReceipt>(&key).await {
+        Ok(receipts) => push_confirmations(&key, receipts, tlm_pools.adsb.clone()),
+        Err(e) => rest_warn!("(process_adsb) could not get reporters of packet from cache: {e}."),
+    }
+
+    Ok(count)
+}
+
+/// Post ADS-B Telemetry
+/// Min 8 bytes, max 263 bytes
+/// Requires a JWT issued to a reporter
+#[utoipa::path(
+    post,
+    path = "/telemetry/adsb",
+    tag = "svc-telemetry",
+    request_body = Vec<u8>,
+    responses(
+        (status = 200, description = "Telemetry received."),
+        (status = 400, description = "Malformed packet, or implausible position."),
+        (status = 401, description = "Missing or invalid token."),
+        (status = 403, description = "Token was not issued to a reporter, or the reporter is quarantined."),
+        (status = 500, description = "Something went wrong."),
+        (status = 503, description = "Dependencies of svc-telemetry were down."),
+    )
+)]
+pub async fn adsb(
+    Extension(ingest): Extension<Ingest>,
+    Extension(claim): Extension<Claim>,
+    headers: HeaderMap,
+    payload: Bytes,
+) -> Result<Json<u32>, StatusCode> {
+    rest_info!("(adsb) entry.");
+    let receipt = ReporterReceipt::from_headers(claim.sub, &headers);
+    process_adsb(&ingest, receipt, &payload).await.map(Json)
 }
@@ -0,0 +1,173 @@
+//! Latest state of aircraft
+
+use super::rest_types::{AircraftState, AltitudeBandQuery, BoundingBoxQuery, RadiusQuery};
+use crate::cache::TelemetryPools;
+use crate::tracking::spatial::{self, AltitudeBand, BoundingBox, QueryError};
+use crate::tracking::state;
+
+use axum::{
+    extract::{Extension, Path, Query},
+    Json,
+};
+use hyper::StatusCode;
+
+/// Get the latest state of an aircraft
+#[utoipa::path(
+    get,
+    path = "/telemetry/aircraft/{id}",
+    tag = "svc-telemetry",
+    params(
+        ("id" = String, Path, description = "Canonical identifier of the aircraft")
+    ),
+    responses(
+        (status = 200, description = "Latest state of the aircraft.", body = AircraftState),
+        (status = 401, description = "Missing or invalid token."),
+        (status = 403, description = "Token was not issued to a reporter."),
+        (status = 404, description = "No recent telemetry from this aircraft."),
+        (status = 500, description = "Something went wrong."),
+        (status = 503, description = "Dependencies of svc-telemetry were down."),
+    )
+)]
+pub async fn get_aircraft(
+    Extension(mut tlm_pools): Extension<TelemetryPools>,
+    Path(id): Path<String>,
+) -> Result<Json<AircraftState>, StatusCode> {
+    rest_info!("(get_aircraft) entry.");
+    match state::get_state(&mut tlm_pools.aircraft, &id).await {
+        Ok(Some(state)) => Ok(Json(state)),
+        Ok(None) => {
+            rest_info!("(get_aircraft) no state for aircraft {id}.");
+            Err(StatusCode::NOT_FOUND)
+        }
+        Err(e) => {
+            rest_error!("(get_aircraft) could not get state of aircraft {id}: {e}.");
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Converts the result of a spatial query to a response
+fn query_response(
+    result: Result<Vec<AircraftState>, QueryError>,
+) -> Result<Json<Vec<AircraftState>>, StatusCode> {
+    match result {
+        Ok(states) => Ok(Json(states)),
+        Err(QueryError::Invalid) => {
+            rest_warn!("(query_response) invalid query.");
+            Err(StatusCode::BAD_REQUEST)
+        }
+        Err(QueryError::Cache(e)) => {
+            rest_error!("(query_response) could not query aircraft states: {e}.");
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Get the latest state of all aircraft with recent telemetry,
+///  optionally only those inside an altitude band
+#[utoipa::path(
+    get,
+    path = "/telemetry/aircraft",
+    tag = "svc-telemetry",
+    params(AltitudeBandQuery),
+    responses(
+        (status = 200, description = "Latest state of each aircraft.", body = [AircraftState]),
+        (status = 401, description = "Missing or invalid token."),
+        (status = 403, description = "Token was not issued to a reporter."),
+        (status = 400, description = "Invalid altitude band."),
+        (status = 500, description = "Something went wrong."),
+        (status = 503, description = "Dependencies of svc-telemetry were down."),
+    )
+)]
+pub async fn list_aircraft(
+    Extension(mut tlm_pools): Extension<TelemetryPools>,
+    Query(query): Query<AltitudeBandQuery>,
+) -> Result<Json<Vec<AircraftState>>, StatusCode> {
+    rest_info!("(list_aircraft) entry.");
+    if query == AltitudeBandQuery::default() {
+        return query_response(
+            state::list_states(&mut tlm_pools.aircraft)
+                .await
+                .map_err(QueryError::from),
+        );
+    }
+
+    let band = AltitudeBand {
+        min_altitude_meters: query.min_altitude_meters,
+        max_altitude_meters: query.max_altitude_meters,
+    };
+
+    query_response(spatial::within_altitude_band(&mut tlm_pools.aircraft, band).await)
+}
+
+/// Get the latest state of aircraft inside a bounding box
+#[utoipa::path(
+    get,
+    path = "/telemetry/aircraft/bbox",
+    tag = "svc-telemetry",
+    params(BoundingBoxQuery),
+    responses(
+        (status = 200, description = "Latest state of each aircraft inside the box.", body = [AircraftState]),
+        (status = 401, description = "Missing or invalid token."),
+        (status = 403, description = "Token was not issued to a reporter."),
+        (status = 400, description = "Invalid bounding box or altitude band."),
+        (status = 500, description = "Something went wrong."),
+        (status = 503, description = "Dependencies of svc-telemetry were down."),
+    )
+)]
+pub async fn aircraft_in_bounding_box(
+    Extension(mut tlm_pools): Extension<TelemetryPools>,
+    Query(query): Query<BoundingBoxQuery>,
+) -> Result<Json<Vec<AircraftState>>, StatusCode> {
+    rest_info!("(aircraft_in_bounding_box) entry.");
+    let bounding_box = BoundingBox {
+        min_latitude: query.min_latitude,
+        min_longitude: query.min_longitude,
+        max_latitude: query.max_latitude,
+        max_longitude: query.max_longitude,
+    };
+
+    let band = AltitudeBand {
+        min_altitude_meters: query.min_altitude_meters,
+        max_altitude_meters: query.max_altitude_meters,
+    };
+
+    query_response(spatial::within_bounding_box(&mut tlm_pools.aircraft, bounding_box, band).await)
+}
+
+/// Get the latest state of aircraft within a radius of a point
+#[utoipa::path(
+    get,
+    path = "/telemetry/aircraft/radius",
+    tag = "svc-telemetry",
+    params(RadiusQuery),
+    responses(
+        (status = 200, description = "Latest state of each aircraft within the radius.", body = [AircraftState]),
+        (status = 401, description = "Missing or invalid token."),
+        (status = 403, description = "Token was not issued to a reporter."),
+        (status = 400, description = "Invalid point, radius or altitude band."),
+        (status = 500, description = "Something went wrong."),
+        (status = 503, description = "Dependencies of svc-telemetry were down."),
+    )
+)]
+pub async fn aircraft_in_radius(
+    Extension(mut tlm_pools): Extension<TelemetryPools>,
+    Query(query): Query<RadiusQuery>,
+) -> Result<Json<Vec<AircraftState>>, StatusCode> {
+    rest_info!("(aircraft_in_radius) entry.");
+    let band = AltitudeBand {
+        min_altitude_meters: query.min_altitude_meters,
+        max_altitude_meters: query.max_altitude_meters,
+    };
+
+    query_response(
+        spatial::within_radius(
+            &mut tlm_pools.aircraft,
+            query.latitude,
+            query.longitude,
+            query.radius_meters,
+            band,
+        )
+        .await,
+    )
+}
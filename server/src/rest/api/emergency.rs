@@ -0,0 +1,23 @@
+//! Active emergencies of aircraft
+
+use super::ingest::Ingest;
+use super::rest_types::Emergency;
+
+use axum::{extract::Extension, Json};
+use chrono::Utc;
+
+/// Get the active emergencies of all aircraft, oldest first
+#[utoipa::path(
+    get,
+    path = "/telemetry/emergencies",
+    tag = "svc-telemetry",
+    responses(
+        (status = 200, description = "Active emergencies.", body = [Emergency]),
+        (status = 401, description = "Missing or invalid token."),
+        (status = 403, description = "Token was not issued to a reporter."),
+    )
+)]
+pub async fn list_emergencies(Extension(ingest): Extension<Ingest>) -> Json<Vec<Emergency>> {
+    rest_info!("(list_emergencies) entry.");
+    Json(ingest.emergencies.active(Utc::now()))
+}
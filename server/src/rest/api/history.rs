@@ -0,0 +1,58 @@
+//! Historical tracks of aircraft
+
+use super::rest_types::{AircraftHistory, HistoryQuery};
+use crate::cache::TelemetryPools;
+use crate::grpc::client::GrpcClients;
+use crate::tracking::history::{self, HistoryError};
+
+use axum::{
+    extract::{Extension, Path, Query},
+    Json,
+};
+use hyper::StatusCode;
+
+/// Get the decoded track of an aircraft in a time range, in time order
+#[utoipa::path(
+    get,
+    path = "/telemetry/history/{id}",
+    tag = "svc-telemetry",
+    params(
+        ("id" = String, Path, description = "Canonical identifier of the aircraft"),
+        HistoryQuery
+    ),
+    responses(
+        (status = 200, description = "A page of the aircraft's track.", body = AircraftHistory),
+        (status = 401, description = "Missing or invalid token."),
+        (status = 403, description = "Token was not issued to a reporter."),
+        (status = 400, description = "Invalid time range, cursor or limit."),
+        (status = 500, description = "Something went wrong."),
+        (status = 503, description = "Dependencies of svc-telemetry were down."),
+    )
+)]
+pub async fn get_history(
+    Extension(grpc_clients): Extension<GrpcClients>,
+    Extension(mut tlm_pools): Extension<TelemetryPools>,
+    Path(id): Path<String>,
+    Query(query): Query<HistoryQuery>,
+) -> Result<Json<AircraftHistory>, StatusCode> {
+    rest_info!("(get_history) entry.");
+    match history::get_history(
+        &grpc_clients,
+        &mut tlm_pools.aircraft,
+        &mut tlm_pools.netrid,
+        &id,
+        query,
+    )
+    .await
+    {
+        Ok(history) => Ok(Json(history)),
+        Err(HistoryError::Invalid) => {
+            rest_warn!("(get_history) invalid query: {:?}.", query);
+            Err(StatusCode::BAD_REQUEST)
+        }
+        Err(HistoryError::Storage) => {
+            rest_error!("(get_history) could not get history of aircraft {id}.");
+            Err(StatusCode::SERVICE_UNAVAILABLE)
+        }
+    }
+}
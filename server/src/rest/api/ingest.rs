@@ -0,0 +1,245 @@
+//! Shared state of the telemetry ingest handlers
+//!
+//! ADS-B and Remote ID packets can be posted to the REST API by
+//!  authenticated reporters, or pushed over gRPC by internal services and
+//!  trusted gateways. Both paths process packets with the same resources,
+//!  so that deduplication, fusion and live events span all sources.
+//!  Accepted telemetry from every source is passed to the monitors
+//!  through [`Ingest::on_accepted`].
+
+use super::rest_types::{AircraftStatePosition, EmergencyKind, TelemetrySource};
+use crate::amqp::init_mq;
+use crate::cache::pool::{GisPool, TelemetryPool};
+use crate::cache::TelemetryPools;
+use crate::grpc::client::GrpcClients;
+use crate::tracking::conflict::{self, ConflictDetector, Separation};
+use crate::tracking::emergency::{self, EmergencyRegistry};
+use crate::tracking::events::{get_event_bus, EventBus};
+use crate::tracking::fusion::{publish_loop, TrackFusion};
+use crate::tracking::geofence::{self, GeofenceMonitor};
+use crate::tracking::link::{self, LinkMonitor, LinkTimeouts};
+use crate::tracking::weather::{self, WeatherMonitor, WeatherSettings};
+use crate::tracking::zones::{self, ZoneMonitor};
+use crate::Config;
+use chrono::Utc;
+use svc_gis_client_grpc::prelude::types::*;
+
+/// Resources used to process incoming telemetry packets
+#[derive(Clone)]
+pub struct Ingest {
+    /// Redis pools for deduplication, reporters and aircraft
+    pub tlm_pools: TelemetryPools,
+
+    /// Redis pool of the svc-gis queues
+    pub gis_pool: GisPool,
+
+    /// RabbitMQ channel
+    pub mq_channel: lapin::Channel,
+
+    /// gRPC clients of other services
+    pub grpc_clients: GrpcClients,
+
+    /// Service configuration
+    pub config: Config,
+
+    /// Fused aircraft tracks
+    pub fusion: TrackFusion,
+
+    /// Live telemetry events
+    pub event_bus: EventBus,
+
+    /// Vertiport geofences crossed by aircraft
+    pub geofences: GeofenceMonitor,
+
+    /// Restricted zones entered, or about to be entered, by aircraft
+    pub zones: ZoneMonitor,
+
+    /// Conflicts between aircraft
+    pub conflicts: ConflictDetector,
+
+    /// Time each aircraft was last seen from each source
+    pub links: LinkMonitor,
+
+    /// Active emergencies of aircraft
+    pub emergencies: EmergencyRegistry,
+
+    /// Turbulence and wind inferred from telemetry
+    pub weather: WeatherMonitor,
+}
+
+impl Ingest {
+    /// Connects to the cache and RabbitMQ, loads the geofences and zones,
+    ///  and starts the background tasks of the monitors
+    ///
+    /// The resources are shared by the REST and gRPC servers.
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: Needs running backends to work.
+    pub async fn new(config: Config, grpc_clients: GrpcClients) -> Result<Self, ()> {
+        rest_info!("(Ingest new) entry.");
+
+        // Redis Pools
+        let tlm_pools = TelemetryPools {
+            adsb: TelemetryPool::new(config.clone(), "tlm:adsb").await?,
+            netrid: TelemetryPool::new(config.clone(), "tlm:netrid").await?,
+            reporters: TelemetryPool::new(config.clone(), "tlm:reporters").await?,
+            aircraft: TelemetryPool::new(config.clone(), "tlm:aircraft").await?,
+        };
+
+        // Updates to svc-gis, pushed in batches
+        let gis_pool = GisPool::new(config.clone()).await?;
+        tokio::spawn(gis_pool.clone().flush_loop(config.gis_push_cadence_ms));
+
+        // RabbitMQ Channel
+        let mq_channel = init_mq(config.clone()).await.map_err(|e| {
+            rest_error!("(Ingest new) could not create RabbitMQ Channel: {e}");
+        })?;
+
+        // Fused aircraft tracks, pushed to svc-gis at a fixed interval
+        let fusion = TrackFusion::new();
+        tokio::spawn(publish_loop(
+            fusion.clone(),
+            gis_pool.clone(),
+            mq_channel.clone(),
+            config.fusion_interval_ms,
+        ));
+
+        // Aircraft whose signal was lost, checked in the background
+        let links = LinkMonitor::new(LinkTimeouts {
+            adsb_ms: config.adsb_signal_timeout_ms,
+            netrid_ms: config.netrid_signal_timeout_ms,
+        });
+        tokio::spawn(link::check_loop(links.clone(), mq_channel.clone()));
+
+        // Turbulence and wind, published in the background
+        let weather = WeatherMonitor::new(WeatherSettings {
+            cell_size_meters: config.weather_cell_size_meters as f64,
+            half_life_seconds: config.weather_half_life_seconds as f64,
+        });
+        tokio::spawn(weather::publish_loop(
+            weather.clone(),
+            mq_channel.clone(),
+            config.weather_publish_interval_ms,
+        ));
+
+        // Vertiport geofences
+        let geofences = match config.vertiport_geofences_file.as_str() {
+            "" => vec![],
+            path => geofence::load_geofences(path).map_err(|e| {
+                rest_error!("(Ingest new) could not load vertiport geofences from {path}: {e}.");
+            })?,
+        };
+
+        rest_info!(
+            "(Ingest new) loaded {} vertiport geofences.",
+            geofences.len()
+        );
+
+        // Restricted zones
+        let restricted_zones = match config.restricted_zones_file.as_str() {
+            "" => vec![],
+            path => zones::load_zones(path).map_err(|e| {
+                rest_error!("(Ingest new) could not load restricted zones from {path}: {e}.");
+            })?,
+        };
+
+        rest_info!(
+            "(Ingest new) loaded {} restricted zones.",
+            restricted_zones.len()
+        );
+
+        let zones = ZoneMonitor::new(restricted_zones, config.zone_lookahead_seconds);
+
+        // Conflicts between aircraft
+        let conflicts = ConflictDetector::new(Separation {
+            horizon_seconds: config.conflict_horizon_seconds as f64,
+            horizontal_meters: config.conflict_horizontal_separation_meters as f64,
+            vertical_meters: config.conflict_vertical_separation_meters as f64,
+        });
+
+        Ok(Ingest {
+            tlm_pools,
+            gis_pool,
+            mq_channel,
+            grpc_clients,
+            config,
+            fusion,
+            event_bus: get_event_bus().clone(),
+            geofences: GeofenceMonitor::new(geofences),
+            zones,
+            conflicts,
+            links,
+            emergencies: EmergencyRegistry::new(),
+            weather,
+        })
+    }
+
+    /// Passes an accepted position and/or velocity of an aircraft, and an
+    ///  emergency reported by the aircraft, to the monitors
+    ///
+    /// Every source calls this once per accepted packet, after fusion, live
+    ///  events and the aircraft state are updated. A velocity received with
+    ///  a position is applied before the position is checked for zone
+    ///  entries and conflicts, and after the position is recorded for
+    ///  weather. The aircraft is recorded as seen for every update.
+    pub async fn on_accepted(
+        &self,
+        identifier: &str,
+        source: TelemetrySource,
+        position: Option<&AircraftPosition>,
+        velocity: Option<&AircraftVelocity>,
+        reported: Option<EmergencyKind>,
+    ) {
+        let now = Utc::now();
+        let link_event = self.links.record(
+            identifier,
+            source,
+            position.map(AircraftStatePosition::from),
+            now,
+        );
+        link::publish_events(&self.mq_channel, link_event).await;
+
+        if let Some(velocity) = velocity {
+            self.zones.update_velocity(velocity);
+        }
+
+        if let Some(position) = position {
+            geofence::publish_events(&self.mq_channel, self.geofences.update(position)).await;
+            zones::publish_alerts(
+                &self.mq_channel,
+                &self.event_bus,
+                source,
+                self.zones.check_position(position),
+            )
+            .await;
+            conflict::publish_alerts(
+                &self.mq_channel,
+                self.conflicts.update_position(position, velocity),
+            )
+            .await;
+            self.weather.update_position(position, now);
+        } else if let Some(velocity) = velocity {
+            self.conflicts.update_velocity(velocity);
+        }
+
+        if let Some(velocity) = velocity {
+            self.weather.update_velocity(velocity, now);
+        }
+
+        let from_vertical_speed = velocity
+            .and_then(|velocity| emergency::from_vertical_speed(velocity.velocity_vertical_mps));
+        let raised: Vec<_> = [reported, from_vertical_speed]
+            .into_iter()
+            .flatten()
+            .filter_map(|kind| {
+                self.emergencies.raise(
+                    identifier,
+                    kind,
+                    source,
+                    position.map(AircraftStatePosition::from),
+                    now,
+                )
+            })
+            .collect();
+        emergency::publish_emergencies(&self.mq_channel, raised).await;
+    }
+}
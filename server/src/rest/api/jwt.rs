@@ -8,6 +8,12 @@
 //!  and will be used to identify the aircraft, so that all remote id
 //!  can be stored with the correct identifier.
 //!
+//! Networked nodes reporting telemetry of other aircraft (e.g. ADS-B
+//!  receivers) "login" with their reporter UUID and a secret provisioned
+//!  for them, and are given a JWT with the reporter role. This identifies
+//!  the reporter of each packet. Only the SHA-256 digest of each secret is
+//!  kept by this service.
+//!
 //! In the future, the login process will be replaced with a more secure
 //!  method of authentication where the aircraft cannot be spoofed. This
 //!  may be a PKI certificate that our network (as a certificate authority)
@@ -23,7 +29,12 @@ use axum::{
 use chrono::{Duration, Utc};
 use hyper::Request;
 use once_cell::sync::OnceCell;
+use rand::{distributions::Alphanumeric, Rng};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use super::rest_types::ReporterLogin;
 
 use axum_extra::extract::cookie::CookieJar;
 use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
@@ -34,9 +45,99 @@ const JWT_ENCRYPTION_TYPE: Algorithm = Algorithm::HS256;
 /// TODO(R5): This is a temporary solution, replace with PKI certificates
 pub static JWT_SECRET: OnceCell<String> = OnceCell::new();
 
+/// Sets JWT_SECRET to a random string, if it is not set yet
+///
+/// Called once at startup, before the REST and gRPC servers start
+///  decoding tokens.
+pub fn init_secret() {
+    JWT_SECRET.get_or_init(|| {
+        rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(42)
+            .map(char::from)
+            .collect()
+    });
+}
+
 /// JWT Expiration time in seconds
 const JWT_EXPIRE_SECONDS: i64 = 360; // TODO(R5): To configuration file
 
+/// SHA-256 digests of the secrets of reporters allowed to login
+pub static REPORTER_CREDENTIALS: OnceCell<HashMap<Uuid, [u8; 32]>> = OnceCell::new();
+
+/// Error loading the credentials of reporters
+#[derive(Debug)]
+pub enum CredentialsError {
+    /// The file could not be read
+    Read(std::io::Error),
+
+    /// The file is not a JSON object of reporter UUIDs and digests
+    Parse(serde_json::Error),
+
+    /// A reporter UUID or the digest of its secret is invalid
+    Invalid(String),
+}
+
+impl std::fmt::Display for CredentialsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CredentialsError::Read(e) => write!(f, "could not read file: {e}"),
+            CredentialsError::Parse(e) => write!(f, "could not parse file: {e}"),
+            CredentialsError::Invalid(reporter) => write!(f, "invalid credentials of {reporter}"),
+        }
+    }
+}
+
+/// Decodes a hexadecimal SHA-256 digest
+fn parse_digest(hex: &str) -> Option<[u8; 32]> {
+    if hex.len() != 64 || !hex.is_ascii() {
+        return None;
+    }
+
+    let mut digest = [0; 32];
+    for (byte, chunk) in digest.iter_mut().zip(hex.as_bytes().chunks(2)) {
+        *byte = u8::from_str_radix(std::str::from_utf8(chunk).ok()?, 16).ok()?;
+    }
+
+    Some(digest)
+}
+
+/// Parses reporter credentials, a JSON object of reporter UUIDs and the
+///  hexadecimal SHA-256 digests of their secrets
+fn parse_credentials(text: &str) -> Result<HashMap<Uuid, [u8; 32]>, CredentialsError> {
+    let credentials: HashMap<String, String> =
+        serde_json::from_str(text).map_err(CredentialsError::Parse)?;
+
+    credentials
+        .into_iter()
+        .map(
+            |(reporter_id, digest)| match (Uuid::parse_str(&reporter_id), parse_digest(&digest)) {
+                (Ok(uuid), Some(digest)) => Ok((uuid, digest)),
+                _ => Err(CredentialsError::Invalid(reporter_id)),
+            },
+        )
+        .collect()
+}
+
+/// Loads reporter credentials from a JSON file
+pub fn load_reporter_credentials(path: &str) -> Result<HashMap<Uuid, [u8; 32]>, CredentialsError> {
+    let text = std::fs::read_to_string(path).map_err(CredentialsError::Read)?;
+    parse_credentials(&text)
+}
+
+/// Checks the secret of a reporter against its provisioned digest
+fn verify_reporter(
+    credentials: &HashMap<Uuid, [u8; 32]>,
+    reporter_id: &Uuid,
+    secret: &str,
+) -> bool {
+    let Some(digest) = credentials.get(reporter_id) else {
+        return false;
+    };
+
+    openssl::memcmp::eq(&openssl::sha::sha256(secret.as_bytes()), digest)
+}
+
 /// Error Response
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ErrorResponse {
@@ -47,12 +148,26 @@ pub struct ErrorResponse {
     message: String,
 }
 
+/// Role of the JWT subject
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    /// An aircraft reporting its own telemetry
+    Aircraft,
+
+    /// A networked node reporting telemetry of other aircraft
+    Reporter,
+}
+
 /// JWT Information
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Claim {
     /// Subject
     pub sub: String,
 
+    /// Role of the subject
+    pub role: Role,
+
     /// Issued at time in seconds
     pub iat: usize,
 
@@ -62,7 +177,7 @@ pub struct Claim {
 
 impl Claim {
     /// Create and encode a JWT token
-    pub fn create(sub: String) -> Result<String, StatusCode> {
+    pub fn create(sub: String, role: Role) -> Result<String, StatusCode> {
         let header = Header::new(JWT_ENCRYPTION_TYPE);
         let iat = Utc::now().timestamp();
         let Ok(iat) = <usize>::try_from(iat) else {
@@ -83,7 +198,12 @@ impl Claim {
             return Err(StatusCode::INTERNAL_SERVER_ERROR);
         };
 
-        let claims = Claim { sub, iat, exp };
+        let claims = Claim {
+            sub,
+            role,
+            iat,
+            exp,
+        };
 
         let Some(jwt_secret) = JWT_SECRET.get() else {
             rest_error!("(Claim::create) JWT_SECRET not set.");
@@ -166,21 +286,63 @@ where
     Err((StatusCode::UNAUTHORIZED, Json(json_error)))
 }
 
-/// Authenticate a request with a JWT
-pub async fn auth<B>(
+/// Authenticate a request reading telemetry
+///
+/// Aircraft tokens are given to any identifier, so only provisioned
+///  reporters may read telemetry.
+pub async fn auth_reader<B>(
+    cookie_jar: CookieJar,
+    req: Request<B>,
+    next: Next<B>,
+) -> Result<Response, (StatusCode, Json<ErrorResponse>)>
+where
+    B: std::fmt::Debug,
+{
+    rest_info!("(auth_reader) authenticating read request.");
+    authenticate(cookie_jar, req, next, Some(Role::Reporter)).await
+}
+
+/// Authenticate a request with a JWT issued to an aircraft
+pub async fn auth_aircraft<B>(
+    cookie_jar: CookieJar,
+    req: Request<B>,
+    next: Next<B>,
+) -> Result<Response, (StatusCode, Json<ErrorResponse>)>
+where
+    B: std::fmt::Debug,
+{
+    rest_info!("(auth_aircraft) authenticating aircraft request.");
+    authenticate(cookie_jar, req, next, Some(Role::Aircraft)).await
+}
+
+/// Authenticate a request with a JWT issued to a reporter
+pub async fn auth_reporter<B>(
+    cookie_jar: CookieJar,
+    req: Request<B>,
+    next: Next<B>,
+) -> Result<Response, (StatusCode, Json<ErrorResponse>)>
+where
+    B: std::fmt::Debug,
+{
+    rest_info!("(auth_reporter) authenticating reporter request.");
+    authenticate(cookie_jar, req, next, Some(Role::Reporter)).await
+}
+
+/// Decode the JWT of a request and check the role of its subject, if required
+async fn authenticate<B>(
     cookie_jar: CookieJar,
     mut req: Request<B>,
     next: Next<B>,
+    required_role: Option<Role>,
 ) -> Result<Response, (StatusCode, Json<ErrorResponse>)>
 where
     B: std::fmt::Debug,
 {
-    rest_info!("(auth) authenticating request.");
     let token = get_token_from_cookie_jar(&req, &cookie_jar)?;
 
-    // rest_debug!("(auth) request token: {token}");
+    // rest_debug!("(authenticate) request token: {token}");
     let claim = Claim::decode(token).map_err(|e| {
-        rest_warn!("(auth) could not decode token: {e}");
+        rest_warn!("(authenticate) could not decode token: {e}");
         let json_error = ErrorResponse {
             status: "fail".to_string(),
             message: "Invalid token".to_string(),
@@ -188,7 +350,23 @@ where
         (StatusCode::UNAUTHORIZED, Json(json_error))
     })?;
 
-    rest_debug!("(auth) request claim: {:?}", claim);
+    rest_debug!("(authenticate) request claim: {:?}", claim);
+
+    if let Some(role) = required_role {
+        if claim.role != role {
+            rest_warn!(
+                "(authenticate) subject {} has role {:?}, expected {:?}.",
+                claim.sub,
+                claim.role,
+                role
+            );
+            let json_error = ErrorResponse {
+                status: "fail".to_string(),
+                message: "Insufficient role".to_string(),
+            };
+            return Err((StatusCode::FORBIDDEN, Json(json_error)));
+        }
+    }
 
     req.extensions_mut().insert(claim);
     Ok(next.run(req).await)
@@ -214,7 +392,41 @@ pub async fn login(identifier: Bytes) -> Result<Json<String>, StatusCode> {
         return Err(StatusCode::BAD_REQUEST);
     }
 
-    let token = Claim::create(identifier)?;
+    let token = Claim::create(identifier, Role::Aircraft)?;
+    Ok(Json(token))
+}
+
+/// Reporter Login
+#[utoipa::path(
+    get,
+    path = "/telemetry/login/reporter",
+    tag = "svc-telemetry",
+    request_body = ReporterLogin,
+    responses(
+        (status = 200, description = "Login successful, token returned."),
+        (status = 400, description = "Bad request."),
+        (status = 401, description = "Unknown reporter or wrong secret."),
+        (status = 500, description = "Something went wrong."),
+        (status = 503, description = "Dependencies of svc-telemetry were down."),
+    )
+)]
+pub async fn login_reporter(Json(login): Json<ReporterLogin>) -> Result<Json<String>, StatusCode> {
+    let Ok(reporter_id) = Uuid::parse_str(login.reporter_id.trim()) else {
+        rest_warn!("(login_reporter) invalid reporter UUID, failing login request.");
+        return Err(StatusCode::BAD_REQUEST);
+    };
+
+    let Some(credentials) = REPORTER_CREDENTIALS.get() else {
+        rest_error!("(login_reporter) REPORTER_CREDENTIALS not set.");
+        return Err(StatusCode::INTERNAL_SERVER_ERROR);
+    };
+
+    if !verify_reporter(credentials, &reporter_id, &login.secret) {
+        rest_warn!("(login_reporter) wrong credentials of reporter {reporter_id}.");
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    let token = Claim::create(reporter_id.to_string(), Role::Reporter)?;
     Ok(Json(token))
 }
 
@@ -233,13 +445,13 @@ mod tests {
             serde_json::to_string(&claim).unwrap();
         }
 
-        JWT_SECRET.set("test".to_string()).unwrap();
+        JWT_SECRET.get_or_init(|| "test".to_string());
 
         let router: Router = Router::new()
             .route("/", post(handler))
-            .route_layer(middleware::from_fn(auth));
+            .route_layer(middleware::from_fn(auth_reader));
 
-        let token = Claim::create("test".to_string()).unwrap();
+        let token = Claim::create("test".to_string(), Role::Reporter).unwrap();
         let req = Request::builder()
             .uri("/")
             .method(Method::POST)
@@ -250,4 +462,141 @@ mod tests {
 
         router.oneshot(req).await.unwrap();
     }
+
+    #[tokio::test]
+    async fn reporter_middleware_checks_role() {
+        async fn handler(Extension(claim): Extension<Claim>) {
+            assert_eq!(claim.role, Role::Reporter);
+        }
+
+        JWT_SECRET.get_or_init(|| "test".to_string());
+
+        let router: Router = Router::new()
+            .route("/", post(handler))
+            .route_layer(middleware::from_fn(auth_reporter));
+
+        for (role, expected) in [
+            (Role::Reporter, StatusCode::OK),
+            (Role::Aircraft, StatusCode::FORBIDDEN),
+        ] {
+            let token = Claim::create(Uuid::nil().to_string(), role).unwrap();
+            let req = Request::builder()
+                .uri("/")
+                .method(Method::POST)
+                .header("content-type", "application/octet-stream")
+                .header("Authorization", format!("Bearer {token}"))
+                .body(Bytes::from(vec![0x82]).into())
+                .unwrap();
+
+            let response = router.clone().oneshot(req).await.unwrap();
+            assert_eq!(response.status(), expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn reader_middleware_rejects_aircraft() {
+        async fn handler() {}
+
+        JWT_SECRET.get_or_init(|| "test".to_string());
+
+        let router: Router = Router::new()
+            .route("/", post(handler))
+            .route_layer(middleware::from_fn(auth_reader));
+
+        let token = Claim::create("aircraft".to_string(), Role::Aircraft).unwrap();
+        let req = Request::builder()
+            .uri("/")
+            .method(Method::POST)
+            .header("Authorization", format!("Bearer {token}"))
+            .body(Bytes::new().into())
+            .unwrap();
+
+        let response = router.oneshot(req).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[test]
+    fn decode_without_rest_server() {
+        init_secret();
+
+        let token = Claim::create("aircraft".to_string(), Role::Aircraft).unwrap();
+        let claim = Claim::decode(token).unwrap();
+        assert_eq!(claim.sub, "aircraft");
+        assert_eq!(claim.role, Role::Aircraft);
+    }
+
+    /// Digest of "secret"
+    const SECRET_DIGEST: &str = "2bb80d537b1da3e38bd30361aa855686bde0eacd7162fef6a25fe97bf527a25b";
+
+    #[tokio::test]
+    async fn login_reporter_requires_credentials() {
+        JWT_SECRET.get_or_init(|| "test".to_string());
+        REPORTER_CREDENTIALS.get_or_init(|| {
+            parse_credentials(&format!(r#"{{"{}": "{SECRET_DIGEST}"}}"#, Uuid::nil())).unwrap()
+        });
+
+        let login = |reporter_id: &str, secret: &str| {
+            login_reporter(Json(ReporterLogin {
+                reporter_id: reporter_id.to_string(),
+                secret: secret.to_string(),
+            }))
+        };
+
+        let result = login("not-a-uuid", "secret").await;
+        assert_eq!(result.unwrap_err(), StatusCode::BAD_REQUEST);
+
+        let result = login(&Uuid::nil().to_string(), "wrong").await;
+        assert_eq!(result.unwrap_err(), StatusCode::UNAUTHORIZED);
+
+        let result = login(&Uuid::new_v4().to_string(), "secret").await;
+        assert_eq!(result.unwrap_err(), StatusCode::UNAUTHORIZED);
+
+        let result = login(&Uuid::nil().to_string(), "secret").await;
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn parse_credentials_invalid() {
+        assert!(matches!(
+            parse_credentials(r#"{"not-a-uuid": "00"}"#),
+            Err(CredentialsError::Invalid(_))
+        ));
+        assert!(matches!(
+            parse_credentials(&format!(r#"{{"{}": "00"}}"#, Uuid::nil())),
+            Err(CredentialsError::Invalid(_))
+        ));
+        assert!(matches!(
+            parse_credentials("[]"),
+            Err(CredentialsError::Parse(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn aircraft_middleware_checks_role() {
+        async fn handler(Extension(claim): Extension<Claim>) {
+            assert_eq!(claim.role, Role::Aircraft);
+        }
+
+        JWT_SECRET.get_or_init(|| "test".to_string());
+
+        let router: Router = Router::new()
+            .route("/", post(handler))
+            .route_layer(middleware::from_fn(auth_aircraft));
+
+        for (role, expected) in [
+            (Role::Aircraft, StatusCode::OK),
+            (Role::Reporter, StatusCode::FORBIDDEN),
+        ] {
+            let token = Claim::create("aircraft".to_string(), role).unwrap();
+            let req = Request::builder()
+                .uri("/")
+                .method(Method::POST)
+                .header("Authorization", format!("Bearer {token}"))
+                .body(Bytes::from(vec![0x82]).into())
+                .unwrap();
+
+            let response = router.clone().oneshot(req).await.unwrap();
+            assert_eq!(response.status(), expected);
+        }
+    }
 }
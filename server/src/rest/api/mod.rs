@@ -1,9 +1,16 @@
 //! API
 
 pub mod adsb;
+pub mod aircraft;
+pub mod emergency;
 pub mod health;
+pub mod history;
+pub mod ingest;
 pub mod jwt;
 pub mod netrid;
+pub mod reporter;
+pub mod stream;
+pub mod websocket;
 
 /// Types Used in REST Messages
 pub mod rest_types {
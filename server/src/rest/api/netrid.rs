@@ -3,29 +3,44 @@
 //!  It will be required for use of U-Space airspace by unmanned aircraft.
 //! Endpoints for updating aircraft positions
 
-use crate::cache::pool::GisPool;
-use crate::cache::TelemetryPools;
+use super::ingest::Ingest;
+use super::rest_types::TelemetrySource;
+use crate::cache::pool::{GisPool, TelemetryPool};
 use crate::msg::netrid::{
     BasicMessage, Frame, IdType, LocationMessage, MessageType, UaType as NetridAircraftType,
 };
+use crate::reputation::{self, Acceptance};
+use crate::rest::api::reporter::{
+    push_confirmations, record_corroborations, ReporterReceipt, N_CONFIRMATIONS_MAX,
+};
+use crate::tracking::emergency;
+use crate::tracking::events::{self, EventBus};
+use crate::tracking::fusion::{TrackFusion, TrackSource};
+use crate::tracking::history;
+use crate::tracking::identity::{Identifier, IdentifierKind, IdentityRegistry};
+use crate::tracking::phase::{self, ReportedState};
+use crate::tracking::{kinematics, state};
 use svc_gis_client_grpc::prelude::types::*;
 
-use axum::{body::Bytes, extract::Extension, Json};
+use axum::{body::Bytes, extract::Extension, http::HeaderMap, Json};
 use chrono::Utc;
 use hyper::StatusCode;
 use packed_struct::PackedStruct;
-use std::cmp::Ordering;
 
 /// Remote ID entries in the cache will expire after 60 seconds
 const CACHE_EXPIRE_MS_NETRID: u32 = 10000;
 
-/// Number of times a packet must be received
-///  from unique senders before it is considered valid
-const N_REPORTERS_NEEDED: u32 = 1;
-
 /// Length of a remote id packet
 const REMOTE_ID_PACKET_LENGTH: usize = 25;
 
+/// Number of unique reporters needed before a remote id packet is accepted
+///
+/// Only the aircraft itself reports its remote id packets, so a higher
+///  number could never be reached. Reports are weighted by reputation like
+///  ADS-B reports, so the packets of an aircraft with a poor reputation are
+///  not accepted.
+const NETRID_REPORTERS_NEEDED: u32 = 1;
+
 impl From<NetridAircraftType> for AircraftType {
     fn from(t: NetridAircraftType) -> Self {
         match t {
@@ -53,18 +68,14 @@ impl From<NetridAircraftType> for AircraftType {
 async fn process_basic_message(
     jwt_identifier: String,
     message: BasicMessage,
+    mut aircraft_pool: TelemetryPool,
+    mut registry: IdentityRegistry,
     mut gis_pool: GisPool,
     mq_channel: lapin::Channel,
+    event_bus: &EventBus,
 ) -> Result<(), StatusCode> {
     rest_debug!("(process_basic_message) entry.");
     let aircraft_type = AircraftType::from(message.ua_type);
-    let mut id_item = AircraftId {
-        identifier: Some(jwt_identifier),
-        session_id: None,
-        aircraft_type,
-        timestamp_network: Utc::now(),
-        timestamp_asset: None,
-    };
 
     let Ok(identifier) = String::from_utf8(message.uas_id.to_vec()) else {
         rest_warn!("(process_basic_message) could not parse identifier to string.");
@@ -73,11 +84,37 @@ async fn process_basic_message(
 
     let identifier = identifier.trim().to_string();
 
-    match message.id_type {
-        IdType::UtmAssigned => id_item.session_id = Some(identifier),
-        IdType::SpecificSession => id_item.session_id = Some(identifier),
-        _ => id_item.identifier = Some(identifier),
-    }
+    //
+    // Link the UAS ID to the aircraft logged in with this JWT
+    //
+    let subject = Identifier::new(IdentifierKind::Subject, &jwt_identifier);
+    let (kind, session_id) = match message.id_type {
+        IdType::UtmAssigned => (Some(IdentifierKind::SessionId), Some(identifier.clone())),
+        IdType::SpecificSession => (Some(IdentifierKind::SessionId), Some(identifier.clone())),
+        IdType::SerialNumber => (Some(IdentifierKind::UasSerial), None),
+        IdType::CaaAssigned => (Some(IdentifierKind::Callsign), None),
+        _ => (None, None),
+    };
+
+    let canonical_id = match kind {
+        Some(kind) => {
+            registry
+                .link(&subject, &Identifier::new(kind, &identifier))
+                .await
+        }
+        None => registry.resolve(&subject).await,
+    };
+
+    kinematics::set_aircraft_type(&mut aircraft_pool, &canonical_id, aircraft_type).await;
+    state::update_identity(&mut aircraft_pool, &canonical_id, aircraft_type).await;
+
+    let id_item = AircraftId {
+        identifier: Some(canonical_id),
+        session_id,
+        aircraft_type,
+        timestamp_network: Utc::now(),
+        timestamp_asset: None,
+    };
 
     gis_pool
         .push::<AircraftId>(id_item.clone(), REDIS_KEY_AIRCRAFT_ID)
@@ -88,6 +125,9 @@ async fn process_basic_message(
         })?;
 
     rest_debug!("(process_basic_message) pushed aircraft id to redis.");
+    if let Some(event) = events::identity_event(&id_item, TelemetrySource::Netrid) {
+        event_bus.publish(event);
+    }
 
     //
     // Send Telemetry to RabbitMQ
@@ -114,13 +154,20 @@ async fn process_basic_message(
     Ok(())
 }
 
-/// Processes a basic remote id message type
+/// Processes a location remote id message type, returning the accepted
+///  position and velocity
+///
+/// Accepted positions and velocities reach svc-gis through track fusion.
 async fn process_location_message(
     identifier: String,
+    reporter_id: &str,
     message: LocationMessage,
-    mut gis_pool: GisPool,
+    mut aircraft_pool: TelemetryPool,
+    mut reporters_pool: TelemetryPool,
     mq_channel: lapin::Channel,
-) -> Result<(), StatusCode> {
+    fusion: TrackFusion,
+    event_bus: &EventBus,
+) -> Result<(AircraftPosition, AircraftVelocity), StatusCode> {
     //
     // TODO(R5): Decide what to do when a field is UNKNOWN
     //  Reject the whole message? Use the 'unknown' value (e.g. 63.0 for vertical rate)?
@@ -150,6 +197,9 @@ async fn process_location_message(
     let latitude = message.decode_latitude();
     let longitude = message.decode_longitude();
 
+    // Received together, so fusion applies the velocity at this position
+    let timestamp_network = Utc::now();
+
     let position_item = AircraftPosition {
         identifier: identifier.clone(),
         position: Position {
@@ -157,7 +207,7 @@ async fn process_location_message(
             longitude,
             altitude_meters: altitude_meters as f64,
         },
-        timestamp_network: Utc::now(),
+        timestamp_network,
         timestamp_asset,
     };
 
@@ -168,28 +218,47 @@ async fn process_location_message(
         velocity_horizontal_air_mps: None,
         track_angle_degrees: message.decode_direction() as f32,
         timestamp_asset,
-        timestamp_network: Utc::now(),
+        timestamp_network,
     };
 
-    gis_pool
-        .push::<AircraftPosition>(position_item.clone(), REDIS_KEY_AIRCRAFT_POSITION)
-        .await
-        .map_err(|_| {
-            rest_warn!("(process_location_message) could not push aircraft position to cache.");
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?; // TODO(R5): Do we want to bail here or still send the velocity to postgis?
+    //
+    // Reject positions which the aircraft couldn't physically reach
+    //
+    if let Err(reason) = kinematics::check_position(&mut aircraft_pool, &position_item).await {
+        kinematics::publish_rejected(&mq_channel, position_item, reason).await;
+        reputation::record_implausible(&mut reporters_pool, reporter_id).await;
+        return Err(StatusCode::BAD_REQUEST);
+    }
 
-    rest_debug!("(process_location_message) pushed aircraft position to redis.");
+    // The aircraft reports its own position
+    let source = TrackSource::new(TelemetrySource::Netrid, &position_item.identifier);
+    fusion.update_position(&position_item, source.clone());
+    fusion.update_velocity(&velocity_item, source);
+    event_bus.publish(events::position_event(
+        &position_item,
+        TelemetrySource::Netrid,
+    ));
+    event_bus.publish(events::velocity_event(
+        &velocity_item,
+        TelemetrySource::Netrid,
+    ));
+    state::update_position(&mut aircraft_pool, &position_item, TelemetrySource::Netrid).await;
+    let reported = ReportedState {
+        operational_status: Some(message.operational_status),
+        height_meters: message.decode_height().ok().map(|height| height as f64),
+    };
 
-    let _ = gis_pool
-        .push::<AircraftVelocity>(velocity_item.clone(), REDIS_KEY_AIRCRAFT_VELOCITY)
-        .await
-        .map_err(|_| {
-            rest_warn!("(process_location_message) could not push aircraft velocity to cache.");
-            // StatusCode::INTERNAL_SERVER_ERROR
-        });
+    let change = state::update_velocity(
+        &mut aircraft_pool,
+        &velocity_item,
+        TelemetrySource::Netrid,
+        reported,
+    )
+    .await;
 
-    rest_debug!("(process_location_message) pushed aircraft velocity to redis.");
+    if let Some(change) = change {
+        phase::publish_phase_change(&mq_channel, &change).await;
+    }
 
     //
     // Send Telemetry to RabbitMQ
@@ -239,103 +308,266 @@ async fn process_location_message(
         rest_warn!("(process_location_message) could not serialize velocity item.");
     }
 
-    Ok(())
+    Ok((position_item, velocity_item))
 }
 
-/// Remote ID
-#[utoipa::path(
-    post,
-    path = "/telemetry/netrid",
-    tag = "svc-telemetry",
-    request_body = Vec<u8>,
-    responses(
-        (status = 200, description = "Telemetry received."),
-        (status = 400, description = "Malformed packet."),
-        (status = 500, description = "Something went wrong."),
-        (status = 503, description = "Dependencies of svc-telemetry were down."),
-    )
-)]
-pub async fn network_remote_id(
-    Extension(mut tlm_pools): Extension<TelemetryPools>,
-    Extension(gis_pool): Extension<GisPool>,
-    Extension(mq_channel): Extension<lapin::Channel>,
-    Extension(claim): Extension<crate::rest::api::jwt::Claim>,
-    payload: Bytes,
-) -> Result<Json<u32>, StatusCode> {
-    rest_info!("(network_remote_id) entry.");
-
-    let payload = <[u8; REMOTE_ID_PACKET_LENGTH]>::try_from(payload.as_ref()).map_err(|_| {
-        rest_warn!("(network_remote_id) could not parse payload.");
+/// Processes a remote id packet reported by an aircraft
+///
+/// The reporter of the packet is the aircraft itself. Returns the number
+///  of reporters of the packet.
+pub async fn process_netrid(
+    ingest: &Ingest,
+    receipt: ReporterReceipt,
+    payload: &[u8],
+) -> Result<u32, StatusCode> {
+    let Ingest {
+        mut tlm_pools,
+        gis_pool,
+        mq_channel,
+        grpc_clients,
+        fusion,
+        event_bus,
+        ..
+    } = ingest.clone();
+
+    let payload = <[u8; REMOTE_ID_PACKET_LENGTH]>::try_from(payload).map_err(|_| {
+        rest_warn!("(process_netrid) could not parse payload.");
         StatusCode::BAD_REQUEST
     })?;
 
     let Ok(frame) = Frame::unpack(&payload) else {
-        rest_warn!("(network_remote_id) could not parse payload.");
+        rest_warn!("(process_netrid) could not parse payload.");
         return Err(StatusCode::BAD_REQUEST);
     };
 
+    //
+    // Reporters with a poor reputation are quarantined
+    //
+    let stats = reputation::get_stats(&mut tlm_pools.reporters, &receipt.reporter_id).await;
+    if stats.is_quarantined() {
+        rest_warn!(
+            "(process_netrid) reporter {} is quarantined (score: {}).",
+            receipt.reporter_id,
+            stats.score()
+        );
+        return Err(StatusCode::FORBIDDEN);
+    }
+
     //
     // BasicMessage is identical throughout the whole flight,
     //  don't want to toss repeats of the same message
     let mut count = 1;
-    if frame.header.message_type != MessageType::Basic {
-        let key = crate::cache::bytes_to_key(&payload);
-        count = tlm_pools
+    let key = crate::cache::bytes_to_key(&payload);
+    let is_deduplicated = frame.header.message_type != MessageType::Basic;
+    if is_deduplicated {
+        let reporters = tlm_pools
             .netrid
-            .increment(&key, CACHE_EXPIRE_MS_NETRID)
+            .add_reporter(&key, &receipt.reporter_id, &receipt, CACHE_EXPIRE_MS_NETRID)
             .await
             .map_err(|_| {
-                rest_warn!("(network_remote_id) could not increment key.");
+                rest_warn!("(process_netrid) could not add reporter to key.");
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+
+        count = reporters.count;
+        if !reporters.is_new_reporter {
+            rest_info!(
+                "(process_netrid) repeated netrid packet from reporter {}, ignoring.",
+                receipt.reporter_id
+            );
+            return Ok(count);
+        }
+
+        //
+        // Corroborated reports improve the reputation of their reporters
+        //
+        reputation::record_report(
+            &mut tlm_pools.reporters,
+            &receipt.reporter_id,
+            receipt.received,
+        )
+        .await;
+        record_corroborations(
+            &mut tlm_pools.netrid,
+            &mut tlm_pools.reporters,
+            &key,
+            &receipt.reporter_id,
+            count,
+        )
+        .await;
+
+        //
+        // Reports are weighted by the reputation of their reporter
+        //
+        let weight = stats.weight();
+        let total_weight = tlm_pools
+            .netrid
+            .add_weight(&key, weight, CACHE_EXPIRE_MS_NETRID)
+            .await
+            .map_err(|e| {
+                rest_error!("(process_netrid) {e}");
                 StatusCode::INTERNAL_SERVER_ERROR
             })?;
 
-        match count.cmp(&N_REPORTERS_NEEDED) {
-            Ordering::Less => {
-                rest_error!(
-                    "(network_remote_id) netrid reporter count should be impossible: {count}."
+        match reputation::acceptance(total_weight, weight, NETRID_REPORTERS_NEEDED as f32) {
+            Acceptance::Pending => {
+                rest_info!(
+                    "(process_netrid) netrid reporter weight is less than needed: {total_weight}/{NETRID_REPORTERS_NEEDED}."
                 );
-                return Err(StatusCode::INTERNAL_SERVER_ERROR);
+                return Ok(count);
             }
-            Ordering::Greater => {
+            Acceptance::AlreadyAccepted => {
                 rest_info!(
-                    "(network_remote_id) netrid reporter count is greater than needed: {count}."
+                    "(process_netrid) netrid packet was already accepted, reporter count: {count}."
                 );
 
-                // TODO(R4) push up to N reporter confirmations to svc-storage with user_ids
-                return Ok(Json(count));
+                // The packet was already accepted, record this reporter's confirmation
+                if count <= N_CONFIRMATIONS_MAX {
+                    push_confirmations(&key, vec![receipt], tlm_pools.netrid.clone());
+                }
+
+                return Ok(count);
             }
-            _ => (), // continue
+            Acceptance::Accepted => (), // continue
         }
     }
 
     // Eventually allow forwarding of packets from other aircraft
     // TODO(R5)
-    let jwt_identifier = claim.sub;
-    match frame.header.message_type {
+    let jwt_identifier = receipt.reporter_id;
+    let mut registry = IdentityRegistry::new(tlm_pools.aircraft.clone(), grpc_clients);
+    let asset_timestamp = match frame.header.message_type {
         MessageType::Basic => {
             let Ok(msg) = BasicMessage::unpack(&frame.message) else {
-                rest_warn!("(network_remote_id) could not parse basic message.");
+                rest_warn!("(process_netrid) could not parse basic message.");
                 return Err(StatusCode::BAD_REQUEST);
             };
 
-            process_basic_message(jwt_identifier, msg, gis_pool, mq_channel).await?;
+            process_basic_message(
+                jwt_identifier.clone(),
+                msg,
+                tlm_pools.aircraft.clone(),
+                registry,
+                gis_pool,
+                mq_channel,
+                &event_bus,
+            )
+            .await?;
+
+            None
         }
         MessageType::Location => {
             let Ok(msg) = LocationMessage::unpack(&frame.message) else {
-                rest_warn!("(network_remote_id) could not parse location message.");
+                rest_warn!("(process_netrid) could not parse location message.");
                 return Err(StatusCode::BAD_REQUEST);
             };
 
-            process_location_message(jwt_identifier, msg, gis_pool, mq_channel).await?;
+            let (latitude, longitude) = (msg.decode_latitude(), msg.decode_longitude());
+            if let Ok(altitude_meters) = msg.decode_altitude() {
+                let altitude_meters = altitude_meters as f64;
+                if !reputation::is_plausible_position(latitude, longitude, altitude_meters) {
+                    rest_warn!(
+                        "(process_netrid) implausible position: {latitude}, {longitude}, {altitude_meters} m."
+                    );
+                    reputation::record_implausible(&mut tlm_pools.reporters, &jwt_identifier).await;
+                    return Err(StatusCode::BAD_REQUEST);
+                }
+            }
+
+            let identifier = registry
+                .resolve(&Identifier::new(IdentifierKind::Subject, &jwt_identifier))
+                .await;
+
+            let (position, velocity) = process_location_message(
+                identifier,
+                &jwt_identifier,
+                msg,
+                tlm_pools.aircraft.clone(),
+                tlm_pools.reporters.clone(),
+                mq_channel.clone(),
+                fusion,
+                &event_bus,
+            )
+            .await?;
+
+            ingest
+                .on_accepted(
+                    &position.identifier,
+                    TelemetrySource::Netrid,
+                    Some(&position),
+                    Some(&velocity),
+                    emergency::from_operational_status(msg.operational_status),
+                )
+                .await;
+
+            msg.decode_timestamp().ok()
         }
         _ => {
             rest_warn!(
-                "(network_remote_id) unsupported message type: {:#?}.",
+                "(process_netrid) unsupported message type: {:#?}.",
                 frame.header.message_type
             );
             return Err(StatusCode::BAD_REQUEST);
         }
+    };
+
+    //
+    // Keep for historical tracks
+    //
+    if let Err(e) = history::store_netrid_frame(
+        &mut tlm_pools.netrid,
+        &jwt_identifier,
+        &payload,
+        Utc::now(),
+        asset_timestamp,
+    )
+    .await
+    {
+        rest_error!("(process_netrid) could not store frame: {e}.");
+        return Err(StatusCode::INTERNAL_SERVER_ERROR);
     }
 
-    Ok(Json(count))
+    //
+    // Record the reporters that confirmed this packet
+    //
+    if is_deduplicated {
+        match tlm_pools
+            .netrid
+            .get_reporters::<ReporterReceipt>(&key)
+            .await
+        {
+            Ok(receipts) => push_confirmations(&key, receipts, tlm_pools.netrid.clone()),
+            Err(e) => {
+                rest_warn!("(process_netrid) could not get reporters of packet from cache: {e}.")
+            }
+        }
+    }
+
+    Ok(count)
+}
+
+/// Remote ID
+#[utoipa::path(
+    post,
+    path = "/telemetry/netrid",
+    tag = "svc-telemetry",
+    request_body = Vec<u8>,
+    responses(
+        (status = 200, description = "Telemetry received."),
+        (status = 401, description = "Missing or invalid token."),
+        (status = 400, description = "Malformed packet, or implausible position."),
+        (status = 403, description = "Token was not issued to an aircraft, or the aircraft is quarantined."),
+        (status = 500, description = "Something went wrong."),
+        (status = 503, description = "Dependencies of svc-telemetry were down."),
+    )
+)]
+pub async fn network_remote_id(
+    Extension(ingest): Extension<Ingest>,
+    Extension(claim): Extension<crate::rest::api::jwt::Claim>,
+    headers: HeaderMap,
+    payload: Bytes,
+) -> Result<Json<u32>, StatusCode> {
+    rest_info!("(network_remote_id) entry.");
+    let receipt = ReporterReceipt::from_headers(claim.sub, &headers);
+    process_netrid(&ingest, receipt, &payload).await.map(Json)
 }
@@ -0,0 +1,308 @@
+//! Reporters of telemetry packets
+//!
+//! The same packet may be received by many networked nodes ("reporters").
+//!  Each unique reporter of a packet confirms that packet, and these
+//!  confirmations are recorded to the cache. This allows us to compute
+//!  network coverage, reward crowdsourced receivers, and audit which
+//!  nodes received which aircraft.
+//!
+//! Confirmations are only kept in the cache for 7 days. svc-storage has no
+//!  resource for reporter confirmations yet, so they are not persisted and
+//!  can't be relied on for audits older than that.
+
+use super::rest_types::{ReporterCounters, ReporterReputation};
+use crate::cache::pool::TelemetryPool;
+use crate::cache::TelemetryPools;
+use crate::reputation;
+
+use axum::{
+    extract::{Extension, Path},
+    http::HeaderMap,
+    Json,
+};
+use chrono::{DateTime, Utc};
+use hyper::StatusCode;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Optional header with the time the reporter received the packet (RFC 3339)
+pub const HEADER_REPORTER_TIMESTAMP: &str = "x-reporter-timestamp";
+
+/// Optional header with the signal strength of the received packet (dBm)
+pub const HEADER_REPORTER_RSSI: &str = "x-reporter-rssi";
+
+/// Maximum number of reporter confirmations to store for a single packet
+pub const N_CONFIRMATIONS_MAX: u32 = 10;
+
+/// Time the reporter confirmations of a packet are kept in the cache (ms)
+const CONFIRMATIONS_EXPIRE_MS: u32 = 7 * 24 * 60 * 60 * 1000;
+
+/// Counters of packets ingested from a reporter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReporterCounter {
+    /// A packet was received from the reporter
+    Received,
+
+    /// The reporter repeated a packet it already reported
+    Duplicate,
+
+    /// A packet was accepted as a result of the reporter's report
+    Accepted,
+
+    /// The reporter confirmed an already accepted packet
+    Confirmed,
+}
+
+impl ReporterCounter {
+    /// Name of the counter in the cache
+    fn field(&self) -> &'static str {
+        match self {
+            ReporterCounter::Received => "received",
+            ReporterCounter::Duplicate => "duplicate",
+            ReporterCounter::Accepted => "accepted",
+            ReporterCounter::Confirmed => "confirmed",
+        }
+    }
+}
+
+/// Increments an ingestion counter of a reporter
+///
+/// Failures are logged but not returned, a missed count shouldn't
+///  cause an otherwise valid packet to be rejected.
+pub async fn increment_counter(
+    mut pool: TelemetryPool,
+    reporter_id: &str,
+    counter: ReporterCounter,
+) {
+    if let Err(e) = pool
+        .increment_counter(&format!("{reporter_id}:counters"), counter.field(), 1)
+        .await
+    {
+        rest_warn!(
+            "(increment_counter) could not increment {:?} counter of reporter {reporter_id}: {e}.",
+            counter
+        );
+    }
+}
+
+/// Get the ingestion counters of a reporter
+#[utoipa::path(
+    get,
+    path = "/telemetry/reporter/{reporter_id}",
+    tag = "svc-telemetry",
+    params(
+        ("reporter_id" = String, Path, description = "UUID of the reporter")
+    ),
+    responses(
+        (status = 200, description = "Counters of the reporter.", body = ReporterCounters),
+        (status = 401, description = "Missing or invalid token."),
+        (status = 403, description = "Token was not issued to a reporter."),
+        (status = 400, description = "Malformed reporter UUID."),
+        (status = 500, description = "Something went wrong."),
+        (status = 503, description = "Dependencies of svc-telemetry were down."),
+    )
+)]
+pub async fn reporter_counters(
+    Extension(mut tlm_pools): Extension<TelemetryPools>,
+    Path(reporter_id): Path<String>,
+) -> Result<Json<ReporterCounters>, StatusCode> {
+    rest_info!("(reporter_counters) entry.");
+    let Ok(reporter_id) = Uuid::parse_str(&reporter_id) else {
+        rest_warn!("(reporter_counters) invalid reporter UUID: {reporter_id}.");
+        return Err(StatusCode::BAD_REQUEST);
+    };
+
+    let counters = tlm_pools
+        .reporters
+        .get_counters(&format!("{reporter_id}:counters"))
+        .await
+        .map_err(|e| {
+            rest_error!("(reporter_counters) could not get counters: {e}.");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let get = |counter: ReporterCounter| counters.get(counter.field()).copied().unwrap_or(0);
+    Ok(Json(ReporterCounters {
+        received: get(ReporterCounter::Received),
+        duplicate: get(ReporterCounter::Duplicate),
+        accepted: get(ReporterCounter::Accepted),
+        confirmed: get(ReporterCounter::Confirmed),
+    }))
+}
+
+/// Get the reputation of a reporter
+#[utoipa::path(
+    get,
+    path = "/telemetry/reporter/{reporter_id}/reputation",
+    tag = "svc-telemetry",
+    params(
+        ("reporter_id" = String, Path, description = "UUID of the reporter")
+    ),
+    responses(
+        (status = 200, description = "Reputation of the reporter.", body = ReporterReputation),
+        (status = 401, description = "Missing or invalid token."),
+        (status = 403, description = "Token was not issued to a reporter."),
+        (status = 400, description = "Malformed reporter UUID."),
+        (status = 500, description = "Something went wrong."),
+        (status = 503, description = "Dependencies of svc-telemetry were down."),
+    )
+)]
+pub async fn reporter_reputation(
+    Extension(mut tlm_pools): Extension<TelemetryPools>,
+    Path(reporter_id): Path<String>,
+) -> Result<Json<ReporterReputation>, StatusCode> {
+    rest_info!("(reporter_reputation) entry.");
+    let Ok(reporter_id) = Uuid::parse_str(&reporter_id) else {
+        rest_warn!("(reporter_reputation) invalid reporter UUID: {reporter_id}.");
+        return Err(StatusCode::BAD_REQUEST);
+    };
+
+    let stats = reputation::get_stats(&mut tlm_pools.reporters, &reporter_id.to_string()).await;
+
+    Ok(Json(ReporterReputation {
+        score: stats.score(),
+        quarantined: stats.is_quarantined(),
+        reports: stats.reports,
+        corroborated: stats.corroborated,
+        implausible: stats.implausible,
+        mean_skew_ms: stats.mean_skew_ms(),
+    }))
+}
+
+/// Details of a single reporter's receipt of a packet
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReporterReceipt {
+    /// The unique identifier of the reporter
+    pub reporter_id: String,
+
+    /// The time the reporter received the packet
+    pub received: DateTime<Utc>,
+
+    /// The signal strength of the received packet (dBm), if known
+    pub rssi: Option<f32>,
+}
+
+impl ReporterReceipt {
+    /// Creates a receipt from the headers of a reporter's request
+    ///
+    /// Falls back to the current time if the reporter didn't provide
+    ///  the time it received the packet.
+    pub fn from_headers(reporter_id: String, headers: &HeaderMap) -> Self {
+        let received = headers
+            .get(HEADER_REPORTER_TIMESTAMP)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| DateTime::parse_from_rfc3339(value).ok())
+            .map(|value| value.with_timezone(&Utc))
+            .unwrap_or_else(Utc::now);
+
+        let rssi = headers
+            .get(HEADER_REPORTER_RSSI)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<f32>().ok());
+
+        ReporterReceipt {
+            reporter_id,
+            received,
+            rssi,
+        }
+    }
+}
+
+/// Records the corroboration of a packet by a new reporter
+///
+/// `packets` is the cache used to deduplicate the packet under `key`, and
+///  `count` is the number of unique reporters including this one. The
+///  second reporter corroborates the first, later reporters are
+///  corroborated by the earlier ones.
+pub async fn record_corroborations(
+    packets: &mut TelemetryPool,
+    reporters: &mut TelemetryPool,
+    key: &str,
+    reporter_id: &str,
+    count: u32,
+) {
+    if count == 2 {
+        // The first reporter is corroborated by this one
+        match packets.get_reporters::<ReporterReceipt>(key).await {
+            Ok(receipts) => {
+                for report in receipts {
+                    reputation::record_corroboration(reporters, &report.reporter_id).await;
+                }
+            }
+            Err(e) => {
+                rest_warn!(
+                    "(record_corroborations) could not get reporters of packet from cache: {e}."
+                )
+            }
+        }
+    } else if count > 2 {
+        reputation::record_corroboration(reporters, reporter_id).await;
+    }
+}
+
+/// Records reporter confirmations of a packet to the cache
+///
+/// The packet is referenced by the same key used to deduplicate it in the cache.
+/// The confirmations are written in the background with a single request,
+///  so that they don't delay the response to the reporter. Failures are
+///  logged, a missing confirmation shouldn't cause an otherwise valid
+///  packet to be rejected.
+///
+/// TODO(R5): Also insert the confirmations into svc-storage once it has a
+///  resource for them.
+pub fn push_confirmations(
+    packet_key: &str,
+    receipts: Vec<ReporterReceipt>,
+    mut pool: TelemetryPool,
+) {
+    let key = format!("confirmations:{packet_key}");
+    tokio::spawn(async move {
+        let fields: Vec<(&str, String)> = receipts
+            .iter()
+            .take(N_CONFIRMATIONS_MAX as usize)
+            .filter_map(|receipt| {
+                let value = serde_json::to_string(receipt).ok()?;
+                Some((receipt.reporter_id.as_str(), value))
+            })
+            .collect();
+
+        let count = fields.len();
+        match pool.set_fields(&key, fields, CONFIRMATIONS_EXPIRE_MS).await {
+            Ok(_) => rest_debug!("(push_confirmations) recorded {count} confirmations of {key}."),
+            Err(e) => {
+                rest_warn!("(push_confirmations) could not record confirmations of {key}: {e}.")
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_receipt_from_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            HEADER_REPORTER_TIMESTAMP,
+            "2023-10-01T12:00:00Z".parse().unwrap(),
+        );
+        headers.insert(HEADER_REPORTER_RSSI, "-72.5".parse().unwrap());
+
+        let receipt = ReporterReceipt::from_headers("reporter".to_string(), &headers);
+        assert_eq!(receipt.reporter_id, "reporter");
+        assert_eq!(receipt.received.to_rfc3339(), "2023-10-01T12:00:00+00:00");
+        assert_eq!(receipt.rssi, Some(-72.5));
+    }
+
+    #[test]
+    fn test_receipt_from_headers_defaults() {
+        let mut headers = HeaderMap::new();
+        headers.insert(HEADER_REPORTER_TIMESTAMP, "invalid".parse().unwrap());
+
+        let before = Utc::now();
+        let receipt = ReporterReceipt::from_headers("reporter".to_string(), &headers);
+        assert!(receipt.received >= before);
+        assert!(receipt.rssi.is_none());
+    }
+}
@@ -0,0 +1,155 @@
+//! Live telemetry stream (Server-Sent Events)
+
+use super::rest_types::{StreamQuery, TelemetryEvent};
+use crate::tracking::events::{self, EventBus, EventFilter};
+use crate::tracking::spatial::BoundingBox;
+
+use axum::{
+    extract::{Extension, Query},
+    response::sse::{Event, KeepAlive, Sse},
+};
+use futures::stream::{Stream, StreamExt};
+use hyper::StatusCode;
+use std::convert::Infallible;
+
+/// Builds the filter of a stream from its query
+///
+/// Returns `None` if a kind is unknown, or the bounding box is
+///  incomplete or invalid.
+fn filter_from_query(query: &StreamQuery) -> Option<EventFilter> {
+    let identifiers = query
+        .identifiers
+        .iter()
+        .flat_map(|identifiers| identifiers.split(','))
+        .map(|identifier| identifier.trim().to_string())
+        .filter(|identifier| !identifier.is_empty())
+        .collect::<Vec<String>>();
+
+    let kinds = query
+        .kinds
+        .iter()
+        .flat_map(|kinds| kinds.split(','))
+        .map(events::parse_kind)
+        .collect::<Option<Vec<_>>>()?;
+
+    let bounding_box = match (
+        query.min_latitude,
+        query.min_longitude,
+        query.max_latitude,
+        query.max_longitude,
+    ) {
+        (None, None, None, None) => None,
+        (Some(min_latitude), Some(min_longitude), Some(max_latitude), Some(max_longitude)) => {
+            Some(BoundingBox {
+                min_latitude,
+                min_longitude,
+                max_latitude,
+                max_longitude,
+            })
+        }
+        _ => return None,
+    };
+
+    EventFilter::new(identifiers, kinds, bounding_box)
+}
+
+/// Converts a telemetry event to a Server-Sent Event named after its kind
+fn sse_event(event: TelemetryEvent) -> Option<Event> {
+    Event::default()
+        .event(events::kind_name(event.kind))
+        .json_data(&event)
+        .map_err(|e| rest_warn!("(sse_event) could not serialize event: {e}."))
+        .ok()
+}
+
+/// Stream decoded telemetry events as they are processed
+///
+/// Events are named `id`, `position`, `velocity` or `intrusion`, and carry a
+///  [`TelemetryEvent`] as JSON data.
+#[utoipa::path(
+    get,
+    path = "/telemetry/stream",
+    tag = "svc-telemetry",
+    params(StreamQuery),
+    responses(
+        (status = 200, description = "Stream of telemetry events.", content_type = "text/event-stream", body = TelemetryEvent),
+        (status = 401, description = "Missing or invalid token."),
+        (status = 403, description = "Token was not issued to a reporter."),
+        (status = 400, description = "Unknown event kind, or incomplete or invalid bounding box."),
+    )
+)]
+pub async fn stream(
+    Extension(event_bus): Extension<EventBus>,
+    Query(query): Query<StreamQuery>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, StatusCode> {
+    rest_info!("(stream) entry.");
+    let Some(filter) = filter_from_query(&query) else {
+        rest_warn!("(stream) invalid filter: {:?}.", query);
+        return Err(StatusCode::BAD_REQUEST);
+    };
+
+    let stream = event_bus
+        .subscribe(filter)
+        .filter_map(|event| async move { sse_event(event).map(Ok) });
+
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rest::api::rest_types::TelemetryEventKind;
+
+    #[test]
+    fn test_filter_from_query() {
+        let query = StreamQuery {
+            identifiers: Some("a, b".to_string()),
+            kinds: Some("position,velocity".to_string()),
+            ..Default::default()
+        };
+
+        assert!(filter_from_query(&query).is_some());
+        assert!(filter_from_query(&StreamQuery::default()).is_some());
+
+        let query = StreamQuery {
+            kinds: Some("position,altitude".to_string()),
+            ..Default::default()
+        };
+
+        assert!(filter_from_query(&query).is_none());
+
+        // Incomplete bounding box
+        let query = StreamQuery {
+            min_latitude: Some(52.),
+            max_latitude: Some(53.),
+            ..Default::default()
+        };
+
+        assert!(filter_from_query(&query).is_none());
+
+        let query = StreamQuery {
+            min_latitude: Some(52.),
+            min_longitude: Some(4.),
+            max_latitude: Some(53.),
+            max_longitude: Some(5.),
+            ..Default::default()
+        };
+
+        assert!(filter_from_query(&query).is_some());
+    }
+
+    #[test]
+    fn test_sse_event() {
+        let event = TelemetryEvent {
+            identifier: "aircraft".to_string(),
+            kind: TelemetryEventKind::Id,
+            source: crate::rest::api::rest_types::TelemetrySource::Adsb,
+            aircraft_type: Some("Rotorcraft".to_string()),
+            position: None,
+            velocity: None,
+            intrusion: None,
+        };
+
+        assert!(sse_event(event).is_some());
+    }
+}
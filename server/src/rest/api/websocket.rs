@@ -0,0 +1,368 @@
+//! Live telemetry stream (WebSocket)
+//!
+//! Clients send [`StreamRequest`] messages to add and remove named
+//!  subscriptions, and receive [`StreamResponse`] messages with the
+//!  telemetry events matching any of their subscriptions. Events for
+//!  slow clients are held as [`PendingEvents`], keeping only the latest
+//!  event of each aircraft rather than disconnecting the client. A client
+//!  which doesn't read its replies is disconnected.
+
+use super::rest_types::{StreamBoundingBox, StreamRequest, StreamResponse, TelemetryEvent};
+use crate::tracking::events::{EventBus, EventFilter, PendingEvents};
+use crate::tracking::spatial::BoundingBox;
+
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Extension,
+    },
+    response::Response,
+};
+use futures::{
+    sink::SinkExt,
+    stream::{SplitSink, StreamExt},
+};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{mpsc, Notify};
+
+/// Most subscriptions a client can hold at once
+const MAX_SUBSCRIPTIONS: usize = 32;
+
+/// Most replies waiting to be sent to a client before it is disconnected
+const MAX_PENDING_REPLIES: usize = 64;
+
+/// Subscriptions of a client, by name
+type Subscriptions = HashMap<String, EventFilter>;
+
+impl From<StreamBoundingBox> for BoundingBox {
+    fn from(bounding_box: StreamBoundingBox) -> Self {
+        BoundingBox {
+            min_latitude: bounding_box.min_latitude,
+            min_longitude: bounding_box.min_longitude,
+            max_latitude: bounding_box.max_latitude,
+            max_longitude: bounding_box.max_longitude,
+        }
+    }
+}
+
+/// Applies a message from a client to its subscriptions, returning the reply
+fn handle_request(subscriptions: &mut Subscriptions, text: &str) -> StreamResponse {
+    let request = match serde_json::from_str::<StreamRequest>(text) {
+        Ok(request) => request,
+        Err(e) => {
+            return StreamResponse::Error {
+                message: format!("invalid message: {e}"),
+            }
+        }
+    };
+
+    match request {
+        StreamRequest::Subscribe {
+            subscription,
+            identifiers,
+            kinds,
+            bounding_box,
+        } => {
+            if !subscriptions.contains_key(&subscription)
+                && subscriptions.len() >= MAX_SUBSCRIPTIONS
+            {
+                return StreamResponse::Error {
+                    message: format!("at most {MAX_SUBSCRIPTIONS} subscriptions are allowed"),
+                };
+            }
+
+            let Some(filter) = EventFilter::new(identifiers, kinds, bounding_box.map(Into::into))
+            else {
+                return StreamResponse::Error {
+                    message: format!("invalid bounding box of subscription {subscription}"),
+                };
+            };
+
+            subscriptions.insert(subscription.clone(), filter);
+            StreamResponse::Subscribed { subscription }
+        }
+        StreamRequest::Unsubscribe { subscription } => match subscriptions.remove(&subscription) {
+            Some(_) => StreamResponse::Unsubscribed { subscription },
+            None => StreamResponse::Error {
+                message: format!("unknown subscription {subscription}"),
+            },
+        },
+    }
+}
+
+/// If an event matches any of the subscriptions
+fn matches_any(subscriptions: &mut Subscriptions, event: &TelemetryEvent) -> bool {
+    // Every filter sees every event, so that each one keeps track of the
+    //  aircraft inside its bounding box
+    subscriptions
+        .values_mut()
+        .fold(false, |matched, filter| filter.matches(event) || matched)
+}
+
+/// Sends a message to the client, returning `false` if it could not be sent
+async fn send(sink: &mut SplitSink<WebSocket, Message>, response: &StreamResponse) -> bool {
+    let text = match serde_json::to_string(response) {
+        Ok(text) => text,
+        Err(e) => {
+            rest_warn!("(send) could not serialize message: {e}.");
+            return true;
+        }
+    };
+
+    sink.send(Message::Text(text)).await.is_ok()
+}
+
+/// Sends replies and pending events to the client until it disconnects
+///
+/// Replies are sent before pending events.
+async fn send_responses(
+    mut sink: SplitSink<WebSocket, Message>,
+    mut replies: mpsc::Receiver<StreamResponse>,
+    pending: Arc<Mutex<PendingEvents>>,
+    notify: Arc<Notify>,
+) {
+    loop {
+        while let Ok(reply) = replies.try_recv() {
+            if !send(&mut sink, &reply).await {
+                return;
+            }
+        }
+
+        let (event, dropped) = match pending.lock() {
+            Ok(mut pending) => {
+                let event = pending.pop();
+                let dropped = if event.is_none() {
+                    pending.take_dropped()
+                } else {
+                    0
+                };
+
+                (event, dropped)
+            }
+            Err(_) => return,
+        };
+
+        if dropped > 0 {
+            rest_info!("(send_responses) client fell behind, dropped {dropped} events.");
+        }
+
+        match event {
+            Some(event) => {
+                if !send(&mut sink, &StreamResponse::Event { event }).await {
+                    return;
+                }
+            }
+            None => {
+                tokio::select! {
+                    reply = replies.recv() => match reply {
+                        Some(reply) => {
+                            if !send(&mut sink, &reply).await {
+                                return;
+                            }
+                        }
+                        None => return,
+                    },
+                    _ = notify.notified() => (),
+                }
+            }
+        }
+    }
+}
+
+/// Handles the messages of a client until it disconnects
+///
+/// Events are received from the live telemetry [`EventBus`], which is
+///  independent of the telemetry published to RabbitMQ.
+async fn handle_socket(socket: WebSocket, event_bus: EventBus) {
+    let (sink, mut messages) = socket.split();
+    let (reply_sender, replies) = mpsc::channel(MAX_PENDING_REPLIES);
+    let pending = Arc::new(Mutex::new(PendingEvents::default()));
+    let notify = Arc::new(Notify::new());
+    let sender = tokio::spawn(send_responses(
+        sink,
+        replies,
+        pending.clone(),
+        notify.clone(),
+    ));
+
+    let mut events = Box::pin(event_bus.subscribe(EventFilter::default()));
+    let mut subscriptions = Subscriptions::new();
+    loop {
+        tokio::select! {
+            message = messages.next() => match message {
+                Some(Ok(Message::Text(text))) => {
+                    let reply = handle_request(&mut subscriptions, &text);
+                    match reply_sender.try_send(reply) {
+                        Ok(()) => (),
+                        Err(mpsc::error::TrySendError::Full(_)) => {
+                            rest_warn!("(handle_socket) client is not reading replies, disconnecting.");
+                            break;
+                        }
+                        Err(mpsc::error::TrySendError::Closed(_)) => break,
+                    }
+                }
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                // Pings are answered by axum, binary messages are ignored
+                Some(Ok(_)) => (),
+            },
+            event = events.next() => {
+                let Some(event) = event else {
+                    break;
+                };
+
+                if !matches_any(&mut subscriptions, &event) {
+                    continue;
+                }
+
+                match pending.lock() {
+                    Ok(mut pending) => pending.push(event),
+                    Err(_) => break,
+                }
+
+                notify.notify_one();
+            }
+        }
+    }
+
+    rest_debug!("(handle_socket) client disconnected.");
+    sender.abort();
+}
+
+/// Subscribe to decoded telemetry events over a WebSocket
+///
+/// Clients send [`StreamRequest`] messages and receive
+///  [`StreamResponse`] messages, both as JSON text.
+#[utoipa::path(
+    get,
+    path = "/telemetry/ws",
+    tag = "svc-telemetry",
+    responses(
+        (status = 101, description = "Switching to the WebSocket protocol.", body = StreamResponse),
+        (status = 401, description = "Missing or invalid token."),
+        (status = 403, description = "Token was not issued to a reporter."),
+    )
+)]
+pub async fn websocket(
+    ws: WebSocketUpgrade,
+    Extension(event_bus): Extension<EventBus>,
+) -> Response {
+    rest_info!("(websocket) entry.");
+    ws.on_upgrade(move |socket| handle_socket(socket, event_bus))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rest::api::rest_types::{
+        AircraftStatePosition, TelemetryEventKind, TelemetrySource,
+    };
+    use chrono::Utc;
+
+    fn position(identifier: &str, latitude: f64, longitude: f64) -> TelemetryEvent {
+        TelemetryEvent {
+            identifier: identifier.to_string(),
+            kind: TelemetryEventKind::Position,
+            source: TelemetrySource::Adsb,
+            aircraft_type: None,
+            position: Some(AircraftStatePosition {
+                latitude,
+                longitude,
+                altitude_meters: 100.,
+                timestamp_network: Utc::now(),
+                timestamp_asset: None,
+            }),
+            velocity: None,
+            intrusion: None,
+        }
+    }
+
+    #[test]
+    fn test_handle_request() {
+        let mut subscriptions = Subscriptions::new();
+
+        let reply = handle_request(
+            &mut subscriptions,
+            r#"{"type": "subscribe", "subscription": "a", "kinds": ["position"]}"#,
+        );
+
+        assert_eq!(
+            reply,
+            StreamResponse::Subscribed {
+                subscription: "a".to_string()
+            }
+        );
+
+        let reply = handle_request(
+            &mut subscriptions,
+            r#"{"type": "unsubscribe", "subscription": "a"}"#,
+        );
+
+        assert_eq!(
+            reply,
+            StreamResponse::Unsubscribed {
+                subscription: "a".to_string()
+            }
+        );
+
+        assert!(subscriptions.is_empty());
+    }
+
+    #[test]
+    fn test_handle_request_invalid() {
+        let mut subscriptions = Subscriptions::new();
+        for text in [
+            "not json",
+            r#"{"type": "unsubscribe", "subscription": "a"}"#,
+            r#"{"type": "subscribe", "subscription": "a", "kinds": ["altitude"]}"#,
+            r#"{"type": "subscribe", "subscription": "a", "bounding_box":
+                {"min_latitude": 53, "min_longitude": 4, "max_latitude": 52, "max_longitude": 5}}"#,
+        ] {
+            let reply = handle_request(&mut subscriptions, text);
+            assert!(matches!(reply, StreamResponse::Error { .. }), "{text}");
+        }
+
+        assert!(subscriptions.is_empty());
+
+        for i in 0..MAX_SUBSCRIPTIONS {
+            let text = format!(r#"{{"type": "subscribe", "subscription": "{i}"}}"#);
+            handle_request(&mut subscriptions, &text);
+        }
+
+        let reply = handle_request(
+            &mut subscriptions,
+            r#"{"type": "subscribe", "subscription": "one too many"}"#,
+        );
+
+        assert!(matches!(reply, StreamResponse::Error { .. }));
+
+        // Replacing an existing subscription is still allowed
+        let reply = handle_request(
+            &mut subscriptions,
+            r#"{"type": "subscribe", "subscription": "0"}"#,
+        );
+
+        assert!(matches!(reply, StreamResponse::Subscribed { .. }));
+    }
+
+    #[test]
+    fn test_matches_any() {
+        let mut subscriptions = Subscriptions::new();
+        assert!(!matches_any(&mut subscriptions, &position("a", 52.5, 4.5)));
+
+        handle_request(
+            &mut subscriptions,
+            r#"{"type": "subscribe", "subscription": "b", "identifiers": ["b"]}"#,
+        );
+
+        handle_request(
+            &mut subscriptions,
+            r#"{"type": "subscribe", "subscription": "area", "bounding_box":
+                {"min_latitude": 52, "min_longitude": 4, "max_latitude": 53, "max_longitude": 5}}"#,
+        );
+
+        assert!(matches_any(&mut subscriptions, &position("a", 52.5, 4.5)));
+        assert!(!matches_any(&mut subscriptions, &position("a", 51., 4.5)));
+        assert!(matches_any(&mut subscriptions, &position("b", 51., 4.5)));
+    }
+}
@@ -12,10 +12,44 @@ use utoipa::OpenApi;
 #[openapi(
     paths(
         api::jwt::login,
+        api::jwt::login_reporter,
         api::netrid::network_remote_id,
         api::adsb::adsb,
+        api::reporter::reporter_counters,
+        api::reporter::reporter_reputation,
+        api::aircraft::get_aircraft,
+        api::aircraft::list_aircraft,
+        api::aircraft::aircraft_in_bounding_box,
+        api::aircraft::aircraft_in_radius,
+        api::history::get_history,
+        api::stream::stream,
+        api::websocket::websocket,
+        api::emergency::list_emergencies,
         api::health::health_check
     ),
+    components(
+        schemas(
+            api::rest_types::ReporterLogin,
+            api::rest_types::ReporterCounters,
+            api::rest_types::ReporterReputation,
+            api::rest_types::TelemetrySource,
+            api::rest_types::AircraftStatus,
+            api::rest_types::FlightPhase,
+            api::rest_types::AircraftStatePosition,
+            api::rest_types::AircraftStateVelocity,
+            api::rest_types::AircraftState,
+            api::rest_types::TrackPoint,
+            api::rest_types::AircraftHistory,
+            api::rest_types::TelemetryEventKind,
+            api::rest_types::ZoneIntrusion,
+            api::rest_types::EmergencyKind,
+            api::rest_types::Emergency,
+            api::rest_types::TelemetryEvent,
+            api::rest_types::StreamBoundingBox,
+            api::rest_types::StreamRequest,
+            api::rest_types::StreamResponse
+        )
+    ),
     tags(
         (name = "svc-telemetry", description = "svc-telemetry REST API.")
     )
@@ -1,10 +1,7 @@
 //! Rest server implementation
 
 use super::api;
-use crate::amqp::init_mq;
-use crate::cache::pool::{GisPool, TelemetryPool};
-use crate::cache::TelemetryPools;
-use crate::grpc::client::GrpcClients;
+use super::api::ingest::Ingest;
 use crate::shutdown_signal;
 use crate::Config;
 use axum::{
@@ -14,7 +11,7 @@ use axum::{
     routing::{get, post},
     BoxError, Router,
 };
-use rand::{distributions::Alphanumeric, Rng};
+use std::collections::HashMap;
 use std::net::SocketAddr;
 use tower::{
     buffer::BufferLayer,
@@ -28,16 +25,16 @@ use tower_http::trace::TraceLayer;
 ///
 /// # Example:
 /// ```
+/// use svc_telemetry::rest::api::ingest::Ingest;
 /// use svc_telemetry::rest::server::rest_server;
 /// use svc_telemetry::grpc::client::GrpcClients;
-/// use svc_gis_client_grpc::prelude::types::{AircraftPosition, AircraftVelocity, AircraftId};
 /// use svc_telemetry::Config;
-/// use std::collections::VecDeque;
-/// use std::sync::{Arc, Mutex};
-/// async fn example() -> Result<(), tokio::task::JoinError> {
+/// async fn example() -> Result<(), ()> {
 ///     let config = Config::default();
 ///     let grpc_clients = GrpcClients::default(config.clone());
-///     tokio::spawn(rest_server(config, grpc_clients, None)).await;
+///     let ingest = Ingest::new(config.clone(), grpc_clients).await?;
+///     svc_telemetry::rest::api::jwt::init_secret();
+///     tokio::spawn(rest_server(config, ingest, None));
 ///     Ok(())
 /// }
 /// ```
@@ -46,7 +43,7 @@ use tower_http::trace::TraceLayer;
 // Will be tested in integration tests.
 pub async fn rest_server(
     config: Config,
-    grpc_clients: GrpcClients,
+    ingest: Ingest,
     shutdown_rx: Option<tokio::sync::oneshot::Receiver<()>>,
 ) -> Result<(), ()> {
     rest_info!("(rest_server) entry.");
@@ -89,52 +86,87 @@ pub async fn rest_server(
             std::time::Duration::from_secs(1),
         ));
 
-    //
-    // Extensions
-    //
-
-    // Redis Pools
-    let tlm_pools = TelemetryPools {
-        adsb: TelemetryPool::new(config.clone(), "tlm:adsb").await?,
-        netrid: TelemetryPool::new(config.clone(), "tlm:netrid").await?,
+    // Reporter credentials
+    let reporter_credentials = match config.reporter_credentials_file.as_str() {
+        "" => {
+            rest_warn!("(rest_server) no reporter credentials file, reporters cannot login.");
+            HashMap::new()
+        }
+        path => crate::rest::api::jwt::load_reporter_credentials(path).map_err(|e| {
+            rest_error!("(rest_server) could not load reporter credentials from {path}: {e}.");
+        })?,
     };
 
-    let gis_pool = GisPool::new(config.clone()).await?;
+    rest_info!(
+        "(rest_server) loaded credentials of {} reporters.",
+        reporter_credentials.len()
+    );
 
-    // RabbitMQ Channel
-    let mq_channel = init_mq(config.clone()).await.map_err(|e| {
-        rest_error!("(rest_server) could not create RabbitMQ Channel: {e}");
-    })?;
-
-    // TODO(R5): Replace with PKI certificates
-    // Temporarily set JWT token to a random string
-    match crate::rest::api::jwt::JWT_SECRET.set(
-        rand::thread_rng()
-            .sample_iter(&Alphanumeric)
-            .take(42)
-            .map(char::from)
-            .collect(),
-    ) {
-        Err(e) => {
-            rest_error!("(rest_server) could not set JWT_SECRET: {}", e);
-            return Err(());
-        }
-        _ => {
-            rest_info!("(rest_server) set JWT_SECRET.");
-        }
+    if crate::rest::api::jwt::REPORTER_CREDENTIALS
+        .set(reporter_credentials)
+        .is_err()
+    {
+        rest_error!("(rest_server) could not set REPORTER_CREDENTIALS.");
+        return Err(());
     }
 
     //
     // Create Server
     //
-    let app = Router::new()
-        // must be first with its route layer
+    let reporter_routes = Router::new()
+        .route("/telemetry/adsb", post(api::adsb::adsb))
+        .route_layer(axum::middleware::from_fn(
+            crate::rest::api::jwt::auth_reporter,
+        ));
+
+    let aircraft_routes = Router::new()
         .route("/telemetry/netrid", post(api::netrid::network_remote_id))
-        .route_layer(axum::middleware::from_fn(crate::rest::api::jwt::auth))
-        // other routes after route_layer not affected
+        .route_layer(axum::middleware::from_fn(
+            crate::rest::api::jwt::auth_aircraft,
+        ));
+
+    // Telemetry reads, restricted to provisioned reporters
+    let read_routes = Router::new()
+        .route(
+            "/telemetry/reporter/:reporter_id",
+            get(api::reporter::reporter_counters),
+        )
+        .route(
+            "/telemetry/reporter/:reporter_id/reputation",
+            get(api::reporter::reporter_reputation),
+        )
+        .route("/telemetry/aircraft", get(api::aircraft::list_aircraft))
+        .route(
+            "/telemetry/aircraft/bbox",
+            get(api::aircraft::aircraft_in_bounding_box),
+        )
+        .route(
+            "/telemetry/aircraft/radius",
+            get(api::aircraft::aircraft_in_radius),
+        )
+        .route("/telemetry/aircraft/:id", get(api::aircraft::get_aircraft))
+        .route("/telemetry/history/:id", get(api::history::get_history))
+        .route("/telemetry/stream", get(api::stream::stream))
+        .route("/telemetry/ws", get(api::websocket::websocket))
+        .route(
+            "/telemetry/emergencies",
+            get(api::emergency::list_emergencies),
+        )
+        .route_layer(axum::middleware::from_fn(
+            crate::rest::api::jwt::auth_reader,
+        ));
+
+    let app = Router::new()
+        // authenticated routes with their own route layers
+        .merge(reporter_routes)
+        .merge(aircraft_routes)
+        .merge(read_routes)
         .route("/health", get(api::health::health_check))
         .route("/telemetry/login", get(crate::rest::api::jwt::login))
-        .route("/telemetry/adsb", post(api::adsb::adsb))
+        .route(
+            "/telemetry/login/reporter",
+            get(crate::rest::api::jwt::login_reporter),
+        )
         .layer(
             CorsLayer::new()
                 .allow_origin(cors_allowed_origin)
@@ -142,10 +174,10 @@ pub async fn rest_server(
                 .allow_methods(Any),
         )
         .layer(limit_middleware)
-        .layer(Extension(tlm_pools))
-        .layer(Extension(gis_pool))
-        .layer(Extension(mq_channel))
-        .layer(Extension(grpc_clients));
+        .layer(Extension(ingest.tlm_pools.clone()))
+        .layer(Extension(ingest.grpc_clients.clone()))
+        .layer(Extension(ingest.event_bus.clone()))
+        .layer(Extension(ingest));
 
     match axum::Server::bind(&full_rest_addr)
         .serve(app.into_make_service())
@@ -0,0 +1,499 @@
+//! Short-term conflict alerting
+//!
+//! Every accepted position is compared with the latest position of every
+//!  other aircraft. Both aircraft are projected along their latest
+//!  velocities, and an alert is published if they would be closer than
+//!  both the horizontal and the vertical separation minima within the
+//!  horizon. Each pair is alerted once, until it is no longer in conflict.
+//!
+//! Aircraft are compared in a local flat-earth frame, which is accurate
+//!  enough at the distances of short-term conflicts.
+
+use super::EARTH_RADIUS_METERS;
+use crate::rest::api::rest_types::AircraftStatePosition;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use svc_gis_client_grpc::prelude::types::*;
+
+/// Positions further apart in time than this (s) are not compared
+const MAX_POSITION_AGE_SECONDS: f64 = 10.;
+
+/// Aircraft slower than this (m/s) in every direction are stationary,
+///  pairs of stationary aircraft such as those parked at a vertiport are
+///  not compared
+const STATIONARY_SPEED_MPS: f64 = 1.;
+
+/// Aircraft without updates for this long are forgotten (s)
+const TRACK_EXPIRE_SECONDS: i64 = 60;
+
+/// Separation minima, and how far ahead they are checked
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Separation {
+    /// Time aircraft are projected ahead (s)
+    pub horizon_seconds: f64,
+
+    /// Smallest horizontal distance between aircraft (m)
+    pub horizontal_meters: f64,
+
+    /// Smallest vertical distance between aircraft (m)
+    pub vertical_meters: f64,
+}
+
+/// Latest position and velocity of an aircraft
+#[derive(Debug, Clone, PartialEq)]
+struct Track {
+    position: AircraftStatePosition,
+
+    /// Velocity north, east and up (m/s), if known
+    velocity: Option<(f64, f64, f64)>,
+}
+
+impl Track {
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.position
+            .timestamp_asset
+            .unwrap_or(self.position.timestamp_network)
+    }
+
+    fn velocity(&self) -> (f64, f64, f64) {
+        self.velocity.unwrap_or_default()
+    }
+
+    fn is_stationary(&self) -> bool {
+        let (north, east, up) = self.velocity();
+        north.hypot(east) < STATIONARY_SPEED_MPS && up.abs() < STATIONARY_SPEED_MPS
+    }
+}
+
+/// Closest approach of two aircraft in conflict
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Conflict {
+    /// Seconds until separation is lost, zero if already lost
+    seconds_to_conflict: f64,
+
+    /// Seconds until the closest horizontal approach within the horizon
+    cpa_seconds: f64,
+
+    /// Horizontal distance at the closest approach (m)
+    cpa_horizontal_meters: f64,
+
+    /// Vertical distance at the closest approach (m)
+    cpa_vertical_meters: f64,
+}
+
+/// Times during which `|offset + rate * t| < limit`, if any
+///
+/// With a non-zero `rate_squared`, solves the quadratic
+///  `rate_squared * t^2 + 2 * dot * t + offset_squared - limit^2 < 0`.
+fn violation_interval(
+    offset_squared: f64,
+    dot: f64,
+    rate_squared: f64,
+    limit: f64,
+) -> Option<(f64, f64)> {
+    let c = offset_squared - limit * limit;
+    if rate_squared < f64::EPSILON {
+        return (c < 0.).then_some((f64::NEG_INFINITY, f64::INFINITY));
+    }
+
+    let discriminant = dot * dot - rate_squared * c;
+    if discriminant <= 0. {
+        return None;
+    }
+
+    let root = discriminant.sqrt();
+    Some(((-dot - root) / rate_squared, (-dot + root) / rate_squared))
+}
+
+/// Predicts a loss of separation between two aircraft within the horizon
+///
+/// The other aircraft is first projected to the time of the own
+///  aircraft's position.
+fn predict_conflict(own: &Track, other: &Track, separation: &Separation) -> Option<Conflict> {
+    let dt = (own.timestamp() - other.timestamp()).num_milliseconds() as f64 / 1000.;
+    if dt.abs() > MAX_POSITION_AGE_SECONDS || (own.is_stationary() && other.is_stationary()) {
+        return None;
+    }
+
+    let (own_north, own_east, own_up) = own.velocity();
+    let (other_north, other_east, other_up) = other.velocity();
+
+    // Relative position and velocity of the other aircraft
+    let latitude = own.position.latitude.to_radians();
+    let north = (other.position.latitude - own.position.latitude).to_radians()
+        * EARTH_RADIUS_METERS
+        + other_north * dt;
+    let east = (other.position.longitude - own.position.longitude).to_radians()
+        * EARTH_RADIUS_METERS
+        * latitude.cos()
+        + other_east * dt;
+    let up = other.position.altitude_meters - own.position.altitude_meters + other_up * dt;
+    let (rate_north, rate_east, rate_up) = (
+        other_north - own_north,
+        other_east - own_east,
+        other_up - own_up,
+    );
+
+    let horizontal_rate_squared = rate_north * rate_north + rate_east * rate_east;
+    let horizontal_dot = north * rate_north + east * rate_east;
+    let (horizontal_start, horizontal_end) = violation_interval(
+        north * north + east * east,
+        horizontal_dot,
+        horizontal_rate_squared,
+        separation.horizontal_meters,
+    )?;
+
+    let (vertical_start, vertical_end) = violation_interval(
+        up * up,
+        up * rate_up,
+        rate_up * rate_up,
+        separation.vertical_meters,
+    )?;
+
+    let start = horizontal_start.max(vertical_start).max(0.);
+    let end = horizontal_end
+        .min(vertical_end)
+        .min(separation.horizon_seconds);
+    if start >= end {
+        return None;
+    }
+
+    let cpa_seconds = if horizontal_rate_squared < f64::EPSILON {
+        0.
+    } else {
+        (-horizontal_dot / horizontal_rate_squared).clamp(0., separation.horizon_seconds)
+    };
+
+    Some(Conflict {
+        seconds_to_conflict: start,
+        cpa_seconds,
+        cpa_horizontal_meters: (north + rate_north * cpa_seconds)
+            .hypot(east + rate_east * cpa_seconds),
+        cpa_vertical_meters: (up + rate_up * cpa_seconds).abs(),
+    })
+}
+
+/// A predicted loss of separation between two aircraft, published to RabbitMQ
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConflictAlert {
+    /// Identifiers of the aircraft
+    pub identifiers: [String; 2],
+
+    /// Latest positions of the aircraft
+    pub positions: [AircraftStatePosition; 2],
+
+    /// Seconds until separation is lost, zero if already lost
+    pub seconds_to_conflict: f64,
+
+    /// Seconds until the closest point of approach
+    pub cpa_seconds: f64,
+
+    /// Horizontal distance at the closest point of approach (m)
+    pub cpa_horizontal_meters: f64,
+
+    /// Vertical distance at the closest point of approach (m)
+    pub cpa_vertical_meters: f64,
+
+    /// Time of the position which raised the alert
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Latest tracks of aircraft, and the pairs in conflict
+#[derive(Debug, Default)]
+struct Tracks {
+    tracks: HashMap<String, Track>,
+    conflicts: HashSet<(String, String)>,
+}
+
+/// Detects conflicts between tracked aircraft
+#[derive(Debug, Clone)]
+pub struct ConflictDetector {
+    separation: Separation,
+    tracks: Arc<Mutex<Tracks>>,
+}
+
+/// Key of a pair of aircraft, in either order
+fn pair(a: &str, b: &str) -> (String, String) {
+    if a < b {
+        (a.to_string(), b.to_string())
+    } else {
+        (b.to_string(), a.to_string())
+    }
+}
+
+impl ConflictDetector {
+    /// Creates a detector without tracks
+    pub fn new(separation: Separation) -> Self {
+        ConflictDetector {
+            separation,
+            tracks: Arc::default(),
+        }
+    }
+
+    /// Updates the track of an aircraft with a position, and the velocity
+    ///  received with it if any, returning alerts for the aircraft it newly
+    ///  conflicts with
+    pub fn update_position(
+        &self,
+        position: &AircraftPosition,
+        velocity: Option<&AircraftVelocity>,
+    ) -> Vec<ConflictAlert> {
+        let Ok(mut state) = self.tracks.lock() else {
+            tracking_error!("(update_position) could not lock conflict tracks.");
+            return vec![];
+        };
+
+        let Tracks { tracks, conflicts } = &mut *state;
+
+        if !tracks.contains_key(&position.identifier) {
+            let now = Utc::now();
+            tracks
+                .retain(|_, track| (now - track.timestamp()).num_seconds() < TRACK_EXPIRE_SECONDS);
+            conflicts.retain(|(a, b)| tracks.contains_key(a) && tracks.contains_key(b));
+        }
+
+        let velocity = velocity.map(components);
+        let own = tracks
+            .entry(position.identifier.clone())
+            .and_modify(|track| {
+                track.position = AircraftStatePosition::from(position);
+                if velocity.is_some() {
+                    track.velocity = velocity;
+                }
+            })
+            .or_insert_with(|| Track {
+                position: AircraftStatePosition::from(position),
+                velocity,
+            })
+            .clone();
+
+        let timestamp = own.timestamp();
+        tracks
+            .iter()
+            .filter(|(identifier, _)| **identifier != position.identifier)
+            .filter_map(|(identifier, other)| {
+                let key = pair(&position.identifier, identifier);
+                let Some(conflict) = predict_conflict(&own, other, &self.separation) else {
+                    conflicts.remove(&key);
+                    return None;
+                };
+
+                if !conflicts.insert(key) {
+                    return None;
+                }
+
+                Some(ConflictAlert {
+                    identifiers: [position.identifier.clone(), identifier.clone()],
+                    positions: [own.position.clone(), other.position.clone()],
+                    seconds_to_conflict: conflict.seconds_to_conflict,
+                    cpa_seconds: conflict.cpa_seconds,
+                    cpa_horizontal_meters: conflict.cpa_horizontal_meters,
+                    cpa_vertical_meters: conflict.cpa_vertical_meters,
+                    timestamp,
+                })
+            })
+            .collect()
+    }
+
+    /// Updates the track of an aircraft with a velocity
+    ///
+    /// Velocities of aircraft without a known position are ignored.
+    pub fn update_velocity(&self, velocity: &AircraftVelocity) {
+        let Ok(mut tracks) = self.tracks.lock() else {
+            tracking_error!("(update_velocity) could not lock conflict tracks.");
+            return;
+        };
+
+        let Some(track) = tracks.tracks.get_mut(&velocity.identifier) else {
+            return;
+        };
+
+        track.velocity = Some(components(velocity));
+    }
+}
+
+/// North, east and vertical components of a velocity, in meters per second
+fn components(velocity: &AircraftVelocity) -> (f64, f64, f64) {
+    let speed = velocity.velocity_horizontal_ground_mps as f64;
+    let track_angle = (velocity.track_angle_degrees as f64).to_radians();
+    (
+        speed * track_angle.cos(),
+        speed * track_angle.sin(),
+        velocity.velocity_vertical_mps as f64,
+    )
+}
+
+/// Publishes conflict alerts to RabbitMQ
+pub async fn publish_alerts(mq_channel: &lapin::Channel, alerts: Vec<ConflictAlert>) {
+    for alert in alerts {
+        tracking_warn!(
+            "(publish_alerts) conflict between {} and {} in {:.0} s, closest approach {:.0} m.",
+            alert.identifiers[0],
+            alert.identifiers[1],
+            alert.seconds_to_conflict,
+            alert.cpa_horizontal_meters
+        );
+
+        let Ok(msg) = serde_json::to_vec(&alert) else {
+            tracking_warn!("(publish_alerts) could not serialize conflict alert.");
+            continue;
+        };
+
+        let _ = mq_channel
+            .basic_publish(
+                crate::amqp::EXCHANGE_NAME_TELEMETRY,
+                crate::amqp::ROUTING_KEY_CONFLICT,
+                lapin::options::BasicPublishOptions::default(),
+                &msg,
+                lapin::BasicProperties::default(),
+            )
+            .await
+            .map_err(|e| {
+                tracking_warn!("(publish_alerts) could not push conflict alert to RabbitMQ: {e}.");
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEPARATION: Separation = Separation {
+        horizon_seconds: 60.,
+        horizontal_meters: 500.,
+        vertical_meters: 100.,
+    };
+
+    /// Degrees of latitude in a meter
+    const DEGREES_PER_METER: f64 = 1. / 111_195.;
+
+    fn position(identifier: &str, north_meters: f64, altitude_meters: f64) -> AircraftPosition {
+        AircraftPosition {
+            identifier: identifier.to_string(),
+            position: Position {
+                latitude: 52. + north_meters * DEGREES_PER_METER,
+                longitude: 4.,
+                altitude_meters,
+            },
+            timestamp_network: Utc::now(),
+            timestamp_asset: None,
+        }
+    }
+
+    fn velocity(identifier: &str, speed: f32, track_angle_degrees: f32) -> AircraftVelocity {
+        AircraftVelocity {
+            identifier: identifier.to_string(),
+            velocity_horizontal_ground_mps: speed,
+            velocity_horizontal_air_mps: None,
+            velocity_vertical_mps: 0.,
+            track_angle_degrees,
+            timestamp_network: Utc::now(),
+            timestamp_asset: None,
+        }
+    }
+
+    fn track(north_meters: f64, altitude_meters: f64, velocity: (f64, f64, f64)) -> Track {
+        Track {
+            position: AircraftStatePosition::from(&position(
+                "aircraft",
+                north_meters,
+                altitude_meters,
+            )),
+            velocity: Some(velocity),
+        }
+    }
+
+    #[test]
+    fn test_violation_interval() {
+        // Closing from 1000 m at 10 m/s, within 500 m from 50 s to 150 s
+        let (start, end) = violation_interval(1000. * 1000., 1000. * -10., 100., 500.).unwrap();
+        assert!((start - 50.).abs() < 1e-9);
+        assert!((end - 150.).abs() < 1e-9);
+
+        // Not moving
+        assert!(violation_interval(1000. * 1000., 0., 0., 500.).is_none());
+        assert!(violation_interval(100. * 100., 0., 0., 500.).is_some());
+    }
+
+    #[test]
+    fn test_predict_conflict() {
+        // Head-on at 20 m/s each, 2 km apart
+        let own = track(0., 100., (20., 0., 0.));
+        let other = track(2000., 120., (-20., 0., 0.));
+        let conflict = predict_conflict(&own, &other, &SEPARATION).unwrap();
+        assert!((conflict.seconds_to_conflict - 37.5).abs() < 0.1);
+        assert!((conflict.cpa_seconds - 50.).abs() < 0.1);
+        assert!(conflict.cpa_horizontal_meters < 1.);
+        assert!((conflict.cpa_vertical_meters - 20.).abs() < 1e-6);
+
+        // Vertically separated
+        let other = track(2000., 300., (-20., 0., 0.));
+        assert!(predict_conflict(&own, &other, &SEPARATION).is_none());
+
+        // Beyond the horizon
+        let other = track(4000., 120., (-20., 0., 0.));
+        assert!(predict_conflict(&own, &other, &SEPARATION).is_none());
+
+        // Diverging
+        let other = track(2000., 120., (20., 0., 0.));
+        assert!(predict_conflict(&own, &other, &SEPARATION).is_none());
+
+        // Parked next to each other
+        let own = track(0., 0., (0., 0., 0.));
+        let other = track(50., 0., (0., 0., 0.));
+        assert!(predict_conflict(&own, &other, &SEPARATION).is_none());
+    }
+
+    #[test]
+    fn test_detector() {
+        let detector = ConflictDetector::new(SEPARATION);
+        assert!(detector
+            .update_position(&position("own", 0., 100.), None)
+            .is_empty());
+        detector.update_velocity(&velocity("own", 20., 0.));
+        assert!(detector
+            .update_position(&position("other", 2000., 100.), None)
+            .is_empty());
+        detector.update_velocity(&velocity("other", 20., 180.));
+
+        let alerts = detector.update_position(&position("own", 20., 100.), None);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(
+            alerts[0].identifiers,
+            ["own".to_string(), "other".to_string()]
+        );
+
+        // Already alerted, from either aircraft
+        assert!(detector
+            .update_position(&position("other", 1980., 100.), None)
+            .is_empty());
+
+        // Climbed away, then back into conflict
+        assert!(detector
+            .update_position(&position("other", 1960., 400.), None)
+            .is_empty());
+        assert!(detector.tracks.lock().unwrap().conflicts.is_empty());
+        assert_eq!(
+            detector
+                .update_position(&position("other", 1940., 100.), None)
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_detector_velocity_with_position() {
+        let detector = ConflictDetector::new(SEPARATION);
+        let own = velocity("own", 20., 0.);
+        assert!(detector
+            .update_position(&position("own", 0., 100.), Some(&own))
+            .is_empty());
+
+        // A new aircraft is checked with the velocity received with its position
+        let other = velocity("other", 20., 180.);
+        let alerts = detector.update_position(&position("other", 2000., 100.), Some(&other));
+        assert_eq!(alerts.len(), 1);
+    }
+}
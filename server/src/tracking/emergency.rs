@@ -0,0 +1,249 @@
+//! Emergencies
+//!
+//! Emergencies are raised from the operational status reported over
+//!  Remote ID, the emergency state and squawk reported over ADS-B, and
+//!  abrupt descents in the velocity of any source. Each kind of emergency
+//!  of an aircraft is published once to the emergency exchange, with a
+//!  high priority, and stays active while it is reported again within a
+//!  timeout.
+
+use crate::msg::netrid::OperationalStatus;
+use crate::rest::api::rest_types::{
+    AircraftStatePosition, Emergency, EmergencyKind, TelemetrySource,
+};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Aircraft descending faster than this (m/s) are in an abrupt descent
+const ABRUPT_DESCENT_MPS: f32 = 15.;
+
+/// Emergencies not reported again for this long are no longer active (s)
+const EMERGENCY_EXPIRE_SECONDS: i64 = 60;
+
+/// Squawk of unlawful interference
+const SQUAWK_UNLAWFUL_INTERFERENCE: u32 = 7500;
+
+/// Squawk of radio failure
+const SQUAWK_RADIO_FAILURE: u32 = 7600;
+
+/// Squawk of a general emergency
+const SQUAWK_EMERGENCY: u32 = 7700;
+
+/// Emergency reported in a Remote ID operational status
+pub fn from_operational_status(status: OperationalStatus) -> Option<EmergencyKind> {
+    match status {
+        OperationalStatus::Emergency => Some(EmergencyKind::Emergency),
+        OperationalStatus::SystemFailure => Some(EmergencyKind::SystemFailure),
+        _ => None,
+    }
+}
+
+/// Emergency reported by an ADS-B squawk
+pub fn from_squawk(squawk: u32) -> Option<EmergencyKind> {
+    match squawk {
+        SQUAWK_UNLAWFUL_INTERFERENCE => Some(EmergencyKind::UnlawfulInterference),
+        SQUAWK_RADIO_FAILURE => Some(EmergencyKind::RadioFailure),
+        SQUAWK_EMERGENCY => Some(EmergencyKind::Emergency),
+        _ => None,
+    }
+}
+
+/// Abrupt descent, from a vertical speed (m/s)
+pub fn from_vertical_speed(velocity_vertical_mps: f32) -> Option<EmergencyKind> {
+    (velocity_vertical_mps < -ABRUPT_DESCENT_MPS).then_some(EmergencyKind::AbruptDescent)
+}
+
+/// Active emergencies of aircraft
+#[derive(Debug, Clone, Default)]
+pub struct EmergencyRegistry {
+    active: Arc<Mutex<HashMap<(String, EmergencyKind), Emergency>>>,
+}
+
+impl EmergencyRegistry {
+    /// Creates a registry without emergencies
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reports an emergency of an aircraft, returning it if it wasn't
+    ///  already active
+    pub fn raise(
+        &self,
+        identifier: &str,
+        kind: EmergencyKind,
+        source: TelemetrySource,
+        position: Option<AircraftStatePosition>,
+        now: DateTime<Utc>,
+    ) -> Option<Emergency> {
+        let Ok(mut active) = self.active.lock() else {
+            tracking_error!("(raise) could not lock emergencies.");
+            return None;
+        };
+
+        active.retain(|_, emergency| {
+            (now - emergency.last_reported).num_seconds() < EMERGENCY_EXPIRE_SECONDS
+        });
+
+        if let Some(emergency) = active.get_mut(&(identifier.to_string(), kind)) {
+            emergency.last_reported = now;
+            if position.is_some() {
+                emergency.position = position;
+            }
+
+            return None;
+        }
+
+        let emergency = Emergency {
+            identifier: identifier.to_string(),
+            kind,
+            source,
+            position,
+            started: now,
+            last_reported: now,
+        };
+
+        active.insert((identifier.to_string(), kind), emergency.clone());
+        Some(emergency)
+    }
+
+    /// Returns the active emergencies, oldest first
+    pub fn active(&self, now: DateTime<Utc>) -> Vec<Emergency> {
+        let Ok(active) = self.active.lock() else {
+            tracking_error!("(active) could not lock emergencies.");
+            return vec![];
+        };
+
+        let mut emergencies: Vec<Emergency> = active
+            .values()
+            .filter(|emergency| {
+                (now - emergency.last_reported).num_seconds() < EMERGENCY_EXPIRE_SECONDS
+            })
+            .cloned()
+            .collect();
+
+        emergencies.sort_by_key(|emergency| emergency.started);
+        emergencies
+    }
+}
+
+/// Publishes new emergencies to the emergency exchange
+pub async fn publish_emergencies(
+    mq_channel: &lapin::Channel,
+    emergencies: impl IntoIterator<Item = Emergency>,
+) {
+    for emergency in emergencies {
+        tracking_warn!(
+            "(publish_emergencies) {} emergency {:?} reported over {:?}.",
+            emergency.identifier,
+            emergency.kind,
+            emergency.source
+        );
+
+        let Ok(msg) = serde_json::to_vec(&emergency) else {
+            tracking_warn!("(publish_emergencies) could not serialize emergency.");
+            continue;
+        };
+
+        let _ = mq_channel
+            .basic_publish(
+                crate::amqp::EXCHANGE_NAME_EMERGENCY,
+                crate::amqp::ROUTING_KEY_EMERGENCY,
+                lapin::options::BasicPublishOptions::default(),
+                &msg,
+                lapin::BasicProperties::default()
+                    .with_priority(crate::amqp::EMERGENCY_MAX_PRIORITY)
+                    .with_delivery_mode(2),
+            )
+            .await
+            .map_err(|e| {
+                tracking_warn!("(publish_emergencies) could not push emergency to RabbitMQ: {e}.");
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    fn time(seconds: i64) -> DateTime<Utc> {
+        Utc.timestamp_opt(seconds, 0).unwrap()
+    }
+
+    #[test]
+    fn test_emergency_kinds() {
+        assert_eq!(
+            from_operational_status(OperationalStatus::SystemFailure),
+            Some(EmergencyKind::SystemFailure)
+        );
+        assert_eq!(from_operational_status(OperationalStatus::Airborne), None);
+        assert_eq!(from_squawk(7500), Some(EmergencyKind::UnlawfulInterference));
+        assert_eq!(from_squawk(7600), Some(EmergencyKind::RadioFailure));
+        assert_eq!(from_squawk(7700), Some(EmergencyKind::Emergency));
+        assert_eq!(from_squawk(1200), None);
+        assert_eq!(
+            from_vertical_speed(-20.),
+            Some(EmergencyKind::AbruptDescent)
+        );
+        assert_eq!(from_vertical_speed(-5.), None);
+    }
+
+    #[test]
+    fn test_raise_deduplicated() {
+        let registry = EmergencyRegistry::new();
+        let raise = |kind, seconds| {
+            registry.raise(
+                "aircraft",
+                kind,
+                TelemetrySource::Netrid,
+                None,
+                time(seconds),
+            )
+        };
+
+        let emergency = raise(EmergencyKind::Emergency, 0).unwrap();
+        assert_eq!(emergency.started, time(0));
+        assert!(raise(EmergencyKind::Emergency, 30).is_none());
+        assert!(raise(EmergencyKind::AbruptDescent, 30).is_some());
+
+        let active = registry.active(time(40));
+        assert_eq!(active.len(), 2);
+        assert_eq!(active[0].kind, EmergencyKind::Emergency);
+        assert_eq!(active[0].last_reported, time(30));
+
+        // Expired, and raised again
+        assert!(registry.active(time(90)).is_empty());
+        assert!(raise(EmergencyKind::Emergency, 100).is_some());
+        assert_eq!(registry.active.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_raise_keeps_position() {
+        let registry = EmergencyRegistry::new();
+        let position = AircraftStatePosition {
+            latitude: 52.,
+            longitude: 4.,
+            altitude_meters: 100.,
+            timestamp_network: time(0),
+            timestamp_asset: None,
+        };
+
+        registry.raise(
+            "aircraft",
+            EmergencyKind::Emergency,
+            TelemetrySource::Adsb,
+            Some(position.clone()),
+            time(0),
+        );
+        registry.raise(
+            "aircraft",
+            EmergencyKind::Emergency,
+            TelemetrySource::Adsb,
+            None,
+            time(0) + Duration::seconds(1),
+        );
+
+        assert_eq!(registry.active(time(2))[0].position, Some(position));
+    }
+}
@@ -0,0 +1,422 @@
+//! Live telemetry events
+//!
+//! Decoded identities, positions and velocities, and restricted zone
+//!  intrusions, are broadcast to live subscribers as they are processed.
+//!  Each subscriber receives the events matching its own filter.
+//!  Subscribers which fall behind skip the oldest events rather than
+//!  slowing down the processing of telemetry, or keep only the latest
+//!  [pending](PendingEvents) event of each aircraft.
+
+use super::spatial::BoundingBox;
+use super::zones::ZoneAlert;
+use crate::rest::api::rest_types::{
+    AircraftStatePosition, AircraftStateVelocity, TelemetryEvent, TelemetryEventKind,
+    TelemetrySource,
+};
+use futures::stream::{self, Stream};
+use once_cell::sync::OnceCell;
+use std::collections::{HashMap, HashSet, VecDeque};
+use svc_gis_client_grpc::prelude::types::*;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+/// Number of events a subscriber can fall behind before skipping events
+const EVENT_CHANNEL_CAPACITY: usize = 1024;
+
+/// Number of aircraft with pending events a slow subscriber can hold
+///  before dropping the oldest
+const MAX_PENDING_EVENTS: usize = 1024;
+
+/// Bus of live telemetry events, shared by the REST and gRPC servers
+static EVENT_BUS: OnceCell<EventBus> = OnceCell::new();
+
+/// Returns the bus of live telemetry events, creating it if needed
+pub fn get_event_bus() -> &'static EventBus {
+    EVENT_BUS.get_or_init(EventBus::new)
+}
+
+/// Broadcasts live telemetry events to subscribers
+#[derive(Debug, Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<TelemetryEvent>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventBus {
+    /// Creates a bus without subscribers
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        EventBus { sender }
+    }
+
+    /// Broadcasts an event to current subscribers
+    pub fn publish(&self, event: TelemetryEvent) {
+        // Fails only if there are no subscribers
+        let _ = self.sender.send(event);
+    }
+
+    /// Returns the stream of future events matching a filter
+    pub fn subscribe(
+        &self,
+        filter: EventFilter,
+    ) -> impl Stream<Item = TelemetryEvent> + Send + 'static {
+        stream::unfold(
+            (self.sender.subscribe(), filter),
+            |(mut receiver, mut filter)| async move {
+                loop {
+                    match receiver.recv().await {
+                        Ok(event) if filter.matches(&event) => {
+                            return Some((event, (receiver, filter)));
+                        }
+                        Ok(_) => (),
+                        Err(RecvError::Lagged(n)) => {
+                            tracking_warn!(
+                                "(subscribe) subscriber fell behind, skipped {n} events."
+                            );
+                        }
+                        Err(RecvError::Closed) => return None,
+                    }
+                }
+            },
+        )
+    }
+}
+
+/// Name of an event kind, as used in filters
+pub fn kind_name(kind: TelemetryEventKind) -> &'static str {
+    match kind {
+        TelemetryEventKind::Id => "id",
+        TelemetryEventKind::Position => "position",
+        TelemetryEventKind::Velocity => "velocity",
+        TelemetryEventKind::Intrusion => "intrusion",
+    }
+}
+
+/// Parses the name of an event kind
+pub fn parse_kind(name: &str) -> Option<TelemetryEventKind> {
+    match name.trim() {
+        "id" => Some(TelemetryEventKind::Id),
+        "position" => Some(TelemetryEventKind::Position),
+        "velocity" => Some(TelemetryEventKind::Velocity),
+        "intrusion" => Some(TelemetryEventKind::Intrusion),
+        _ => None,
+    }
+}
+
+/// Creates an identity event
+pub fn identity_event(item: &AircraftId, source: TelemetrySource) -> Option<TelemetryEvent> {
+    Some(TelemetryEvent {
+        identifier: item.identifier.clone()?,
+        kind: TelemetryEventKind::Id,
+        source,
+        aircraft_type: Some(format!("{:?}", item.aircraft_type)),
+        position: None,
+        velocity: None,
+        intrusion: None,
+    })
+}
+
+/// Creates a position event
+pub fn position_event(item: &AircraftPosition, source: TelemetrySource) -> TelemetryEvent {
+    TelemetryEvent {
+        identifier: item.identifier.clone(),
+        kind: TelemetryEventKind::Position,
+        source,
+        aircraft_type: None,
+        position: Some(AircraftStatePosition::from(item)),
+        velocity: None,
+        intrusion: None,
+    }
+}
+
+/// Creates a velocity event
+pub fn velocity_event(item: &AircraftVelocity, source: TelemetrySource) -> TelemetryEvent {
+    TelemetryEvent {
+        identifier: item.identifier.clone(),
+        kind: TelemetryEventKind::Velocity,
+        source,
+        aircraft_type: None,
+        position: None,
+        velocity: Some(AircraftStateVelocity::from(item)),
+        intrusion: None,
+    }
+}
+
+/// Creates an intrusion event
+pub fn intrusion_event(alert: &ZoneAlert, source: TelemetrySource) -> TelemetryEvent {
+    TelemetryEvent {
+        identifier: alert.identifier.clone(),
+        kind: TelemetryEventKind::Intrusion,
+        source,
+        aircraft_type: None,
+        position: Some(alert.position.clone()),
+        velocity: None,
+        intrusion: Some(alert.intrusion.clone()),
+    }
+}
+
+/// Filter of the events sent to a subscriber
+///
+/// With a bounding box, identity and velocity events pass only for
+///  aircraft whose latest position seen by the subscriber is inside it.
+#[derive(Debug, Clone, Default)]
+pub struct EventFilter {
+    /// Aircraft to include, all if empty
+    identifiers: HashSet<String>,
+
+    /// Kinds of events to include, all if empty
+    kinds: HashSet<TelemetryEventKind>,
+
+    /// Area to include, everywhere if not given
+    bounding_box: Option<BoundingBox>,
+
+    /// Aircraft whose latest position is inside the bounding box
+    inside: HashSet<String>,
+}
+
+impl EventFilter {
+    /// Creates a filter, or returns `None` if the bounding box is invalid
+    pub fn new(
+        identifiers: impl IntoIterator<Item = String>,
+        kinds: impl IntoIterator<Item = TelemetryEventKind>,
+        bounding_box: Option<BoundingBox>,
+    ) -> Option<Self> {
+        if bounding_box.map_or(false, |bounding_box| !bounding_box.is_valid()) {
+            return None;
+        }
+
+        Some(EventFilter {
+            identifiers: identifiers.into_iter().collect(),
+            kinds: kinds.into_iter().collect(),
+            bounding_box,
+            inside: HashSet::new(),
+        })
+    }
+
+    /// If an event passes the filter
+    pub fn matches(&mut self, event: &TelemetryEvent) -> bool {
+        if !self.identifiers.is_empty() && !self.identifiers.contains(&event.identifier) {
+            return false;
+        }
+
+        // Track aircraft entering and leaving the box, even if position
+        //  events are filtered out
+        let inside = match (&self.bounding_box, &event.position) {
+            (None, _) => true,
+            (Some(bounding_box), Some(position)) => {
+                let inside = bounding_box.contains(position.latitude, position.longitude);
+                if inside {
+                    self.inside.insert(event.identifier.clone());
+                } else {
+                    self.inside.remove(&event.identifier);
+                }
+
+                inside
+            }
+            (Some(_), None) => self.inside.contains(&event.identifier),
+        };
+
+        inside && (self.kinds.is_empty() || self.kinds.contains(&event.kind))
+    }
+}
+
+/// Events waiting to be sent to a slow subscriber
+///
+/// Only the latest event of each kind is kept for each aircraft, in the
+///  order the aircraft first became pending. When too many aircraft are
+///  pending, the oldest ones are dropped.
+#[derive(Debug, Default)]
+pub struct PendingEvents {
+    /// Pending aircraft and event kinds, oldest first
+    order: VecDeque<(String, TelemetryEventKind)>,
+
+    /// Latest event of each pending aircraft and event kind
+    latest: HashMap<(String, TelemetryEventKind), TelemetryEvent>,
+
+    /// Number of events dropped or replaced since the last call to `take_dropped`
+    dropped: u64,
+}
+
+impl PendingEvents {
+    /// Adds an event, replacing a pending event of the same aircraft and kind
+    pub fn push(&mut self, event: TelemetryEvent) {
+        let key = (event.identifier.clone(), event.kind);
+        if self.latest.insert(key.clone(), event).is_some() {
+            self.dropped += 1;
+            return;
+        }
+
+        self.order.push_back(key);
+        if self.order.len() > MAX_PENDING_EVENTS {
+            if let Some(oldest) = self.order.pop_front() {
+                self.latest.remove(&oldest);
+                self.dropped += 1;
+            }
+        }
+    }
+
+    /// Removes the oldest pending event
+    pub fn pop(&mut self) -> Option<TelemetryEvent> {
+        let key = self.order.pop_front()?;
+        self.latest.remove(&key)
+    }
+
+    /// If no events are pending
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+
+    /// Returns the number of events dropped or replaced, and resets it
+    pub fn take_dropped(&mut self) -> u64 {
+        std::mem::take(&mut self.dropped)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use futures::StreamExt;
+
+    fn position(identifier: &str, latitude: f64, longitude: f64) -> TelemetryEvent {
+        let item = AircraftPosition {
+            identifier: identifier.to_string(),
+            position: Position {
+                latitude,
+                longitude,
+                altitude_meters: 100.,
+            },
+            timestamp_network: Utc::now(),
+            timestamp_asset: None,
+        };
+
+        position_event(&item, TelemetrySource::Netrid)
+    }
+
+    fn velocity(identifier: &str) -> TelemetryEvent {
+        let item = AircraftVelocity {
+            identifier: identifier.to_string(),
+            velocity_horizontal_ground_mps: 10.,
+            velocity_horizontal_air_mps: None,
+            velocity_vertical_mps: 0.,
+            track_angle_degrees: 90.,
+            timestamp_network: Utc::now(),
+            timestamp_asset: None,
+        };
+
+        velocity_event(&item, TelemetrySource::Netrid)
+    }
+
+    #[test]
+    fn test_parse_kind() {
+        for kind in [
+            TelemetryEventKind::Id,
+            TelemetryEventKind::Position,
+            TelemetryEventKind::Velocity,
+            TelemetryEventKind::Intrusion,
+        ] {
+            assert_eq!(parse_kind(kind_name(kind)), Some(kind));
+        }
+
+        assert_eq!(parse_kind(" position"), Some(TelemetryEventKind::Position));
+        assert_eq!(parse_kind("altitude"), None);
+    }
+
+    #[test]
+    fn test_filter_identifiers_and_kinds() {
+        let mut filter = EventFilter::new(
+            vec!["a".to_string()],
+            vec![TelemetryEventKind::Velocity],
+            None,
+        )
+        .unwrap();
+
+        assert!(filter.matches(&velocity("a")));
+        assert!(!filter.matches(&velocity("b")));
+        assert!(!filter.matches(&position("a", 52., 4.)));
+
+        let mut filter = EventFilter::default();
+        assert!(filter.matches(&velocity("b")));
+        assert!(filter.matches(&position("a", 52., 4.)));
+    }
+
+    #[test]
+    fn test_filter_bounding_box() {
+        let bounding_box = BoundingBox {
+            min_latitude: 52.,
+            min_longitude: 4.,
+            max_latitude: 53.,
+            max_longitude: 5.,
+        };
+
+        let mut filter = EventFilter::new(vec![], vec![], Some(bounding_box)).unwrap();
+
+        // No position seen yet
+        assert!(!filter.matches(&velocity("a")));
+
+        assert!(filter.matches(&position("a", 52.5, 4.5)));
+        assert!(filter.matches(&velocity("a")));
+
+        // Left the box
+        assert!(!filter.matches(&position("a", 51., 4.5)));
+        assert!(!filter.matches(&velocity("a")));
+
+        let inverted = BoundingBox {
+            min_latitude: 53.,
+            max_latitude: 52.,
+            ..bounding_box
+        };
+
+        assert!(EventFilter::new(vec![], vec![], Some(inverted)).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_subscribe() {
+        let bus = EventBus::new();
+        let filter = EventFilter::new(vec!["a".to_string()], vec![], None).unwrap();
+        let mut events = Box::pin(bus.subscribe(filter));
+
+        bus.publish(velocity("b"));
+        bus.publish(velocity("a"));
+
+        let event = events.next().await.unwrap();
+        assert_eq!(event.identifier, "a");
+        assert_eq!(event.kind, TelemetryEventKind::Velocity);
+    }
+
+    #[test]
+    fn test_pending_events_replace() {
+        let mut pending = PendingEvents::default();
+        pending.push(position("a", 52., 4.));
+        pending.push(velocity("a"));
+        pending.push(position("b", 52., 4.));
+        pending.push(position("a", 52.5, 4.5));
+
+        // The newer position of "a" keeps the place of the older one
+        let event = pending.pop().unwrap();
+        assert_eq!(event.identifier, "a");
+        assert_eq!(event.position.unwrap().latitude, 52.5);
+        assert_eq!(pending.pop().unwrap().kind, TelemetryEventKind::Velocity);
+        assert_eq!(pending.pop().unwrap().identifier, "b");
+        assert!(pending.pop().is_none());
+        assert!(pending.is_empty());
+        assert_eq!(pending.take_dropped(), 1);
+        assert_eq!(pending.take_dropped(), 0);
+    }
+
+    #[test]
+    fn test_pending_events_overflow() {
+        let mut pending = PendingEvents::default();
+        for i in 0..=MAX_PENDING_EVENTS {
+            pending.push(velocity(&i.to_string()));
+        }
+
+        // The oldest aircraft was dropped
+        assert_eq!(pending.pop().unwrap().identifier, "1");
+        assert_eq!(pending.take_dropped(), 1);
+    }
+}
@@ -0,0 +1,570 @@
+//! Multi-source track fusion
+//!
+//! An aircraft may report its own position over Remote ID while also
+//!  being heard over ADS-B by several reporters. These interleaved
+//!  updates are merged per aircraft by an alpha-beta filter, and a single
+//!  smoothed state is pushed to svc-gis and published at a fixed interval
+//!  along with the sources which contributed to it.
+//!
+//! Updates are timed by when they were received, since only some sources
+//!  report the time of the aircraft. Updates older than the track are
+//!  dropped rather than applied out of order.
+
+use super::EARTH_RADIUS_METERS;
+use crate::cache::pool::GisPool;
+use crate::rest::api::rest_types::TelemetrySource;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use svc_gis_client_grpc::prelude::types::*;
+
+/// Weight of a position measurement's residual on the position estimate
+const ALPHA: f64 = 0.5;
+
+/// Weight of a position measurement's residual on the rate estimate
+const BETA: f64 = 0.2;
+
+/// Weight of a velocity measurement on the rate estimate
+const VELOCITY_GAIN: f64 = 0.5;
+
+/// Shortest interval over which a position residual updates the rate,
+///  shorter intervals give noisy rates
+const MIN_RATE_INTERVAL_SECONDS: f64 = 0.1;
+
+/// Longest time a track is extrapolated beyond its last update
+const MAX_EXTRAPOLATION_SECONDS: f64 = 2.;
+
+/// Tracks without updates for this long are dropped
+const TRACK_EXPIRE_SECONDS: i64 = 60;
+
+/// A source of track updates
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TrackSource {
+    /// Protocol of the update
+    pub protocol: TelemetrySource,
+
+    /// Identifier of the reporter, or of the aircraft itself
+    pub reporter_id: String,
+}
+
+impl TrackSource {
+    /// Creates a source from a protocol and reporter
+    pub fn new(protocol: TelemetrySource, reporter_id: &str) -> Self {
+        TrackSource {
+            protocol,
+            reporter_id: reporter_id.to_string(),
+        }
+    }
+}
+
+/// A source which contributed to a fused state
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ContributingSource {
+    /// Protocol of the updates
+    pub protocol: TelemetrySource,
+
+    /// Identifier of the reporter, or of the aircraft itself
+    pub reporter_id: String,
+
+    /// Number of updates from this source since the last fused state
+    pub updates: u32,
+
+    /// Time of the latest update from this source
+    pub last_update: DateTime<Utc>,
+}
+
+/// Smoothed state of an aircraft, fused from all sources
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FusedState {
+    /// Identifier of the aircraft
+    pub identifier: String,
+
+    /// Latitude (degrees)
+    pub latitude: f64,
+
+    /// Longitude (degrees)
+    pub longitude: f64,
+
+    /// Altitude (m)
+    pub altitude_meters: f64,
+
+    /// Ground speed (m/s)
+    pub velocity_horizontal_ground_mps: f64,
+
+    /// Vertical speed (m/s), positive when climbing
+    pub velocity_vertical_mps: f64,
+
+    /// Track angle (degrees clockwise from true north)
+    pub track_angle_degrees: f64,
+
+    /// Time of the state
+    pub timestamp: DateTime<Utc>,
+
+    /// Sources which contributed since the last fused state
+    pub sources: Vec<ContributingSource>,
+}
+
+impl From<&FusedState> for AircraftPosition {
+    fn from(state: &FusedState) -> Self {
+        AircraftPosition {
+            identifier: state.identifier.clone(),
+            position: Position {
+                latitude: state.latitude,
+                longitude: state.longitude,
+                altitude_meters: state.altitude_meters,
+            },
+            timestamp_network: state.timestamp,
+            timestamp_asset: None,
+        }
+    }
+}
+
+impl From<&FusedState> for AircraftVelocity {
+    fn from(state: &FusedState) -> Self {
+        AircraftVelocity {
+            identifier: state.identifier.clone(),
+            velocity_horizontal_ground_mps: state.velocity_horizontal_ground_mps as f32,
+            velocity_horizontal_air_mps: None,
+            velocity_vertical_mps: state.velocity_vertical_mps as f32,
+            track_angle_degrees: state.track_angle_degrees as f32,
+            timestamp_network: state.timestamp,
+            timestamp_asset: None,
+        }
+    }
+}
+
+/// Alpha-beta filter of a single axis
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct AlphaBeta {
+    /// Estimated value (m)
+    value: f64,
+
+    /// Estimated rate of change (m/s)
+    rate: f64,
+}
+
+impl AlphaBeta {
+    /// Predicted value after `dt` seconds
+    fn predict(&self, dt: f64) -> f64 {
+        self.value + self.rate * dt
+    }
+
+    /// Updates the filter with a measured value, `dt` seconds after the last update
+    fn update_value(&mut self, measured: f64, dt: f64) {
+        let predicted = self.predict(dt);
+        let residual = measured - predicted;
+        self.value = predicted + ALPHA * residual;
+        if dt >= MIN_RATE_INTERVAL_SECONDS {
+            self.rate += BETA * residual / dt;
+        }
+    }
+
+    /// Updates the filter with a measured rate, `dt` seconds after the last update
+    fn update_rate(&mut self, measured_rate: f64, dt: f64) {
+        self.value = self.predict(dt);
+        self.rate += VELOCITY_GAIN * (measured_rate - self.rate);
+    }
+}
+
+/// Fused track of a single aircraft
+///
+/// Positions are filtered in a local plane (north, east, up) in meters,
+///  centered on the first position of the track.
+#[derive(Debug, Clone)]
+struct Track {
+    origin_latitude: f64,
+    origin_longitude: f64,
+    north: AlphaBeta,
+    east: AlphaBeta,
+    up: AlphaBeta,
+    timestamp: DateTime<Utc>,
+    sources: HashMap<TrackSource, ContributingSource>,
+}
+
+impl Track {
+    /// Starts a track at a position
+    fn new(position: &Position, timestamp: DateTime<Utc>) -> Self {
+        Track {
+            origin_latitude: position.latitude,
+            origin_longitude: position.longitude,
+            north: AlphaBeta::default(),
+            east: AlphaBeta::default(),
+            up: AlphaBeta {
+                value: position.altitude_meters,
+                rate: 0.,
+            },
+            timestamp,
+            sources: HashMap::new(),
+        }
+    }
+
+    /// Converts a position to meters north and east of the track's origin
+    fn local_position(&self, latitude: f64, longitude: f64) -> (f64, f64) {
+        let north = (latitude - self.origin_latitude).to_radians() * EARTH_RADIUS_METERS;
+        let east = (longitude - self.origin_longitude).to_radians()
+            * EARTH_RADIUS_METERS
+            * self.origin_latitude.to_radians().cos();
+
+        (north, east)
+    }
+
+    /// Converts meters north and east of the track's origin to a position
+    fn geodetic_position(&self, north: f64, east: f64) -> (f64, f64) {
+        let latitude = self.origin_latitude + (north / EARTH_RADIUS_METERS).to_degrees();
+        let longitude = self.origin_longitude
+            + (east / (EARTH_RADIUS_METERS * self.origin_latitude.to_radians().cos())).to_degrees();
+
+        (latitude, longitude)
+    }
+
+    /// Seconds since the last update, and advances the track's time.
+    /// Returns `None` for updates older than the track.
+    fn advance(&mut self, timestamp: DateTime<Utc>) -> Option<f64> {
+        let dt = (timestamp - self.timestamp).num_milliseconds() as f64 / 1000.;
+        if dt < 0. {
+            return None;
+        }
+
+        self.timestamp = timestamp;
+        Some(dt)
+    }
+
+    /// Records an update from a source
+    fn record_source(&mut self, source: TrackSource, timestamp: DateTime<Utc>) {
+        let entry = self
+            .sources
+            .entry(source.clone())
+            .or_insert(ContributingSource {
+                protocol: source.protocol,
+                reporter_id: source.reporter_id,
+                updates: 0,
+                last_update: timestamp,
+            });
+
+        entry.updates += 1;
+        entry.last_update = timestamp;
+    }
+
+    /// Updates the track with a position, returning if it was applied.
+    /// Positions which aren't newer than the track are dropped.
+    fn update_position(&mut self, position: &Position, timestamp: DateTime<Utc>) -> bool {
+        let Some(dt) = self.advance(timestamp).filter(|dt| *dt > 0.) else {
+            return false;
+        };
+
+        let (north, east) = self.local_position(position.latitude, position.longitude);
+        self.north.update_value(north, dt);
+        self.east.update_value(east, dt);
+        self.up.update_value(position.altitude_meters, dt);
+        true
+    }
+
+    /// Updates the track with a velocity, returning if it was applied.
+    /// Velocities older than the track are dropped, a velocity received
+    ///  with the last position updates the rates at that position.
+    fn update_velocity(&mut self, velocity: &AircraftVelocity, timestamp: DateTime<Utc>) -> bool {
+        let Some(dt) = self.advance(timestamp) else {
+            return false;
+        };
+
+        let speed = velocity.velocity_horizontal_ground_mps as f64;
+        let track_angle = (velocity.track_angle_degrees as f64).to_radians();
+        self.north.update_rate(speed * track_angle.cos(), dt);
+        self.east.update_rate(speed * track_angle.sin(), dt);
+        self.up
+            .update_rate(velocity.velocity_vertical_mps as f64, dt);
+        true
+    }
+
+    /// The fused state of the track at a time, extrapolated from the last update
+    fn state(&self, identifier: &str, now: DateTime<Utc>) -> FusedState {
+        let dt = ((now - self.timestamp).num_milliseconds() as f64 / 1000.)
+            .clamp(0., MAX_EXTRAPOLATION_SECONDS);
+
+        let (latitude, longitude) =
+            self.geodetic_position(self.north.predict(dt), self.east.predict(dt));
+        let track_angle_degrees = self
+            .east
+            .rate
+            .atan2(self.north.rate)
+            .to_degrees()
+            .rem_euclid(360.);
+
+        FusedState {
+            identifier: identifier.to_string(),
+            latitude,
+            longitude,
+            altitude_meters: self.up.predict(dt),
+            velocity_horizontal_ground_mps: self.north.rate.hypot(self.east.rate),
+            velocity_vertical_mps: self.up.rate,
+            track_angle_degrees,
+            timestamp: self.timestamp + chrono::Duration::milliseconds((dt * 1000.) as i64),
+            sources: self.sources.values().cloned().collect(),
+        }
+    }
+}
+
+/// Fused tracks of all aircraft, shared between request handlers
+#[derive(Debug, Clone, Default)]
+pub struct TrackFusion {
+    tracks: Arc<Mutex<HashMap<String, Track>>>,
+}
+
+impl TrackFusion {
+    /// Creates an empty set of tracks
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Updates the track of an aircraft with a position
+    pub fn update_position(&self, position: &AircraftPosition, source: TrackSource) {
+        let timestamp = position.timestamp_network;
+        let Ok(mut tracks) = self.tracks.lock() else {
+            tracking_error!("(update_position) could not lock tracks.");
+            return;
+        };
+
+        let Some(track) = tracks.get_mut(&position.identifier) else {
+            let mut track = Track::new(&position.position, timestamp);
+            track.record_source(source, timestamp);
+            tracks.insert(position.identifier.clone(), track);
+            return;
+        };
+
+        if !track.update_position(&position.position, timestamp) {
+            tracking_debug!(
+                "(update_position) stale position of {}, ignoring.",
+                position.identifier
+            );
+            return;
+        }
+
+        track.record_source(source, timestamp);
+    }
+
+    /// Updates the track of an aircraft with a velocity
+    ///
+    /// Velocities of aircraft without a known position are ignored.
+    pub fn update_velocity(&self, velocity: &AircraftVelocity, source: TrackSource) {
+        let timestamp = velocity.timestamp_network;
+        let Ok(mut tracks) = self.tracks.lock() else {
+            tracking_error!("(update_velocity) could not lock tracks.");
+            return;
+        };
+
+        let Some(track) = tracks.get_mut(&velocity.identifier) else {
+            tracking_debug!(
+                "(update_velocity) no track for {}, ignoring velocity.",
+                velocity.identifier
+            );
+            return;
+        };
+
+        if !track.update_velocity(velocity, timestamp) {
+            tracking_debug!(
+                "(update_velocity) stale velocity of {}, ignoring.",
+                velocity.identifier
+            );
+            return;
+        }
+
+        track.record_source(source, timestamp);
+    }
+
+    /// Takes the fused states of all tracks updated since the last call
+    ///
+    /// Tracks without updates for a while are dropped.
+    pub fn take_states(&self, now: DateTime<Utc>) -> Vec<FusedState> {
+        let Ok(mut tracks) = self.tracks.lock() else {
+            tracking_error!("(take_states) could not lock tracks.");
+            return vec![];
+        };
+
+        tracks.retain(|_, track| (now - track.timestamp).num_seconds() < TRACK_EXPIRE_SECONDS);
+        tracks
+            .iter_mut()
+            .filter(|(_, track)| !track.sources.is_empty())
+            .map(|(identifier, track)| {
+                let state = track.state(identifier, now);
+                track.sources.clear();
+                state
+            })
+            .collect()
+    }
+}
+
+/// Pushes fused states to svc-gis and publishes them to RabbitMQ at a
+///  fixed interval
+#[cfg(not(tarpaulin_include))]
+// no_coverage: Needs running backends to work.
+pub async fn publish_loop(
+    fusion: TrackFusion,
+    mut gis_pool: GisPool,
+    mq_channel: lapin::Channel,
+    interval_ms: u32,
+) {
+    tracking_info!("(publish_loop) publishing fused states every {interval_ms} ms.");
+    let mut interval =
+        tokio::time::interval(std::time::Duration::from_millis(interval_ms.max(1) as u64));
+
+    loop {
+        interval.tick().await;
+        for state in fusion.take_states(Utc::now()) {
+            if gis_pool
+                .push(AircraftPosition::from(&state), REDIS_KEY_AIRCRAFT_POSITION)
+                .await
+                .is_err()
+            {
+                tracking_warn!("(publish_loop) could not push fused position to svc-gis.");
+            }
+
+            if gis_pool
+                .push(AircraftVelocity::from(&state), REDIS_KEY_AIRCRAFT_VELOCITY)
+                .await
+                .is_err()
+            {
+                tracking_warn!("(publish_loop) could not push fused velocity to svc-gis.");
+            }
+
+            let Ok(msg) = serde_json::to_vec(&state) else {
+                tracking_warn!("(publish_loop) could not serialize fused state.");
+                continue;
+            };
+
+            let _ = mq_channel
+                .basic_publish(
+                    crate::amqp::EXCHANGE_NAME_TELEMETRY,
+                    crate::amqp::ROUTING_KEY_FUSED_STATE,
+                    lapin::options::BasicPublishOptions::default(),
+                    &msg,
+                    lapin::BasicProperties::default(),
+                )
+                .await
+                .map_err(|e| {
+                    tracking_warn!("(publish_loop) could not push fused state to RabbitMQ: {e}.");
+                });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    fn time(milliseconds: i64) -> DateTime<Utc> {
+        Utc.timestamp_opt(0, 0).unwrap() + Duration::milliseconds(milliseconds)
+    }
+
+    fn position(latitude: f64, longitude: f64, milliseconds: i64) -> AircraftPosition {
+        AircraftPosition {
+            identifier: "aircraft".to_string(),
+            position: Position {
+                latitude,
+                longitude,
+                altitude_meters: 100.,
+            },
+            timestamp_network: time(milliseconds),
+            timestamp_asset: None,
+        }
+    }
+
+    #[test]
+    fn test_local_conversion() {
+        let track = Track::new(&position(52., 4., 0).position, time(0));
+        let (north, east) = track.local_position(52.01, 4.01);
+        let (latitude, longitude) = track.geodetic_position(north, east);
+        assert!((latitude - 52.01).abs() < 1e-9);
+        assert!((longitude - 4.01).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_fusion_converges_on_velocity() {
+        let fusion = TrackFusion::new();
+        let netrid = TrackSource::new(TelemetrySource::Netrid, "aircraft");
+        let adsb = TrackSource::new(TelemetrySource::Adsb, "reporter");
+
+        // Flying north at ~11 m/s, reported alternately by two sources
+        for i in 0..40 {
+            let source = if i % 2 == 0 { &netrid } else { &adsb };
+            let latitude = 52. + 0.0001 * i as f64;
+            fusion.update_position(&position(latitude, 4., i * 1000), source.clone());
+        }
+
+        let states = fusion.take_states(time(39_000));
+        assert_eq!(states.len(), 1);
+
+        let state = &states[0];
+        assert!((state.velocity_horizontal_ground_mps - 11.1).abs() < 1.);
+        assert!(state.track_angle_degrees < 5. || state.track_angle_degrees > 355.);
+        assert_eq!(state.sources.len(), 2);
+        assert_eq!(state.sources.iter().map(|s| s.updates).sum::<u32>(), 40);
+
+        // Sources are cleared after each fused state
+        assert!(fusion.take_states(time(40_000)).is_empty());
+    }
+
+    #[test]
+    fn test_velocity_without_track_ignored() {
+        let fusion = TrackFusion::new();
+        let velocity = AircraftVelocity {
+            identifier: "aircraft".to_string(),
+            velocity_horizontal_ground_mps: 10.,
+            velocity_horizontal_air_mps: None,
+            velocity_vertical_mps: 0.,
+            track_angle_degrees: 90.,
+            timestamp_network: time(0),
+            timestamp_asset: None,
+        };
+
+        fusion.update_velocity(
+            &velocity,
+            TrackSource::new(TelemetrySource::Adsb, "reporter"),
+        );
+        assert!(fusion.take_states(time(0)).is_empty());
+    }
+
+    #[test]
+    fn test_out_of_order_updates_dropped() {
+        let fusion = TrackFusion::new();
+        let source = TrackSource::new(TelemetrySource::Netrid, "aircraft");
+        fusion.update_position(&position(52., 4., 1000), source.clone());
+
+        // Older and repeated positions don't move the track or count as updates
+        fusion.update_position(&position(53., 4., 0), source.clone());
+        fusion.update_position(&position(53., 4., 1000), source);
+
+        let states = fusion.take_states(time(1000));
+        assert_eq!(states.len(), 1);
+        assert!((states[0].latitude - 52.).abs() < 1e-9);
+        assert_eq!(states[0].sources[0].updates, 1);
+    }
+
+    #[test]
+    fn test_fused_state_to_gis() {
+        let fusion = TrackFusion::new();
+        let source = TrackSource::new(TelemetrySource::Netrid, "aircraft");
+        fusion.update_position(&position(52., 4., 0), source);
+
+        let state = fusion.take_states(time(0)).pop().unwrap();
+        let position = AircraftPosition::from(&state);
+        assert_eq!(position.identifier, "aircraft");
+        assert_eq!(position.position.latitude, 52.);
+        assert_eq!(position.timestamp_network, time(0));
+
+        let velocity = AircraftVelocity::from(&state);
+        assert_eq!(velocity.identifier, "aircraft");
+        assert_eq!(velocity.velocity_horizontal_ground_mps, 0.);
+    }
+
+    #[test]
+    fn test_stale_tracks_dropped() {
+        let fusion = TrackFusion::new();
+        let source = TrackSource::new(TelemetrySource::Netrid, "aircraft");
+        fusion.update_position(&position(52., 4., 0), source);
+
+        let states = fusion.take_states(time(TRACK_EXPIRE_SECONDS * 1000 + 1));
+        assert!(states.is_empty());
+    }
+}
@@ -0,0 +1,332 @@
+//! Vertiport geofences
+//!
+//! Vertiport managers define geofences around their vertiports, such as
+//!  the approach area or the pad, each a polygon between an altitude floor
+//!  and ceiling. Every accepted position is checked against the geofences,
+//!  and an event is published whenever an aircraft enters or exits one.
+//!
+//! Geofences are loaded at startup from the JSON file named by
+//!  `VERTIPORT_GEOFENCES_FILE`, an array of [`Geofence`] objects.
+
+use super::spatial::polygon_contains;
+use crate::rest::api::rest_types::AircraftStatePosition;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use svc_gis_client_grpc::prelude::types::*;
+
+/// A geofence of a vertiport
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Geofence {
+    /// Identifier of the vertiport
+    pub vertiport_id: String,
+
+    /// Name of the geofence, unique within the vertiport
+    pub name: String,
+
+    /// Vertices of the polygon, as (latitude, longitude) pairs (degrees)
+    pub vertices: Vec<(f64, f64)>,
+
+    /// Lowest altitude inside the geofence (m)
+    pub floor_meters: f64,
+
+    /// Highest altitude inside the geofence (m)
+    pub ceiling_meters: f64,
+}
+
+impl Geofence {
+    /// If the geofence has a polygon and a floor below its ceiling
+    pub fn is_valid(&self) -> bool {
+        self.vertices.len() >= 3
+            && self.vertices.iter().all(|(latitude, longitude)| {
+                (-90. ..=90.).contains(latitude) && (-180. ..=180.).contains(longitude)
+            })
+            && self.floor_meters < self.ceiling_meters
+    }
+
+    /// If a position is inside the geofence
+    pub fn contains(&self, latitude: f64, longitude: f64, altitude_meters: f64) -> bool {
+        (self.floor_meters..=self.ceiling_meters).contains(&altitude_meters)
+            && polygon_contains(&self.vertices, latitude, longitude)
+    }
+}
+
+/// Errors loading geofences
+#[derive(Debug)]
+pub enum GeofenceError {
+    /// The file could not be read
+    Read(std::io::Error),
+
+    /// The file is not an array of geofences
+    Parse(serde_json::Error),
+
+    /// A geofence has too few vertices, or an inverted altitude range
+    Invalid(String),
+}
+
+impl std::fmt::Display for GeofenceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GeofenceError::Read(e) => write!(f, "could not read file: {e}"),
+            GeofenceError::Parse(e) => write!(f, "could not parse file: {e}"),
+            GeofenceError::Invalid(name) => write!(f, "invalid geofence {name}"),
+        }
+    }
+}
+
+/// Parses an array of geofences
+fn parse_geofences(text: &str) -> Result<Vec<Geofence>, GeofenceError> {
+    let geofences: Vec<Geofence> = serde_json::from_str(text).map_err(GeofenceError::Parse)?;
+    if let Some(geofence) = geofences.iter().find(|geofence| !geofence.is_valid()) {
+        return Err(GeofenceError::Invalid(format!(
+            "{}/{}",
+            geofence.vertiport_id, geofence.name
+        )));
+    }
+
+    Ok(geofences)
+}
+
+/// Loads geofences from a JSON file
+pub fn load_geofences(path: &str) -> Result<Vec<Geofence>, GeofenceError> {
+    let text = std::fs::read_to_string(path).map_err(GeofenceError::Read)?;
+    parse_geofences(&text)
+}
+
+/// Whether an aircraft entered or exited a geofence
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GeofenceCrossing {
+    /// The aircraft entered the geofence
+    Enter,
+
+    /// The aircraft exited the geofence
+    Exit,
+}
+
+/// An aircraft crossing a vertiport geofence, published to RabbitMQ
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GeofenceEvent {
+    /// Identifier of the aircraft
+    pub identifier: String,
+
+    /// Identifier of the vertiport
+    pub vertiport_id: String,
+
+    /// Name of the geofence
+    pub geofence: String,
+
+    /// Whether the aircraft entered or exited the geofence
+    pub crossing: GeofenceCrossing,
+
+    /// Position of the aircraft after the crossing
+    pub position: AircraftStatePosition,
+
+    /// Time of the position
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Geofences, and the geofences each aircraft is inside
+///
+/// Only aircraft inside at least one geofence are kept.
+#[derive(Debug, Clone, Default)]
+pub struct GeofenceMonitor {
+    geofences: Arc<Vec<Geofence>>,
+    inside: Arc<Mutex<HashMap<String, HashSet<usize>>>>,
+}
+
+impl GeofenceMonitor {
+    /// Creates a monitor of geofences
+    pub fn new(geofences: Vec<Geofence>) -> Self {
+        GeofenceMonitor {
+            geofences: Arc::new(geofences),
+            inside: Arc::default(),
+        }
+    }
+
+    /// Updates the geofences an aircraft is inside with its position,
+    ///  returning the geofences it entered or exited
+    pub fn update(&self, position: &AircraftPosition) -> Vec<GeofenceEvent> {
+        if self.geofences.is_empty() {
+            return vec![];
+        }
+
+        let Position {
+            latitude,
+            longitude,
+            altitude_meters,
+        } = position.position;
+
+        let now_inside: HashSet<usize> = self
+            .geofences
+            .iter()
+            .enumerate()
+            .filter(|(_, geofence)| geofence.contains(latitude, longitude, altitude_meters))
+            .map(|(index, _)| index)
+            .collect();
+
+        let Ok(mut inside) = self.inside.lock() else {
+            tracking_error!("(update) could not lock geofences.");
+            return vec![];
+        };
+
+        let was_inside = if now_inside.is_empty() {
+            inside.remove(&position.identifier)
+        } else {
+            inside.insert(position.identifier.clone(), now_inside.clone())
+        }
+        .unwrap_or_default();
+
+        let entered = now_inside
+            .difference(&was_inside)
+            .map(|index| (*index, GeofenceCrossing::Enter));
+        let exited = was_inside
+            .difference(&now_inside)
+            .map(|index| (*index, GeofenceCrossing::Exit));
+
+        let state = AircraftStatePosition::from(position);
+        let timestamp = position
+            .timestamp_asset
+            .unwrap_or(position.timestamp_network);
+
+        entered
+            .chain(exited)
+            .filter_map(|(index, crossing)| {
+                let geofence = self.geofences.get(index)?;
+                Some(GeofenceEvent {
+                    identifier: position.identifier.clone(),
+                    vertiport_id: geofence.vertiport_id.clone(),
+                    geofence: geofence.name.clone(),
+                    crossing,
+                    position: state.clone(),
+                    timestamp,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Publishes geofence crossings to RabbitMQ
+pub async fn publish_events(mq_channel: &lapin::Channel, events: Vec<GeofenceEvent>) {
+    for event in events {
+        tracking_info!(
+            "(publish_events) {} {:?} geofence {}/{}.",
+            event.identifier,
+            event.crossing,
+            event.vertiport_id,
+            event.geofence
+        );
+
+        let Ok(msg) = serde_json::to_vec(&event) else {
+            tracking_warn!("(publish_events) could not serialize geofence event.");
+            continue;
+        };
+
+        let _ = mq_channel
+            .basic_publish(
+                crate::amqp::EXCHANGE_NAME_TELEMETRY,
+                crate::amqp::ROUTING_KEY_GEOFENCE,
+                lapin::options::BasicPublishOptions::default(),
+                &msg,
+                lapin::BasicProperties::default(),
+            )
+            .await
+            .map_err(|e| {
+                tracking_warn!("(publish_events) could not push geofence event to RabbitMQ: {e}.");
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn geofence(name: &str, floor_meters: f64, ceiling_meters: f64) -> Geofence {
+        Geofence {
+            vertiport_id: "vertiport".to_string(),
+            name: name.to_string(),
+            vertices: vec![(52., 4.), (52., 4.1), (52.1, 4.1), (52.1, 4.)],
+            floor_meters,
+            ceiling_meters,
+        }
+    }
+
+    fn position(latitude: f64, longitude: f64, altitude_meters: f64) -> AircraftPosition {
+        AircraftPosition {
+            identifier: "aircraft".to_string(),
+            position: Position {
+                latitude,
+                longitude,
+                altitude_meters,
+            },
+            timestamp_network: Utc::now(),
+            timestamp_asset: None,
+        }
+    }
+
+    #[test]
+    fn test_geofence_contains() {
+        let geofence = geofence("approach", 0., 300.);
+        assert!(geofence.is_valid());
+        assert!(geofence.contains(52.05, 4.05, 100.));
+        assert!(!geofence.contains(52.05, 4.05, 301.));
+        assert!(!geofence.contains(52.15, 4.05, 100.));
+    }
+
+    #[test]
+    fn test_parse_geofences() {
+        let geofences = parse_geofences(
+            r#"[{"vertiport_id": "vertiport", "name": "pad",
+                "vertices": [[52, 4], [52, 4.1], [52.1, 4.1]],
+                "floor_meters": 0, "ceiling_meters": 10}]"#,
+        )
+        .unwrap();
+
+        assert_eq!(geofences.len(), 1);
+        assert_eq!(geofences[0].vertices[1], (52., 4.1));
+
+        let inverted = parse_geofences(
+            r#"[{"vertiport_id": "vertiport", "name": "pad",
+                "vertices": [[52, 4], [52, 4.1], [52.1, 4.1]],
+                "floor_meters": 10, "ceiling_meters": 0}]"#,
+        );
+
+        assert!(matches!(inverted, Err(GeofenceError::Invalid(_))));
+        assert!(matches!(
+            parse_geofences("{}"),
+            Err(GeofenceError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn test_monitor_update() {
+        let monitor = GeofenceMonitor::new(vec![
+            geofence("approach", 0., 300.),
+            geofence("pad", 0., 10.),
+        ]);
+
+        assert!(monitor.update(&position(51.9, 4.05, 200.)).is_empty());
+
+        let events = monitor.update(&position(52.05, 4.05, 200.));
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].geofence, "approach");
+        assert_eq!(events[0].crossing, GeofenceCrossing::Enter);
+
+        // Still inside the approach area
+        assert!(monitor.update(&position(52.06, 4.05, 100.)).is_empty());
+
+        let events = monitor.update(&position(52.06, 4.05, 5.));
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].geofence, "pad");
+        assert_eq!(events[0].crossing, GeofenceCrossing::Enter);
+
+        let mut events = monitor.update(&position(51.9, 4.05, 200.));
+        events.sort_by(|a, b| a.geofence.cmp(&b.geofence));
+        assert_eq!(events.len(), 2);
+        assert!(events
+            .iter()
+            .all(|event| event.crossing == GeofenceCrossing::Exit));
+        assert!(monitor.inside.lock().unwrap().is_empty());
+    }
+}
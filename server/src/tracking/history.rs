@@ -0,0 +1,790 @@
+//! Historical tracks of aircraft
+//!
+//! Raw ADS-B packets are stored in svc-storage as they are accepted, and
+//!  raw Remote ID frames are kept in the cache for as long as the longest
+//!  query range. The track of an aircraft over a time range is rebuilt by
+//!  retrieving its stored packets and decoding them again, the same way
+//!  live packets are decoded.
+//!
+//! Tracks are paged with a time cursor. Each page only fetches a limited
+//!  number of packets from each source, in time order, starting at the
+//!  cursor.
+//!
+//! Remote ID frames are only cached to rebuild recent tracks. svc-storage
+//!  has no resource for Remote ID frames yet, so they are not persisted
+//!  and are not retained for regulatory purposes.
+
+use super::identity::{self, IdentifierKind};
+use crate::cache::pool::{CacheError, TelemetryPool};
+use crate::grpc::client::GrpcClients;
+use crate::msg::adsb::{
+    decode_altitude, decode_cpr, decode_speed_direction, decode_vertical_speed,
+    get_adsb_icao_address,
+};
+use crate::msg::netrid::{Frame, LocationMessage, MessageType};
+use crate::rest::api::rest_types::{
+    AircraftHistory, AircraftStatePosition, AircraftStateVelocity, HistoryQuery, TelemetrySource,
+    TrackPoint,
+};
+use adsb_deku::adsb::ME::AirbornePositionBaroAltitude as AirbornePosition;
+use adsb_deku::adsb::ME::AirborneVelocity as Velocity;
+use adsb_deku::adsb::{AirborneVelocitySubType, GroundSpeedDecoding};
+use adsb_deku::deku::DekuContainerRead;
+use adsb_deku::CPRFormat;
+use chrono::{DateTime, Duration, Utc};
+use packed_struct::{PackedStruct, PackedStructSlice};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use svc_storage_client_grpc::prelude::*;
+
+/// Longest time range of a single query
+const MAX_RANGE_HOURS: i64 = 24;
+
+/// Remote ID frames in the cache will expire after the longest time range
+/// TODO(R5): Store frames in svc-storage once it has a Remote ID resource
+const CACHE_EXPIRE_MS_FRAMES: u32 = MAX_RANGE_HOURS as u32 * 3_600_000;
+
+/// Number of points per page if not given
+const DEFAULT_LIMIT: u32 = 100;
+
+/// Largest number of points per page
+const MAX_LIMIT: u32 = 1000;
+
+/// Packets fetched from each source per point of a page,
+///  since an ADS-B position is decoded from two packets
+const PACKETS_PER_POINT: u32 = 2;
+
+/// Packets fetched from just before a page, to pair the first ADS-B
+///  positions of the page with their odd CPR packets
+const PAIRING_PACKETS: u32 = 10;
+
+/// Longest interval between the two packets of a CPR pair,
+///  the same as for live packets
+const MAX_CPR_PAIR_INTERVAL_MS: i64 = 1000;
+
+/// Errors of a history query
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HistoryError {
+    /// The time range, cursor or limit is out of range
+    Invalid,
+
+    /// Stored packets could not be retrieved
+    Storage,
+}
+
+/// A stored packet
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct StoredPacket {
+    /// Time the packet was received
+    timestamp: DateTime<Utc>,
+
+    /// Time of the packet reported by the aircraft, if known
+    timestamp_asset: Option<DateTime<Utc>>,
+
+    /// Raw packet
+    payload: Vec<u8>,
+}
+
+/// Stored packets of a source fetched for a page
+#[derive(Debug, Default)]
+struct Fetched {
+    /// Packets starting at the page
+    packets: Vec<StoredPacket>,
+
+    /// Time of the last packet, if the source may have more packets after it
+    truncated_at: Option<DateTime<Utc>>,
+}
+
+impl Fetched {
+    /// Packets fetched with a limit, which may have left out later packets
+    ///  if the limit was reached
+    fn new(packets: Vec<StoredPacket>, limit: u32) -> Self {
+        let truncated_at = if packets.len() >= limit as usize {
+            packets.iter().map(|packet| packet.timestamp).max()
+        } else {
+            None
+        };
+
+        Fetched {
+            packets,
+            truncated_at,
+        }
+    }
+}
+
+/// Earliest of two optional times
+fn earliest(a: Option<DateTime<Utc>>, b: Option<DateTime<Utc>>) -> Option<DateTime<Utc>> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// Key of the timeline of Remote ID frames posted by a subject
+fn frames_key(subject: &str) -> String {
+    format!("frames:{subject}")
+}
+
+/// Keeps an accepted Remote ID frame posted by a subject, for historical
+///  tracks only
+pub async fn store_netrid_frame(
+    pool: &mut TelemetryPool,
+    subject: &str,
+    payload: &[u8],
+    received: DateTime<Utc>,
+    asset_timestamp: Option<DateTime<Utc>>,
+) -> Result<(), CacheError> {
+    let packet = StoredPacket {
+        timestamp: received,
+        timestamp_asset: asset_timestamp,
+        payload: payload.to_vec(),
+    };
+
+    let Ok(member) = serde_json::to_string(&packet) else {
+        tracking_error!("(store_netrid_frame) could not serialize frame.");
+        return Err(CacheError::OperationFailed);
+    };
+
+    pool.timeline_add(
+        &frames_key(subject),
+        &member,
+        received.timestamp_millis(),
+        CACHE_EXPIRE_MS_FRAMES,
+    )
+    .await
+}
+
+/// Checks the time range, cursor and limit of a query, and returns the
+///  start of the page and its largest number of points
+fn validate(query: &HistoryQuery) -> Result<(DateTime<Utc>, u32), HistoryError> {
+    if query.from >= query.to || query.to - query.from > Duration::hours(MAX_RANGE_HOURS) {
+        return Err(HistoryError::Invalid);
+    }
+
+    let start = match query.after {
+        Some(after) if after < query.from || after >= query.to => {
+            return Err(HistoryError::Invalid);
+        }
+        Some(after) => after,
+        None => query.from,
+    };
+
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT);
+    if limit == 0 || limit > MAX_LIMIT {
+        return Err(HistoryError::Invalid);
+    }
+
+    Ok((start, limit))
+}
+
+/// Decodes stored ADS-B packets into a track, in time order
+///
+/// A position is decoded from an even CPR packet and the latest odd CPR
+///  packet of the same aircraft.
+fn decode_adsb(mut packets: Vec<StoredPacket>) -> Vec<TrackPoint> {
+    packets.sort_by_key(|packet| packet.timestamp);
+
+    // Latest odd CPR packet of each ICAO address
+    let mut odd_cpr: HashMap<u32, (DateTime<Utc>, u32, u32)> = HashMap::new();
+    let mut points = vec![];
+    for packet in packets {
+        let Ok((_, frame)) = adsb_deku::Frame::from_bytes((&packet.payload, 0)) else {
+            tracking_warn!("(decode_adsb) could not parse stored ads-b message.");
+            continue;
+        };
+
+        let adsb_deku::DF::ADSB(msg) = &frame.df else {
+            continue;
+        };
+
+        let icao = get_adsb_icao_address(&msg.icao.0);
+        match &msg.me {
+            AirbornePosition(adsb_deku::Altitude {
+                odd_flag,
+                lat_cpr,
+                lon_cpr,
+                alt: Some(alt),
+                ..
+            }) => {
+                if *odd_flag == CPRFormat::Odd {
+                    odd_cpr.insert(icao, (packet.timestamp, *lat_cpr, *lon_cpr));
+                    continue;
+                }
+
+                let Some((odd_timestamp, odd_lat_cpr, odd_lon_cpr)) = odd_cpr.get(&icao) else {
+                    continue;
+                };
+
+                if (packet.timestamp - *odd_timestamp).num_milliseconds() > MAX_CPR_PAIR_INTERVAL_MS
+                {
+                    continue;
+                }
+
+                let Ok((latitude, longitude)) =
+                    decode_cpr(*lat_cpr, *lon_cpr, *odd_lat_cpr, *odd_lon_cpr)
+                else {
+                    continue;
+                };
+
+                points.push(TrackPoint {
+                    timestamp: packet.timestamp,
+                    source: TelemetrySource::Adsb,
+                    position: Some(AircraftStatePosition {
+                        latitude,
+                        longitude,
+                        altitude_meters: decode_altitude(*alt) as f64,
+                        timestamp_network: packet.timestamp,
+                        timestamp_asset: None,
+                    }),
+                    velocity: None,
+                });
+            }
+            Velocity(adsb_deku::adsb::AirborneVelocity {
+                st,
+                sub_type:
+                    AirborneVelocitySubType::GroundSpeedDecoding(GroundSpeedDecoding {
+                        ew_sign,
+                        ew_vel,
+                        ns_sign,
+                        ns_vel,
+                    }),
+                vrate_sign,
+                vrate_value,
+                ..
+            }) => {
+                let Ok((velocity_horizontal_ground_mps, track_angle_degrees)) =
+                    decode_speed_direction(*st, *ew_sign, *ew_vel, *ns_sign, *ns_vel)
+                else {
+                    continue;
+                };
+
+                let Ok(velocity_vertical_mps) = decode_vertical_speed(*vrate_sign, *vrate_value)
+                else {
+                    continue;
+                };
+
+                points.push(TrackPoint {
+                    timestamp: packet.timestamp,
+                    source: TelemetrySource::Adsb,
+                    position: None,
+                    velocity: Some(AircraftStateVelocity {
+                        velocity_horizontal_ground_mps,
+                        velocity_vertical_mps,
+                        track_angle_degrees,
+                        timestamp_network: packet.timestamp,
+                        timestamp_asset: None,
+                    }),
+                });
+            }
+            _ => (),
+        }
+    }
+
+    points
+}
+
+/// Decodes stored Remote ID frames into a track, in time order
+///
+/// Only location messages are part of the track. The time reported by
+///  the aircraft is relative to the hour it was received in, so the
+///  time decoded when the frame was received is used.
+fn decode_netrid(mut packets: Vec<StoredPacket>) -> Vec<TrackPoint> {
+    packets.sort_by_key(|packet| packet.timestamp);
+
+    let mut points = vec![];
+    for packet in packets {
+        let Ok(frame) = Frame::unpack_from_slice(&packet.payload) else {
+            tracking_warn!("(decode_netrid) could not parse stored netrid frame.");
+            continue;
+        };
+
+        if frame.header.message_type != MessageType::Location {
+            continue;
+        }
+
+        let Ok(message) = LocationMessage::unpack(&frame.message) else {
+            continue;
+        };
+
+        let (Ok(altitude_meters), Ok(velocity_horizontal_ground_mps), Ok(velocity_vertical_mps)) = (
+            message.decode_altitude(),
+            message.decode_speed(),
+            message.decode_vertical_speed(),
+        ) else {
+            continue;
+        };
+
+        points.push(TrackPoint {
+            timestamp: packet.timestamp,
+            source: TelemetrySource::Netrid,
+            position: Some(AircraftStatePosition {
+                latitude: message.decode_latitude(),
+                longitude: message.decode_longitude(),
+                altitude_meters: altitude_meters as f64,
+                timestamp_network: packet.timestamp,
+                timestamp_asset: packet.timestamp_asset,
+            }),
+            velocity: Some(AircraftStateVelocity {
+                velocity_horizontal_ground_mps,
+                velocity_vertical_mps,
+                track_angle_degrees: message.decode_direction() as f32,
+                timestamp_network: packet.timestamp,
+                timestamp_asset: packet.timestamp_asset,
+            }),
+        });
+    }
+
+    points
+}
+
+/// Gets at most `limit` stored ADS-B packets of an ICAO address in a
+///  time range, the oldest or the newest first
+async fn get_adsb_packets(
+    grpc_clients: &GrpcClients,
+    icao: u32,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    newest_first: bool,
+    limit: u32,
+) -> Result<Vec<StoredPacket>, HistoryError> {
+    let mut filter =
+        AdvancedSearchFilter::search_equals("icao_address".to_owned(), icao.to_string())
+            .and_between(
+                "network_timestamp".to_owned(),
+                from.to_rfc3339(),
+                to.to_rfc3339(),
+            );
+
+    let sort_order = if newest_first {
+        SortOrder::Desc
+    } else {
+        SortOrder::Asc
+    };
+
+    filter.order_by = vec![SortOption {
+        sort_field: "network_timestamp".to_owned(),
+        sort_order: sort_order as i32,
+    }];
+    filter.page_number = 1;
+    filter.results_per_page = limit as i32;
+
+    let response = grpc_clients
+        .storage
+        .adsb
+        .search(filter)
+        .await
+        .map_err(|e| {
+            tracking_error!("(get_adsb_packets) could not search ads-b packets: {e}.");
+            HistoryError::Storage
+        })?;
+
+    let packets = response
+        .into_inner()
+        .list
+        .into_iter()
+        .filter_map(|object| object.data)
+        .filter_map(|data| {
+            Some(StoredPacket {
+                timestamp: data.network_timestamp?.into(),
+                timestamp_asset: None,
+                payload: data.payload,
+            })
+        })
+        .collect();
+
+    Ok(packets)
+}
+
+/// Gets at most `limit` stored Remote ID frames posted by a subject in a
+///  time range, the oldest first
+async fn get_netrid_packets(
+    pool: &mut TelemetryPool,
+    subject: &str,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    limit: u32,
+) -> Result<Vec<StoredPacket>, HistoryError> {
+    let members = pool
+        .timeline_range(
+            &frames_key(subject),
+            from.timestamp_millis(),
+            to.timestamp_millis(),
+            limit,
+        )
+        .await
+        .map_err(|e| {
+            tracking_error!("(get_netrid_packets) could not get netrid frames: {e}.");
+            HistoryError::Storage
+        })?;
+
+    let packets = members
+        .iter()
+        .filter_map(|member| match serde_json::from_str(member) {
+            Ok(packet) => Some(packet),
+            Err(e) => {
+                tracking_warn!("(get_netrid_packets) could not parse stored frame: {e}.");
+                None
+            }
+        })
+        .collect();
+
+    Ok(packets)
+}
+
+/// Gets a page of the decoded track of an aircraft in a time range
+pub async fn get_history(
+    grpc_clients: &GrpcClients,
+    aircraft_pool: &mut TelemetryPool,
+    netrid_pool: &mut TelemetryPool,
+    identifier: &str,
+    query: HistoryQuery,
+) -> Result<AircraftHistory, HistoryError> {
+    let (start, limit) = validate(&query)?;
+    let fetch_limit = limit * PACKETS_PER_POINT;
+    let mut truncated_at = None;
+
+    let icao_addresses = identity::identifiers_of(aircraft_pool, identifier, IdentifierKind::Icao)
+        .await
+        .map_err(|e| {
+            tracking_error!("(get_history) could not get identifiers of {identifier}: {e}.");
+            HistoryError::Storage
+        })?;
+
+    let mut packets = vec![];
+    for icao in icao_addresses {
+        let Ok(icao) = u32::from_str_radix(&icao, 16) else {
+            tracking_warn!("(get_history) invalid ICAO address: {icao}.");
+            continue;
+        };
+
+        let pairing_start = start - Duration::milliseconds(MAX_CPR_PAIR_INTERVAL_MS);
+        let pairing = get_adsb_packets(
+            grpc_clients,
+            icao,
+            pairing_start,
+            start,
+            true,
+            PAIRING_PACKETS,
+        )
+        .await?;
+        packets.extend(
+            pairing
+                .into_iter()
+                .filter(|packet| packet.timestamp < start),
+        );
+
+        let fetched = Fetched::new(
+            get_adsb_packets(grpc_clients, icao, start, query.to, false, fetch_limit).await?,
+            fetch_limit,
+        );
+        truncated_at = earliest(truncated_at, fetched.truncated_at);
+        packets.extend(fetched.packets);
+    }
+
+    let subjects = identity::identifiers_of(aircraft_pool, identifier, IdentifierKind::Subject)
+        .await
+        .map_err(|e| {
+            tracking_error!("(get_history) could not get identifiers of {identifier}: {e}.");
+            HistoryError::Storage
+        })?;
+
+    let mut frames = vec![];
+    for subject in subjects {
+        let fetched = Fetched::new(
+            get_netrid_packets(netrid_pool, &subject, start, query.to, fetch_limit).await?,
+            fetch_limit,
+        );
+        truncated_at = earliest(truncated_at, fetched.truncated_at);
+        frames.extend(fetched.packets);
+    }
+
+    let mut points = decode_adsb(packets);
+    points.extend(decode_netrid(frames));
+    points.retain(|point| match query.after {
+        Some(after) => point.timestamp > after,
+        None => point.timestamp >= query.from,
+    });
+    points.sort_by_key(|point| point.timestamp);
+    Ok(page(identifier, points, limit, truncated_at))
+}
+
+/// Takes a page of a track in time order, along with the cursor of the
+///  next page
+///
+/// Points after the last packet of a truncated source are left for the
+///  next page, since that source may have more points before them. Points
+///  at the same time as the last point of the page are kept on the page,
+///  since the next page starts after that time.
+fn page(
+    identifier: &str,
+    mut points: Vec<TrackPoint>,
+    limit: u32,
+    truncated_at: Option<DateTime<Utc>>,
+) -> AircraftHistory {
+    if let Some(truncated_at) = truncated_at {
+        let mut end = points.partition_point(|point| point.timestamp < truncated_at);
+        if end == 0 {
+            // Nothing else is complete, the cursor must still advance
+            end = points.partition_point(|point| point.timestamp <= truncated_at);
+        }
+
+        points.truncate(end);
+    }
+
+    let mut end = points.len().min(limit as usize);
+    while end > 0 && end < points.len() && points[end].timestamp == points[end - 1].timestamp {
+        end += 1;
+    }
+
+    let more = end < points.len() || truncated_at.is_some();
+    points.truncate(end);
+    let next = if more {
+        points.last().map(|point| point.timestamp).or(truncated_at)
+    } else {
+        None
+    };
+
+    AircraftHistory {
+        identifier: identifier.to_string(),
+        points,
+        next,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    /// Airborne position, even CPR
+    const EVEN_POSITION: [u8; 14] = [
+        0x8D, 0x40, 0x62, 0x1D, 0x58, 0xC3, 0x82, 0xD6, 0x90, 0xC8, 0xAC, 0x28, 0x63, 0xA7,
+    ];
+
+    /// Airborne position, odd CPR
+    const ODD_POSITION: [u8; 14] = [
+        0x8D, 0x40, 0x62, 0x1D, 0x58, 0xC3, 0x86, 0x43, 0x5C, 0xC4, 0x12, 0x69, 0x2A, 0xD6,
+    ];
+
+    /// Airborne velocity, ground speed subtype
+    const VELOCITY: [u8; 14] = [
+        0x8D, 0x48, 0x50, 0x20, 0x99, 0x44, 0x09, 0x94, 0x08, 0x38, 0x17, 0x5B, 0x28, 0x4F,
+    ];
+
+    fn packet(seconds: i64, millis: u32, payload: &[u8]) -> StoredPacket {
+        StoredPacket {
+            timestamp: Utc.timestamp_opt(seconds, millis * 1_000_000).unwrap(),
+            timestamp_asset: None,
+            payload: payload.to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_stored_packet_roundtrip() {
+        let packet = StoredPacket {
+            timestamp: Utc.timestamp_opt(1_700_000_000, 0).unwrap(),
+            timestamp_asset: Some(Utc.timestamp_opt(1_699_999_999, 0).unwrap()),
+            payload: vec![0x10, 0x20],
+        };
+
+        let member = serde_json::to_string(&packet).unwrap();
+        assert_eq!(
+            serde_json::from_str::<StoredPacket>(&member).unwrap(),
+            packet
+        );
+        assert_eq!(frames_key("aircraft"), "frames:aircraft");
+    }
+
+    #[test]
+    fn test_validate() {
+        let from = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        let mut query = HistoryQuery {
+            from,
+            to: from + Duration::minutes(30),
+            after: None,
+            limit: None,
+        };
+
+        assert_eq!(validate(&query), Ok((from, DEFAULT_LIMIT)));
+
+        query.after = Some(from + Duration::minutes(10));
+        assert_eq!(
+            validate(&query),
+            Ok((from + Duration::minutes(10), DEFAULT_LIMIT))
+        );
+
+        query.after = Some(from + Duration::minutes(30));
+        assert_eq!(validate(&query), Err(HistoryError::Invalid));
+
+        query.after = Some(from - Duration::minutes(1));
+        assert_eq!(validate(&query), Err(HistoryError::Invalid));
+
+        query.after = None;
+        query.to = from;
+        assert_eq!(validate(&query), Err(HistoryError::Invalid));
+
+        query.to = from + Duration::hours(MAX_RANGE_HOURS + 1);
+        assert_eq!(validate(&query), Err(HistoryError::Invalid));
+
+        query.to = from + Duration::minutes(30);
+        query.limit = Some(0);
+        assert_eq!(validate(&query), Err(HistoryError::Invalid));
+
+        query.limit = Some(MAX_LIMIT + 1);
+        assert_eq!(validate(&query), Err(HistoryError::Invalid));
+    }
+
+    #[test]
+    fn test_fetched_truncation() {
+        let packets = vec![
+            packet(1_700_000_001, 0, &VELOCITY),
+            packet(1_700_000_000, 0, &VELOCITY),
+        ];
+
+        let fetched = Fetched::new(packets.clone(), 2);
+        assert_eq!(
+            fetched.truncated_at,
+            Some(Utc.timestamp_opt(1_700_000_001, 0).unwrap())
+        );
+
+        assert_eq!(Fetched::new(packets, 3).truncated_at, None);
+    }
+
+    #[test]
+    fn test_decode_adsb() {
+        // Out of order, as returned by svc-storage
+        let packets = vec![
+            packet(1_700_000_001, 0, &VELOCITY),
+            packet(1_700_000_000, 500, &EVEN_POSITION),
+            packet(1_700_000_000, 0, &ODD_POSITION),
+        ];
+
+        let points = decode_adsb(packets);
+        assert_eq!(points.len(), 2);
+
+        let position = points[0].position.as_ref().unwrap();
+        assert_eq!(points[0].source, TelemetrySource::Adsb);
+        assert!((position.latitude - 52.2572).abs() < 0.01);
+        assert!((position.longitude - 3.9193).abs() < 0.01);
+        assert!(points[0].velocity.is_none());
+
+        let velocity = points[1].velocity.as_ref().unwrap();
+        assert!(velocity.velocity_horizontal_ground_mps > 0.);
+        assert!(points[1].position.is_none());
+    }
+
+    #[test]
+    fn test_decode_adsb_unpaired() {
+        // The odd packet is too old to pair with the even packet
+        let packets = vec![
+            packet(1_700_000_000, 0, &ODD_POSITION),
+            packet(1_700_000_005, 0, &EVEN_POSITION),
+        ];
+
+        assert!(decode_adsb(packets).is_empty());
+    }
+
+    #[test]
+    fn test_decode_netrid() {
+        use crate::msg::netrid::*;
+
+        let location = LocationMessage {
+            operational_status: OperationalStatus::Airborne,
+            reserved_0: 0.into(),
+            height_type: HeightType::AboveTakeoff,
+            ew_direction: EastWestDirection::East,
+            speed_multiplier: SpeedMultiplier::X0_25,
+            track_direction: 90,
+            speed: 40,
+            vertical_speed: 2,
+            latitude: LocationMessage::encode_latitude(52.),
+            longitude: LocationMessage::encode_longitude(4.),
+            pressure_altitude: LocationMessage::encode_altitude(120.),
+            geodetic_altitude: 0,
+            height: 0,
+            vertical_accuracy: VerticalAccuracyMeters::Lt1,
+            horizontal_accuracy: HorizontalAccuracyMeters::Lt1,
+            barometric_altitude_accuracy: VerticalAccuracyMeters::Lt1,
+            speed_accuracy: SpeedAccuracyMetersPerSecond::Lt1,
+            timestamp: 0,
+            reserved_1: 0.into(),
+            timestamp_accuracy: 0.into(),
+            reserved_2: 0,
+        };
+
+        let location = Frame {
+            header: Header {
+                message_type: MessageType::Location,
+                ..Default::default()
+            },
+            message: location.pack().unwrap(),
+        }
+        .pack()
+        .unwrap();
+
+        let basic = Frame {
+            header: Header::default(),
+            message: [0; 24],
+        }
+        .pack()
+        .unwrap();
+
+        let packets = vec![
+            packet(1_700_000_001, 0, &location),
+            packet(1_700_000_000, 0, &basic),
+        ];
+
+        let points = decode_netrid(packets);
+        assert_eq!(points.len(), 1);
+        assert_eq!(points[0].source, TelemetrySource::Netrid);
+
+        let position = points[0].position.as_ref().unwrap();
+        assert!((position.latitude - 52.).abs() < 1e-6);
+        assert!((position.longitude - 4.).abs() < 1e-6);
+        assert_eq!(position.altitude_meters, 120.);
+
+        let velocity = points[0].velocity.as_ref().unwrap();
+        assert_eq!(velocity.velocity_horizontal_ground_mps, 10.);
+        assert_eq!(velocity.velocity_vertical_mps, 1.);
+        assert_eq!(velocity.track_angle_degrees, 90.);
+    }
+
+    #[test]
+    fn test_page() {
+        let point = |seconds: i64| TrackPoint {
+            timestamp: Utc.timestamp_opt(1_700_000_000 + seconds, 0).unwrap(),
+            source: TelemetrySource::Netrid,
+            position: None,
+            velocity: None,
+        };
+
+        let points: Vec<TrackPoint> = (0..5).map(point).collect();
+
+        // The cursor of the next page is the time of the last point
+        let history = page("aircraft", points.clone(), 2, None);
+        assert_eq!(history.identifier, "aircraft");
+        assert_eq!(history.points, points[..2].to_vec());
+        assert_eq!(history.next, Some(points[1].timestamp));
+
+        // The last page has no cursor
+        let history = page("aircraft", points.clone(), 5, None);
+        assert_eq!(history.points, points);
+        assert_eq!(history.next, None);
+
+        // Points at the same time stay on the same page
+        let tied = vec![point(0), point(1), point(1), point(2)];
+        let history = page("aircraft", tied.clone(), 2, None);
+        assert_eq!(history.points, tied[..3].to_vec());
+
+        // Points after a truncated source wait for the next page
+        let truncated_at = Some(points[3].timestamp);
+        let history = page("aircraft", points.clone(), 10, truncated_at);
+        assert_eq!(history.points, points[..3].to_vec());
+        assert_eq!(history.next, Some(points[2].timestamp));
+
+        // Unless nothing else is left
+        let history = page("aircraft", points[3..].to_vec(), 10, truncated_at);
+        assert_eq!(history.points, points[3..4].to_vec());
+        assert_eq!(history.next, truncated_at);
+    }
+}
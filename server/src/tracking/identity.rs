@@ -0,0 +1,464 @@
+//! Identity correlation of aircraft across protocols
+//!
+//! ADS-B identifies aircraft by ICAO address and callsign, while Remote ID
+//!  identifies them by the login subject and a UAS serial or session ID.
+//!  The registry maps each of these identifiers to a single canonical
+//!  aircraft ID, so that the same aircraft doesn't appear as several
+//!  unrelated aircraft downstream.
+//!
+//! The canonical ID of an aircraft is the UUID of its vehicle in
+//!  svc-storage, if its UAS serial matches the serial number of a vehicle
+//!  or its callsign matches the registration number of a vehicle. Otherwise
+//!  it is the first identifier registered for it. Identifiers are linked when a message carries two of them, such as
+//!  an ADS-B identification message (ICAO address and callsign) or a Remote
+//!  ID basic message (login subject and UAS ID).
+//!
+//! Only stable identifiers (ICAO address, UAS serial, login subject) can
+//!  merge two aircraft: if the second identifier is stable and already
+//!  known, the first joins its aircraft along with every identifier linked
+//!  to it. Callsigns and session IDs are reused by other aircraft, so they
+//!  only ever join the aircraft of the first identifier. Remote ID
+//!  identifiers never join an aircraft identified by an ICAO address.
+//!  Identifiers of a vehicle can merge aircraft whatever their kind, and a
+//!  vehicle never joins another aircraft.
+//!
+//! Mappings are kept in the cache, along with the identifiers linked to
+//!  each canonical ID. Both expire 24 hours after an identifier was last
+//!  registered. Identifiers missing from the cache are looked up in the
+//!  vehicles of svc-storage.
+
+use crate::cache::pool::{CacheError, TelemetryPool};
+use crate::grpc::client::GrpcClients;
+use serde::{Deserialize, Serialize};
+use svc_storage_client_grpc::prelude::*;
+
+/// Identity mappings in the cache will expire after 24 hours
+const CACHE_EXPIRE_MS_IDENTITY: u32 = 86_400_000;
+
+/// Kinds of aircraft identifiers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IdentifierKind {
+    /// ADS-B ICAO address, as lowercase hexadecimal
+    Icao,
+
+    /// Callsign or registration number
+    ///
+    /// Remote ID CAA registration IDs are stored as callsigns, since
+    ///  registration numbers are commonly used as callsigns.
+    Callsign,
+
+    /// Remote ID UAS serial number
+    UasSerial,
+
+    /// Remote ID UTM assigned or specific session ID
+    SessionId,
+
+    /// Subject of the JWT used to post telemetry
+    Subject,
+
+    /// UUID of a vehicle in svc-storage
+    Vehicle,
+}
+
+/// All kinds of aircraft identifiers
+const IDENTIFIER_KINDS: [IdentifierKind; 6] = [
+    IdentifierKind::Icao,
+    IdentifierKind::Callsign,
+    IdentifierKind::UasSerial,
+    IdentifierKind::SessionId,
+    IdentifierKind::Subject,
+    IdentifierKind::Vehicle,
+];
+
+impl IdentifierKind {
+    /// Name of the kind in the cache
+    fn as_str(&self) -> &'static str {
+        match self {
+            IdentifierKind::Icao => "icao",
+            IdentifierKind::Callsign => "callsign",
+            IdentifierKind::UasSerial => "uas_serial",
+            IdentifierKind::SessionId => "session_id",
+            IdentifierKind::Subject => "subject",
+            IdentifierKind::Vehicle => "vehicle",
+        }
+    }
+
+    /// Field of svc-storage vehicles matching identifiers of this kind
+    fn vehicle_field(&self) -> Option<&'static str> {
+        match self {
+            IdentifierKind::UasSerial => Some("serial_number"),
+            IdentifierKind::Callsign => Some("registration_number"),
+            _ => None,
+        }
+    }
+
+    /// Parses the name of a kind in the cache
+    fn from_name(name: &str) -> Option<Self> {
+        IDENTIFIER_KINDS
+            .into_iter()
+            .find(|kind| kind.as_str() == name)
+    }
+
+    /// If identifiers of this kind belong to a single aircraft, and can
+    ///  link aircraft
+    pub fn is_stable(&self) -> bool {
+        matches!(
+            self,
+            IdentifierKind::Icao
+                | IdentifierKind::UasSerial
+                | IdentifierKind::Subject
+                | IdentifierKind::Vehicle
+        )
+    }
+}
+
+/// An identifier of an aircraft
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Identifier {
+    /// Kind of identifier
+    pub kind: IdentifierKind,
+
+    /// Value of the identifier
+    pub value: String,
+}
+
+impl Identifier {
+    /// Creates an identifier, trimming whitespace padding from the value
+    pub fn new(kind: IdentifierKind, value: &str) -> Self {
+        Identifier {
+            kind,
+            value: value.trim().to_string(),
+        }
+    }
+
+    /// Parses the field of an identifier in the hash of identifiers of its aircraft
+    fn from_field(field: &str) -> Option<Self> {
+        let (kind, value) = field.split_once(':')?;
+        Some(Identifier {
+            kind: IdentifierKind::from_name(kind)?,
+            value: value.to_string(),
+        })
+    }
+
+    /// If the identifier has no value, such as a blank callsign
+    pub fn is_empty(&self) -> bool {
+        self.value.is_empty()
+    }
+
+    /// Key of the identifier's canonical ID in the cache
+    fn key(&self) -> String {
+        format!("identity:{}:{}", self.kind.as_str(), self.value)
+    }
+
+    /// Field of the identifier in the hash of identifiers of its aircraft
+    fn field(&self) -> String {
+        format!("{}:{}", self.kind.as_str(), self.value)
+    }
+}
+
+/// Key of the hash of identifiers linked to a canonical ID in the cache
+fn members_key(canonical_id: &str) -> String {
+    format!("identifiers:{canonical_id}")
+}
+
+/// Registry of canonical aircraft IDs
+#[derive(Clone)]
+pub struct IdentityRegistry {
+    pool: TelemetryPool,
+    grpc_clients: GrpcClients,
+}
+
+impl IdentityRegistry {
+    /// Creates a registry backed by the given pool and the vehicles of svc-storage
+    pub fn new(pool: TelemetryPool, grpc_clients: GrpcClients) -> Self {
+        IdentityRegistry { pool, grpc_clients }
+    }
+
+    /// Gets the canonical ID of an identifier, if it is known
+    ///
+    /// Identifiers missing from the cache are looked up in the vehicles of
+    ///  svc-storage, and cached if found.
+    pub async fn lookup(&mut self, identifier: &Identifier) -> Option<String> {
+        match self.pool.get_json::<String>(&identifier.key()).await {
+            Ok(Some(canonical_id)) => return Some(canonical_id),
+            Ok(None) => (),
+            Err(e) => tracking_warn!("(lookup) could not get identity from cache: {e}."),
+        }
+
+        let vehicle_id = self.find_vehicle(identifier).await?;
+        let vehicle = Identifier::new(IdentifierKind::Vehicle, &vehicle_id);
+        self.register(&vehicle, &vehicle_id, None).await;
+        self.register(identifier, &vehicle_id, None).await;
+        Some(vehicle_id)
+    }
+
+    /// Gets the UUID of the svc-storage vehicle with an identifier, if any
+    async fn find_vehicle(&self, identifier: &Identifier) -> Option<String> {
+        let field = identifier.kind.vehicle_field()?;
+        let filter =
+            AdvancedSearchFilter::search_equals(field.to_owned(), identifier.value.clone());
+        match self.grpc_clients.storage.vehicle.search(filter).await {
+            Ok(response) => response
+                .into_inner()
+                .list
+                .into_iter()
+                .next()
+                .map(|vehicle| vehicle.id),
+            Err(e) => {
+                tracking_warn!("(find_vehicle) could not search vehicles by {field}: {e}.");
+                None
+            }
+        }
+    }
+
+    /// If an aircraft is a vehicle of svc-storage
+    async fn is_vehicle(&mut self, canonical_id: &str) -> bool {
+        let vehicle = Identifier::new(IdentifierKind::Vehicle, canonical_id);
+        self.lookup(&vehicle).await.as_deref() == Some(canonical_id)
+    }
+
+    /// Records the canonical ID of an identifier
+    ///
+    /// The identifier is removed from the identifiers of its previous
+    ///  aircraft, if any.
+    async fn register(
+        &mut self,
+        identifier: &Identifier,
+        canonical_id: &str,
+        previous_id: Option<&str>,
+    ) {
+        tracking_info!(
+            "(register) {} {} is aircraft {canonical_id}.",
+            identifier.kind.as_str(),
+            identifier.value
+        );
+
+        if let Err(e) = self
+            .pool
+            .set_json(&identifier.key(), &canonical_id, CACHE_EXPIRE_MS_IDENTITY)
+            .await
+        {
+            tracking_warn!("(register) could not cache identity: {e}.");
+        }
+
+        let field = identifier.field();
+        if let Err(e) = self
+            .pool
+            .set_fields(
+                &members_key(canonical_id),
+                vec![(&field, identifier.value.clone())],
+                CACHE_EXPIRE_MS_IDENTITY,
+            )
+            .await
+        {
+            tracking_warn!("(register) could not cache identifiers of {canonical_id}: {e}.");
+        }
+
+        let Some(previous_id) = previous_id.filter(|id| *id != canonical_id) else {
+            return;
+        };
+
+        if let Err(e) = self
+            .pool
+            .remove_fields(&members_key(previous_id), &[field])
+            .await
+        {
+            tracking_warn!("(register) could not remove identifier of {previous_id}: {e}.");
+        }
+    }
+
+    /// Moves every identifier of an aircraft to another aircraft
+    async fn merge(&mut self, from_id: &str, into_id: &str) {
+        let fields: Vec<String> = match self.pool.get_fields(&members_key(from_id)).await {
+            Ok(fields) => fields.into_keys().collect(),
+            Err(e) => {
+                tracking_warn!("(merge) could not get identifiers of {from_id}: {e}.");
+                return;
+            }
+        };
+
+        tracking_info!("(merge) aircraft {from_id} is aircraft {into_id}.");
+        for identifier in fields
+            .iter()
+            .filter_map(|field| Identifier::from_field(field))
+        {
+            self.register(&identifier, into_id, None).await;
+        }
+
+        if let Err(e) = self
+            .pool
+            .remove_fields(&members_key(from_id), &fields)
+            .await
+        {
+            tracking_warn!("(merge) could not remove identifiers of {from_id}: {e}.");
+        }
+    }
+
+    /// If an aircraft was identified by an ICAO address
+    ///
+    /// If the identifiers can't be retrieved, the aircraft is assumed to be.
+    async fn has_icao_address(&mut self, canonical_id: &str) -> bool {
+        match identifiers_of(&mut self.pool, canonical_id, IdentifierKind::Icao).await {
+            Ok(addresses) => !addresses.is_empty(),
+            Err(e) => {
+                tracking_warn!(
+                    "(has_icao_address) could not get identifiers of {canonical_id}: {e}."
+                );
+                true
+            }
+        }
+    }
+
+    /// Gets the canonical ID of an identifier
+    ///
+    /// Unknown identifiers become the canonical ID of a new aircraft.
+    pub async fn resolve(&mut self, identifier: &Identifier) -> String {
+        if let Some(canonical_id) = self.lookup(identifier).await {
+            return canonical_id;
+        }
+
+        let canonical_id = identifier.value.clone();
+        self.register(identifier, &canonical_id, None).await;
+        canonical_id
+    }
+
+    /// Links two identifiers received together to the same aircraft,
+    ///  and returns the canonical ID of the aircraft
+    ///
+    /// Blank secondary identifiers are ignored.
+    pub async fn link(&mut self, primary: &Identifier, secondary: &Identifier) -> String {
+        if secondary.is_empty() {
+            return self.resolve(primary).await;
+        }
+
+        let primary_id = self.lookup(primary).await;
+        let secondary_id = self.lookup(secondary).await;
+
+        //
+        // A stable identifier of another aircraft, or any identifier of a
+        //  vehicle, brings the primary identifier, and the identifiers
+        //  linked to it, to that aircraft
+        //
+        if let Some(canonical_id) = secondary_id.clone() {
+            if primary_id.as_ref() == Some(&canonical_id) {
+                return canonical_id;
+            }
+
+            let is_stable = secondary.kind.is_stable() || self.is_vehicle(&canonical_id).await;
+            let primary_is_vehicle = match &primary_id {
+                Some(primary_id) => self.is_vehicle(primary_id).await,
+                None => false,
+            };
+
+            let may_join = is_stable
+                && !primary_is_vehicle
+                && (primary.kind == IdentifierKind::Icao
+                    || !self.has_icao_address(&canonical_id).await);
+
+            if may_join {
+                if let Some(primary_id) = &primary_id {
+                    self.merge(primary_id, &canonical_id).await;
+                }
+
+                self.register(primary, &canonical_id, None).await;
+                return canonical_id;
+            }
+        }
+
+        let canonical_id = match primary_id {
+            Some(canonical_id) => canonical_id,
+            None => {
+                let canonical_id = primary.value.clone();
+                self.register(primary, &canonical_id, None).await;
+                canonical_id
+            }
+        };
+
+        if secondary_id.as_ref() != Some(&canonical_id) {
+            self.register(secondary, &canonical_id, secondary_id.as_deref())
+                .await;
+        }
+
+        canonical_id
+    }
+}
+
+/// Gets the identifiers of a kind linked to a canonical aircraft ID
+pub async fn identifiers_of(
+    pool: &mut TelemetryPool,
+    canonical_id: &str,
+    kind: IdentifierKind,
+) -> Result<Vec<String>, CacheError> {
+    let prefix = format!("{}:", kind.as_str());
+    let mut identifiers: Vec<String> = pool
+        .get_fields(&members_key(canonical_id))
+        .await?
+        .into_keys()
+        .filter_map(|field| field.strip_prefix(&prefix).map(String::from))
+        .collect();
+
+    identifiers.sort();
+    Ok(identifiers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identifier_key() {
+        let identifier = Identifier::new(IdentifierKind::Callsign, "N123AB  ");
+        assert_eq!(identifier.value, "N123AB");
+        assert_eq!(identifier.key(), "identity:callsign:N123AB");
+
+        let identifier = Identifier::new(IdentifierKind::Icao, "4840d6");
+        assert_eq!(identifier.key(), "identity:icao:4840d6");
+        assert_eq!(identifier.field(), "icao:4840d6");
+        assert_eq!(members_key("4840d6"), "identifiers:4840d6");
+    }
+
+    #[test]
+    fn test_identifier_from_field() {
+        for kind in IDENTIFIER_KINDS {
+            let identifier = Identifier::new(kind, "N123:AB");
+            assert_eq!(
+                Identifier::from_field(&identifier.field()),
+                Some(identifier)
+            );
+        }
+
+        assert_eq!(Identifier::from_field("unknown:N123AB"), None);
+        assert_eq!(Identifier::from_field("N123AB"), None);
+    }
+
+    #[test]
+    fn test_stable_identifiers() {
+        assert!(IdentifierKind::Icao.is_stable());
+        assert!(IdentifierKind::UasSerial.is_stable());
+        assert!(IdentifierKind::Subject.is_stable());
+        assert!(IdentifierKind::Vehicle.is_stable());
+        assert!(!IdentifierKind::Callsign.is_stable());
+        assert!(!IdentifierKind::SessionId.is_stable());
+    }
+
+    #[test]
+    fn test_vehicle_field() {
+        assert_eq!(
+            IdentifierKind::UasSerial.vehicle_field(),
+            Some("serial_number")
+        );
+        assert_eq!(
+            IdentifierKind::Callsign.vehicle_field(),
+            Some("registration_number")
+        );
+        assert_eq!(IdentifierKind::Icao.vehicle_field(), None);
+        assert_eq!(IdentifierKind::Vehicle.vehicle_field(), None);
+    }
+
+    #[test]
+    fn test_blank_identifier() {
+        assert!(Identifier::new(IdentifierKind::Callsign, "        ").is_empty());
+        assert!(!Identifier::new(IdentifierKind::Callsign, "N123AB").is_empty());
+    }
+}
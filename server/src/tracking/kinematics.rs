@@ -0,0 +1,564 @@
+//! Kinematic plausibility of aircraft positions
+//!
+//! Corrupted or spoofed packets can make an aircraft appear to jump
+//!  kilometres in an instant. Each new position is compared with the
+//!  last accepted position of the aircraft, and positions implying an
+//!  impossible ground speed, climb rate or acceleration for the type of
+//!  aircraft are rejected.
+
+use super::EARTH_RADIUS_METERS;
+use crate::cache::pool::TelemetryPool;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use svc_gis_client_grpc::prelude::types::*;
+
+/// Last accepted positions in the cache will expire after 60 seconds,
+///  after which the next position is accepted without comparison
+const CACHE_EXPIRE_MS_LAST_FIX: u32 = 60_000;
+
+/// Kinematic limits of an aircraft in the cache will expire after 1 hour
+const CACHE_EXPIRE_MS_LIMITS: u32 = 3_600_000;
+
+/// Horizontal error tolerated between two positions (m)
+const POSITION_NOISE_METERS: f64 = 100.;
+
+/// Vertical error tolerated between two positions (m)
+const ALTITUDE_NOISE_METERS: f64 = 30.;
+
+/// Shortest interval between two positions over which acceleration is checked,
+///  shorter intervals give noisy speed estimates
+const MIN_ACCELERATION_INTERVAL_SECONDS: f64 = 0.5;
+
+/// After this many consecutive rejections, the track may be re-baselined.
+/// This prevents a single bad position from blocking an aircraft's track.
+const MAX_CONSECUTIVE_REJECTIONS: u32 = 5;
+
+/// Number of consecutive rejected positions, each plausible from the
+///  previous rejected position, needed to re-baseline the track.
+/// A burst of unrelated bad positions can't replace a good position.
+const MIN_CONSISTENT_REJECTIONS: u32 = 3;
+
+/// Physical limits of an aircraft's motion
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct KinematicLimits {
+    /// Maximum ground speed (m/s)
+    pub max_ground_speed_mps: f64,
+
+    /// Maximum climb or descent rate (m/s)
+    pub max_vertical_speed_mps: f64,
+
+    /// Maximum horizontal acceleration (m/s^2)
+    pub max_acceleration_mps2: f64,
+}
+
+impl KinematicLimits {
+    const fn new(
+        max_ground_speed_mps: f64,
+        max_vertical_speed_mps: f64,
+        max_acceleration_mps2: f64,
+    ) -> Self {
+        KinematicLimits {
+            max_ground_speed_mps,
+            max_vertical_speed_mps,
+            max_acceleration_mps2,
+        }
+    }
+}
+
+impl Default for KinematicLimits {
+    /// Generous limits for aircraft of unknown type
+    fn default() -> Self {
+        KinematicLimits::new(350., 100., 30.)
+    }
+}
+
+impl From<AircraftType> for KinematicLimits {
+    fn from(aircraft_type: AircraftType) -> Self {
+        match aircraft_type {
+            AircraftType::Rotorcraft
+            | AircraftType::Gyroplane
+            | AircraftType::Hybridlift
+            | AircraftType::Ornithopter => KinematicLimits::new(120., 30., 15.),
+            AircraftType::Glider | AircraftType::Kite | AircraftType::Unpowered => {
+                KinematicLimits::new(100., 30., 15.)
+            }
+            AircraftType::Freeballoon | AircraftType::Captiveballoon | AircraftType::Airship => {
+                KinematicLimits::new(40., 10., 5.)
+            }
+            AircraftType::Tethered | AircraftType::Groundobstacle => {
+                KinematicLimits::new(5., 5., 5.)
+            }
+            AircraftType::Rocket => KinematicLimits::new(3000., 3000., 100.),
+            _ => KinematicLimits::default(),
+        }
+    }
+}
+
+/// Reason a position was rejected
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Implausibility {
+    /// Implied ground speed exceeds the limit (m/s)
+    GroundSpeed(f64),
+
+    /// Implied climb or descent rate exceeds the limit (m/s)
+    VerticalSpeed(f64),
+
+    /// Implied horizontal acceleration exceeds the limit (m/s^2)
+    Acceleration(f64),
+
+    /// The position isn't newer than the last accepted position (s)
+    OutOfOrder(f64),
+}
+
+impl std::fmt::Display for Implausibility {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Implausibility::GroundSpeed(v) => write!(f, "implied ground speed {v:.1} m/s"),
+            Implausibility::VerticalSpeed(v) => write!(f, "implied vertical speed {v:.1} m/s"),
+            Implausibility::Acceleration(v) => write!(f, "implied acceleration {v:.1} m/s^2"),
+            Implausibility::OutOfOrder(dt) => {
+                write!(f, "{:.1} s older than the last accepted position", -dt)
+            }
+        }
+    }
+}
+
+/// A position rejected by the kinematic filter, published for analysis
+#[derive(Debug, Clone, Serialize)]
+pub struct RejectedPosition {
+    /// The rejected position
+    pub position: AircraftPosition,
+
+    /// Why the position was rejected
+    pub reason: Implausibility,
+}
+
+/// A position of an aircraft, with the times it was reported
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Fix {
+    /// Latitude (degrees)
+    pub latitude: f64,
+
+    /// Longitude (degrees)
+    pub longitude: f64,
+
+    /// Altitude (m)
+    pub altitude_meters: f64,
+
+    /// Time the position was received
+    pub timestamp_network: DateTime<Utc>,
+
+    /// Time of the position reported by the aircraft, if any
+    pub timestamp_asset: Option<DateTime<Utc>>,
+}
+
+impl Fix {
+    /// Creates a fix from an aircraft position
+    pub fn from_position(position: &AircraftPosition) -> Self {
+        Fix {
+            latitude: position.position.latitude,
+            longitude: position.position.longitude,
+            altitude_meters: position.position.altitude_meters,
+            timestamp_network: position.timestamp_network,
+            timestamp_asset: position.timestamp_asset,
+        }
+    }
+
+    /// Time elapsed since an earlier fix (s)
+    ///
+    /// Both fixes are compared on the same time basis, the aircraft's clock
+    ///  if both fixes were timed by the aircraft, otherwise the time the
+    ///  fixes were received.
+    pub fn seconds_since(&self, earlier: &Fix) -> f64 {
+        let dt = match (self.timestamp_asset, earlier.timestamp_asset) {
+            (Some(time), Some(earlier_time)) => time - earlier_time,
+            _ => self.timestamp_network - earlier.timestamp_network,
+        };
+
+        dt.num_milliseconds() as f64 / 1000.
+    }
+}
+
+/// Last accepted position of an aircraft
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LastFix {
+    /// The last accepted position
+    pub fix: Fix,
+
+    /// Ground speed implied by this and the previous position (m/s)
+    pub ground_speed_mps: Option<f64>,
+
+    /// Number of positions rejected since this position
+    pub rejections: u32,
+
+    /// Most recent rejected position
+    pub candidate: Option<Fix>,
+
+    /// Number of consecutive rejected positions consistent with each
+    ///  other, up to and including the candidate
+    pub candidate_support: u32,
+}
+
+impl LastFix {
+    /// Creates an accepted position with no rejections
+    pub fn new(fix: Fix, ground_speed_mps: Option<f64>) -> Self {
+        LastFix {
+            fix,
+            ground_speed_mps,
+            rejections: 0,
+            candidate: None,
+            candidate_support: 0,
+        }
+    }
+}
+
+/// Great-circle distance between two points (m)
+pub fn haversine_distance_meters(
+    latitude_1: f64,
+    longitude_1: f64,
+    latitude_2: f64,
+    longitude_2: f64,
+) -> f64 {
+    let d_lat = (latitude_2 - latitude_1).to_radians();
+    let d_lon = (longitude_2 - longitude_1).to_radians();
+    let a = (d_lat / 2.).sin().powi(2)
+        + latitude_1.to_radians().cos()
+            * latitude_2.to_radians().cos()
+            * (d_lon / 2.).sin().powi(2);
+
+    2. * EARTH_RADIUS_METERS * a.sqrt().asin()
+}
+
+/// Checks a new position against an earlier position of an aircraft
+///
+/// Returns the implied ground speed (m/s) if the position is plausible.
+/// Positions which aren't newer than the earlier position can't be
+///  evaluated and are rejected as out of order.
+pub fn check_kinematics(
+    last: &Fix,
+    last_ground_speed_mps: Option<f64>,
+    fix: &Fix,
+    limits: &KinematicLimits,
+) -> Result<f64, Implausibility> {
+    let dt = fix.seconds_since(last);
+    if dt <= 0. {
+        return Err(Implausibility::OutOfOrder(dt));
+    }
+
+    let distance =
+        haversine_distance_meters(last.latitude, last.longitude, fix.latitude, fix.longitude);
+
+    let ground_speed_mps = (distance - POSITION_NOISE_METERS).max(0.) / dt;
+    if ground_speed_mps > limits.max_ground_speed_mps {
+        return Err(Implausibility::GroundSpeed(ground_speed_mps));
+    }
+
+    let climb = (fix.altitude_meters - last.altitude_meters).abs();
+    let vertical_speed_mps = (climb - ALTITUDE_NOISE_METERS).max(0.) / dt;
+    if vertical_speed_mps > limits.max_vertical_speed_mps {
+        return Err(Implausibility::VerticalSpeed(vertical_speed_mps));
+    }
+
+    if let Some(last_ground_speed_mps) = last_ground_speed_mps {
+        if dt >= MIN_ACCELERATION_INTERVAL_SECONDS {
+            let acceleration_mps2 = (ground_speed_mps - last_ground_speed_mps).abs() / dt;
+            if acceleration_mps2 > limits.max_acceleration_mps2 {
+                return Err(Implausibility::Acceleration(acceleration_mps2));
+            }
+        }
+    }
+
+    Ok(ground_speed_mps)
+}
+
+/// Checks a new position against the last state of an aircraft
+///
+/// Returns the new last state of the aircraft, or `None` if it is
+///  unchanged, along with the outcome of the check.
+/// Out of order positions don't count as rejections. After enough
+///  consecutive rejections consistent with each other, the new position
+///  replaces the last accepted position.
+pub fn next_fix(
+    last: Option<LastFix>,
+    fix: Fix,
+    limits: &KinematicLimits,
+) -> (Option<LastFix>, Result<(), Implausibility>) {
+    let Some(last) = last else {
+        return (Some(LastFix::new(fix, None)), Ok(()));
+    };
+
+    let reason = match check_kinematics(&last.fix, last.ground_speed_mps, &fix, limits) {
+        Ok(ground_speed_mps) => return (Some(LastFix::new(fix, Some(ground_speed_mps))), Ok(())),
+        Err(reason @ Implausibility::OutOfOrder(_)) => return (None, Err(reason)),
+        Err(reason) => reason,
+    };
+
+    let candidate_support = match last.candidate {
+        Some(candidate) if check_kinematics(&candidate, None, &fix, limits).is_ok() => {
+            last.candidate_support + 1
+        }
+        _ => 1,
+    };
+
+    let rejections = last.rejections + 1;
+    if rejections >= MAX_CONSECUTIVE_REJECTIONS && candidate_support >= MIN_CONSISTENT_REJECTIONS {
+        tracking_warn!(
+            "(next_fix) accepting position after {rejections} rejections, {candidate_support} consistent ({reason})."
+        );
+
+        return (Some(LastFix::new(fix, None)), Ok(()));
+    }
+
+    let last = LastFix {
+        rejections,
+        candidate: Some(fix),
+        candidate_support,
+        ..last
+    };
+
+    (Some(last), Err(reason))
+}
+
+/// Caches the kinematic limits of an aircraft from its type
+pub async fn set_aircraft_type(
+    pool: &mut TelemetryPool,
+    identifier: &str,
+    aircraft_type: AircraftType,
+) {
+    let limits = KinematicLimits::from(aircraft_type);
+    if let Err(e) = pool
+        .set_json(
+            &format!("{identifier}:limits"),
+            &limits,
+            CACHE_EXPIRE_MS_LIMITS,
+        )
+        .await
+    {
+        tracking_warn!("(set_aircraft_type) could not cache limits of {identifier}: {e}.");
+    }
+}
+
+/// Checks a new position against the cached last state of the aircraft
+///
+/// Accepted positions become the new last state of the aircraft.
+/// If the cache is unavailable, the position is accepted, since an
+///  outage shouldn't stop valid telemetry from reaching svc-gis.
+pub async fn check_position(
+    pool: &mut TelemetryPool,
+    position: &AircraftPosition,
+) -> Result<(), Implausibility> {
+    let identifier = &position.identifier;
+    let limits = pool
+        .get_json::<KinematicLimits>(&format!("{identifier}:limits"))
+        .await
+        .ok()
+        .flatten()
+        .unwrap_or_default();
+
+    // Concurrent positions of the same aircraft are checked one at a time
+    let fix = Fix::from_position(position);
+    match pool
+        .update_json(
+            &format!("{identifier}:last_fix"),
+            CACHE_EXPIRE_MS_LAST_FIX,
+            |last| next_fix(last, fix, &limits),
+        )
+        .await
+    {
+        Ok(result) => result,
+        Err(e) => {
+            tracking_warn!("(check_position) could not update last position of {identifier}: {e}.");
+            Ok(())
+        }
+    }
+}
+
+/// Publishes a rejected position to RabbitMQ for analysis
+pub async fn publish_rejected(
+    mq_channel: &lapin::Channel,
+    position: AircraftPosition,
+    reason: Implausibility,
+) {
+    tracking_warn!(
+        "(publish_rejected) rejected position of {}: {reason}.",
+        position.identifier
+    );
+
+    let rejected = RejectedPosition { position, reason };
+    let Ok(msg) = serde_json::to_vec(&rejected) else {
+        tracking_warn!("(publish_rejected) could not serialize rejected position.");
+        return;
+    };
+
+    let _ = mq_channel
+        .basic_publish(
+            crate::amqp::EXCHANGE_NAME_TELEMETRY,
+            crate::amqp::ROUTING_KEY_REJECTED_POSITION,
+            lapin::options::BasicPublishOptions::default(),
+            &msg,
+            lapin::BasicProperties::default(),
+        )
+        .await
+        .map_err(|e| {
+            tracking_warn!("(publish_rejected) could not push rejected position to RabbitMQ: {e}.");
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn position(
+        latitude: f64,
+        longitude: f64,
+        altitude_meters: f64,
+        seconds: i64,
+    ) -> AircraftPosition {
+        AircraftPosition {
+            identifier: "aircraft".to_string(),
+            position: Position {
+                latitude,
+                longitude,
+                altitude_meters,
+            },
+            timestamp_network: Utc.timestamp_opt(seconds, 0).unwrap(),
+            timestamp_asset: None,
+        }
+    }
+
+    #[test]
+    fn test_haversine_distance() {
+        // One degree of latitude is about 111 km
+        let distance = haversine_distance_meters(52., 4., 53., 4.);
+        assert!((distance - 111_195.).abs() < 100.);
+        assert_eq!(haversine_distance_meters(52., 4., 52., 4.), 0.);
+    }
+
+    fn fix(latitude: f64, longitude: f64, altitude_meters: f64, seconds: i64) -> Fix {
+        Fix::from_position(&position(latitude, longitude, altitude_meters, seconds))
+    }
+
+    #[test]
+    fn test_plausible_position() {
+        let last = fix(52., 4., 100., 0);
+        let limits = KinematicLimits::from(AircraftType::Rotorcraft);
+
+        // ~556 m in 10 seconds
+        let next = fix(52.005, 4., 110., 10);
+        let ground_speed_mps = check_kinematics(&last, None, &next, &limits).unwrap();
+        assert!(ground_speed_mps > 40. && ground_speed_mps < 50.);
+    }
+
+    #[test]
+    fn test_implausible_ground_speed() {
+        let last = fix(52., 4., 100., 0);
+        let limits = KinematicLimits::from(AircraftType::Rotorcraft);
+
+        // ~111 km in 1 second
+        let next = fix(53., 4., 100., 1);
+        let result = check_kinematics(&last, None, &next, &limits);
+        assert!(matches!(result, Err(Implausibility::GroundSpeed(_))));
+    }
+
+    #[test]
+    fn test_implausible_vertical_speed() {
+        let last = fix(52., 4., 100., 0);
+        let limits = KinematicLimits::from(AircraftType::Airship);
+
+        let next = fix(52., 4., 1100., 10);
+        let result = check_kinematics(&last, None, &next, &limits);
+        assert!(matches!(result, Err(Implausibility::VerticalSpeed(_))));
+    }
+
+    #[test]
+    fn test_implausible_acceleration() {
+        let last = fix(52., 4., 100., 0);
+        let limits = KinematicLimits::from(AircraftType::Rotorcraft);
+
+        // From hovering to ~100 m/s in 1 second
+        let next = fix(52.0018, 4., 100., 1);
+        let result = check_kinematics(&last, Some(0.), &next, &limits);
+        assert!(matches!(result, Err(Implausibility::Acceleration(_))));
+    }
+
+    #[test]
+    fn test_out_of_order_position() {
+        let last = fix(52., 4., 100., 10);
+        let limits = KinematicLimits::default();
+
+        let next = fix(53., 4., 100., 5);
+        assert_eq!(
+            check_kinematics(&last, None, &next, &limits),
+            Err(Implausibility::OutOfOrder(-5.))
+        );
+
+        let repeated = fix(52., 4., 100., 10);
+        assert_eq!(
+            check_kinematics(&last, None, &repeated, &limits),
+            Err(Implausibility::OutOfOrder(0.))
+        );
+
+        // Out of order positions leave the last state unchanged
+        let (last, result) = next_fix(Some(LastFix::new(last, None)), next, &limits);
+        assert_eq!(last, None);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_single_time_basis() {
+        let mut last = fix(52., 4., 100., 100);
+        last.timestamp_asset = Some(Utc.timestamp_opt(10, 0).unwrap());
+
+        // Timed by the aircraft, compared with the aircraft's clock
+        let mut next = fix(52., 4., 100., 101);
+        next.timestamp_asset = Some(Utc.timestamp_opt(15, 0).unwrap());
+        assert_eq!(next.seconds_since(&last), 5.);
+
+        // Not timed by the aircraft, compared with the time received
+        next.timestamp_asset = None;
+        assert_eq!(next.seconds_since(&last), 1.);
+    }
+
+    #[test]
+    fn test_rebaseline_after_consistent_rejections() {
+        let limits = KinematicLimits::from(AircraftType::Rotorcraft);
+        let mut last = Some(LastFix::new(fix(52., 4., 100., 0), None));
+
+        // The aircraft is really ~111 km away, moving slowly
+        for seconds in 1..MAX_CONSECUTIVE_REJECTIONS as i64 {
+            let (next, result) = next_fix(last, fix(53., 4., 100., seconds), &limits);
+            assert!(result.is_err());
+            last = next;
+        }
+
+        let (next, result) = next_fix(
+            last,
+            fix(53., 4., 100., MAX_CONSECUTIVE_REJECTIONS as i64),
+            &limits,
+        );
+        assert_eq!(result, Ok(()));
+        let next = next.unwrap();
+        assert_eq!(next.fix.latitude, 53.);
+        assert_eq!(next.rejections, 0);
+    }
+
+    #[test]
+    fn test_no_rebaseline_after_inconsistent_rejections() {
+        let limits = KinematicLimits::from(AircraftType::Rotorcraft);
+        let mut last = Some(LastFix::new(fix(52., 4., 100., 0), None));
+
+        // Bad positions jumping between two far away places
+        for seconds in 1..=(MAX_CONSECUTIVE_REJECTIONS as i64 * 2) {
+            let latitude = if seconds % 2 == 0 { 53. } else { 51. };
+            let (next, result) = next_fix(last, fix(latitude, 4., 100., seconds), &limits);
+            assert!(result.is_err());
+            last = next;
+        }
+
+        let last = last.unwrap();
+        assert_eq!(last.fix.latitude, 52.);
+        assert_eq!(last.candidate_support, 1);
+    }
+}
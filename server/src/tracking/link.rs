@@ -0,0 +1,319 @@
+//! Lost-link detection
+//!
+//! The time each aircraft was last seen is kept per source, since an
+//!  aircraft may still be heard over ADS-B after its Remote ID link fails.
+//!  Every accepted update counts, with or without a position.
+//!  A background task publishes an event when an aircraft hasn't been
+//!  seen from a source for longer than that source's timeout, with its
+//!  last known position, and another when it is seen again.
+
+use crate::rest::api::rest_types::{AircraftStatePosition, TelemetrySource};
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Interval between checks for lost signals (ms)
+const CHECK_INTERVAL_MS: u64 = 1000;
+
+/// Aircraft whose signal was lost this long ago are forgotten (s)
+const LOST_EXPIRE_SECONDS: i64 = 3600;
+
+/// Timeouts after which the signal of an aircraft is lost, per source
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LinkTimeouts {
+    /// Timeout of ADS-B (ms)
+    pub adsb_ms: u32,
+
+    /// Timeout of Remote ID (ms)
+    pub netrid_ms: u32,
+}
+
+impl LinkTimeouts {
+    /// Timeout of a source
+    fn timeout(&self, source: TelemetrySource) -> Duration {
+        let ms = match source {
+            TelemetrySource::Adsb => self.adsb_ms,
+            TelemetrySource::Netrid => self.netrid_ms,
+        };
+
+        Duration::milliseconds(ms as i64)
+    }
+}
+
+/// Whether the signal of an aircraft was lost or restored
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkStatus {
+    /// The aircraft hasn't been seen for longer than the timeout
+    SignalLost,
+
+    /// The aircraft was seen again after its signal was lost
+    SignalRestored,
+}
+
+/// A lost or restored signal, published to RabbitMQ
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LinkEvent {
+    /// Identifier of the aircraft
+    pub identifier: String,
+
+    /// Source whose signal was lost or restored
+    pub source: TelemetrySource,
+
+    /// Whether the signal was lost or restored
+    pub status: LinkStatus,
+
+    /// Latest position of the aircraft from the source, if any
+    pub position: Option<AircraftStatePosition>,
+
+    /// Time the aircraft was last seen before the event
+    pub last_seen: DateTime<Utc>,
+
+    /// Time of the event
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Latest position of an aircraft from a source
+#[derive(Debug, Clone, PartialEq)]
+struct Link {
+    position: Option<AircraftStatePosition>,
+    last_seen: DateTime<Utc>,
+    lost: bool,
+}
+
+/// Time each aircraft was last seen from each source
+#[derive(Debug, Clone)]
+pub struct LinkMonitor {
+    timeouts: LinkTimeouts,
+    links: Arc<Mutex<HashMap<(String, TelemetrySource), Link>>>,
+}
+
+impl LinkMonitor {
+    /// Creates a monitor without aircraft
+    pub fn new(timeouts: LinkTimeouts) -> Self {
+        LinkMonitor {
+            timeouts,
+            links: Arc::default(),
+        }
+    }
+
+    /// Records an aircraft seen from a source, returning an event if its
+    ///  signal from the source was lost
+    ///
+    /// Updates without a position keep the last known position.
+    pub fn record(
+        &self,
+        identifier: &str,
+        source: TelemetrySource,
+        position: Option<AircraftStatePosition>,
+        now: DateTime<Utc>,
+    ) -> Option<LinkEvent> {
+        let Ok(mut links) = self.links.lock() else {
+            tracking_error!("(record) could not lock links.");
+            return None;
+        };
+
+        let key = (identifier.to_string(), source);
+        let position = position.or_else(|| links.get(&key).and_then(|link| link.position.clone()));
+        let link = Link {
+            position,
+            last_seen: now,
+            lost: false,
+        };
+
+        let previous = links.insert(key, link.clone())?;
+        if !previous.lost {
+            return None;
+        }
+
+        Some(LinkEvent {
+            identifier: identifier.to_string(),
+            source,
+            status: LinkStatus::SignalRestored,
+            position: link.position,
+            last_seen: previous.last_seen,
+            timestamp: now,
+        })
+    }
+
+    /// Marks aircraft not seen within the timeout of a source as lost,
+    ///  returning an event for each
+    pub fn check(&self, now: DateTime<Utc>) -> Vec<LinkEvent> {
+        let Ok(mut links) = self.links.lock() else {
+            tracking_error!("(check) could not lock links.");
+            return vec![];
+        };
+
+        links.retain(|_, link| {
+            !link.lost || (now - link.last_seen).num_seconds() < LOST_EXPIRE_SECONDS
+        });
+
+        links
+            .iter_mut()
+            .filter(|((_, source), link)| {
+                !link.lost && now - link.last_seen > self.timeouts.timeout(*source)
+            })
+            .map(|((identifier, source), link)| {
+                link.lost = true;
+                LinkEvent {
+                    identifier: identifier.clone(),
+                    source: *source,
+                    status: LinkStatus::SignalLost,
+                    position: link.position.clone(),
+                    last_seen: link.last_seen,
+                    timestamp: now,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Publishes lost and restored signals to RabbitMQ
+pub async fn publish_events(
+    mq_channel: &lapin::Channel,
+    events: impl IntoIterator<Item = LinkEvent>,
+) {
+    for event in events {
+        tracking_warn!(
+            "(publish_events) {} {:?} over {:?}, last seen {}.",
+            event.identifier,
+            event.status,
+            event.source,
+            event.last_seen
+        );
+
+        let Ok(msg) = serde_json::to_vec(&event) else {
+            tracking_warn!("(publish_events) could not serialize link event.");
+            continue;
+        };
+
+        let _ = mq_channel
+            .basic_publish(
+                crate::amqp::EXCHANGE_NAME_TELEMETRY,
+                crate::amqp::ROUTING_KEY_LINK,
+                lapin::options::BasicPublishOptions::default(),
+                &msg,
+                lapin::BasicProperties::default(),
+            )
+            .await
+            .map_err(|e| {
+                tracking_warn!("(publish_events) could not push link event to RabbitMQ: {e}.");
+            });
+    }
+}
+
+/// Publishes lost signals to RabbitMQ as they time out
+#[cfg(not(tarpaulin_include))]
+// no_coverage: Needs running backends to work.
+pub async fn check_loop(monitor: LinkMonitor, mq_channel: lapin::Channel) {
+    tracking_info!(
+        "(check_loop) checking for lost signals every {CHECK_INTERVAL_MS} ms, timeouts {:?}.",
+        monitor.timeouts
+    );
+
+    let mut interval = tokio::time::interval(std::time::Duration::from_millis(CHECK_INTERVAL_MS));
+    loop {
+        interval.tick().await;
+        publish_events(&mq_channel, monitor.check(Utc::now())).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    const TIMEOUTS: LinkTimeouts = LinkTimeouts {
+        adsb_ms: 10_000,
+        netrid_ms: 3_000,
+    };
+
+    fn time(seconds: i64) -> DateTime<Utc> {
+        Utc.timestamp_opt(seconds, 0).unwrap()
+    }
+
+    fn position(seconds: i64) -> Option<AircraftStatePosition> {
+        Some(AircraftStatePosition {
+            latitude: 52.,
+            longitude: 4.,
+            altitude_meters: 100.,
+            timestamp_network: time(seconds),
+            timestamp_asset: None,
+        })
+    }
+
+    #[test]
+    fn test_signal_lost_per_source() {
+        let monitor = LinkMonitor::new(TIMEOUTS);
+        assert!(monitor
+            .record("aircraft", TelemetrySource::Netrid, position(0), time(0))
+            .is_none());
+        assert!(monitor
+            .record("aircraft", TelemetrySource::Adsb, position(0), time(0))
+            .is_none());
+        assert!(monitor.check(time(3)).is_empty());
+
+        let events = monitor.check(time(4));
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].source, TelemetrySource::Netrid);
+        assert_eq!(events[0].status, LinkStatus::SignalLost);
+        assert_eq!(events[0].last_seen, time(0));
+
+        // Lost only once
+        assert!(monitor.check(time(5)).is_empty());
+
+        let events = monitor.check(time(11));
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].source, TelemetrySource::Adsb);
+    }
+
+    #[test]
+    fn test_signal_restored() {
+        let monitor = LinkMonitor::new(TIMEOUTS);
+        monitor.record("aircraft", TelemetrySource::Netrid, position(0), time(0));
+        assert!(monitor
+            .record("aircraft", TelemetrySource::Netrid, position(2), time(2))
+            .is_none());
+        assert_eq!(monitor.check(time(6)).len(), 1);
+
+        let event = monitor
+            .record("aircraft", TelemetrySource::Netrid, position(8), time(8))
+            .unwrap();
+        assert_eq!(event.status, LinkStatus::SignalRestored);
+        assert_eq!(event.last_seen, time(2));
+        assert_eq!(event.timestamp, time(8));
+        assert!(monitor.check(time(9)).is_empty());
+    }
+
+    #[test]
+    fn test_seen_without_position() {
+        let monitor = LinkMonitor::new(TIMEOUTS);
+        monitor.record("aircraft", TelemetrySource::Adsb, position(0), time(0));
+        assert!(monitor
+            .record("aircraft", TelemetrySource::Adsb, None, time(8))
+            .is_none());
+        assert!(monitor.check(time(11)).is_empty());
+
+        let events = monitor.check(time(19));
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].last_seen, time(8));
+        assert_eq!(events[0].position, position(0));
+
+        // Seen before any position
+        monitor.record("other", TelemetrySource::Netrid, None, time(20));
+        let events = monitor.check(time(24));
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].identifier, "other");
+        assert_eq!(events[0].position, None);
+    }
+
+    #[test]
+    fn test_lost_expire() {
+        let monitor = LinkMonitor::new(TIMEOUTS);
+        monitor.record("aircraft", TelemetrySource::Netrid, position(0), time(0));
+        assert_eq!(monitor.check(time(10)).len(), 1);
+        assert!(monitor.check(time(LOST_EXPIRE_SECONDS)).is_empty());
+        assert!(monitor.links.lock().unwrap().is_empty());
+    }
+}
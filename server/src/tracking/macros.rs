@@ -0,0 +1,4 @@
+//! log macro's for tracking logging
+
+use lib_common::log_macros;
+log_macros!("tracking", "app::tracking");
@@ -0,0 +1,25 @@
+//! Tracking of individual aircraft
+//!
+//! Telemetry from all sources is combined into a per-aircraft state,
+//!  which is used to check and enrich new telemetry before it is
+//!  forwarded to other services.
+
+#[macro_use]
+pub mod macros;
+pub mod conflict;
+pub mod emergency;
+pub mod events;
+pub mod fusion;
+pub mod geofence;
+pub mod history;
+pub mod identity;
+pub mod kinematics;
+pub mod link;
+pub mod phase;
+pub mod spatial;
+pub mod state;
+pub mod weather;
+pub mod zones;
+
+/// Mean radius of the Earth in meters
+pub(crate) const EARTH_RADIUS_METERS: f64 = 6_371_000.;
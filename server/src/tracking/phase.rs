@@ -0,0 +1,486 @@
+//! Flight phase of aircraft
+//!
+//! Each aircraft has a state machine driven by its velocity updates, its
+//!  latest altitude, and the operational status and height it reports
+//!  over Remote ID. Departures and arrivals are confirmed from telemetry
+//!  alone, by publishing an event whenever the phase of an aircraft
+//!  changes, with the maneuver it completed if any:
+//!
+//! - takeoff: from the ground to the air
+//! - landing: from the air to the ground
+//! - hover: nearly stationary in the air
+//! - go-around: climbing again after descending close to the ground,
+//!   without landing
+//!
+//! The height of an aircraft is reported in Remote ID, or else derived
+//!  from the altitude where it was last seen on the ground. Without a
+//!  height, an aircraft is considered on the ground while it is nearly
+//!  stationary, and landed if it was airborne before.
+
+use crate::msg::netrid::OperationalStatus;
+use crate::rest::api::rest_types::{AircraftStatePosition, AircraftStateVelocity, FlightPhase};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Aircraft slower than this (m/s) horizontally may be on the ground
+const STATIONARY_GROUND_SPEED_MPS: f32 = 1.0;
+
+/// Vertical speeds slower than this (m/s) are level flight
+const LEVEL_VERTICAL_SPEED_MPS: f32 = 1.0;
+
+/// Stationary aircraft higher than this (m) above the ground are hovering
+const HOVER_MIN_HEIGHT_METERS: f64 = 5.0;
+
+/// Climbs after descending below this height (m) are go-arounds
+const GO_AROUND_MAX_HEIGHT_METERS: f64 = 150.0;
+
+/// Maneuvers completed by an aircraft when its phase changes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PhaseTransition {
+    /// From the ground to the air
+    Takeoff,
+
+    /// From the air to the ground
+    Landing,
+
+    /// Nearly stationary in the air
+    Hover,
+
+    /// Climbing again after descending close to the ground
+    GoAround,
+}
+
+/// Flight state reported by an aircraft itself, such as over Remote ID
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ReportedState {
+    /// Operational status of the aircraft
+    pub operational_status: Option<OperationalStatus>,
+
+    /// Height above takeoff or ground (m)
+    pub height_meters: Option<f64>,
+}
+
+/// State machine of an aircraft's flight phase, kept with its state
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PhaseState {
+    /// Current flight phase
+    pub phase: FlightPhase,
+
+    /// Altitude (m) where the aircraft was last seen on the ground
+    pub ground_altitude_meters: Option<f64>,
+
+    /// Lowest height (m) of the latest descent, until the aircraft climbs
+    pub descent_height_meters: Option<f64>,
+}
+
+/// Change of an aircraft's flight phase, published to RabbitMQ
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PhaseChange {
+    /// Identifier of the aircraft
+    pub identifier: String,
+
+    /// Previous flight phase, if the aircraft was tracked before
+    pub previous: Option<FlightPhase>,
+
+    /// New flight phase
+    pub phase: FlightPhase,
+
+    /// Maneuver completed by the aircraft, if any
+    pub transition: Option<PhaseTransition>,
+
+    /// Latest position of the aircraft
+    pub position: Option<AircraftStatePosition>,
+
+    /// Time of the change
+    pub timestamp: DateTime<Utc>,
+}
+
+/// If an aircraft in a phase is airborne
+pub fn is_airborne(phase: FlightPhase) -> bool {
+    matches!(
+        phase,
+        FlightPhase::Climbing | FlightPhase::Cruise | FlightPhase::Descending | FlightPhase::Hover
+    )
+}
+
+/// Phase of an aircraft after a velocity update
+///
+/// `height_meters` is the height of the aircraft above the ground or its
+///  takeoff point, if known.
+fn next_phase(
+    previous: Option<FlightPhase>,
+    velocity: &AircraftStateVelocity,
+    operational_status: Option<OperationalStatus>,
+    height_meters: Option<f64>,
+) -> FlightPhase {
+    let vertical = velocity.velocity_vertical_mps;
+    let stationary = velocity.velocity_horizontal_ground_mps < STATIONARY_GROUND_SPEED_MPS
+        && vertical.abs() < LEVEL_VERTICAL_SPEED_MPS;
+
+    let on_ground = match operational_status {
+        Some(OperationalStatus::Ground) => true,
+        Some(OperationalStatus::Airborne) | Some(OperationalStatus::Emergency) => false,
+        _ => match height_meters {
+            Some(height) => stationary && height < HOVER_MIN_HEIGHT_METERS,
+            // Without a height, only a known hover can be told from the ground
+            None => stationary && previous != Some(FlightPhase::Hover),
+        },
+    };
+
+    if on_ground {
+        return match previous {
+            Some(phase) if is_airborne(phase) || phase == FlightPhase::Landed => {
+                FlightPhase::Landed
+            }
+            _ => FlightPhase::OnGround,
+        };
+    }
+
+    if stationary {
+        FlightPhase::Hover
+    } else if vertical >= LEVEL_VERTICAL_SPEED_MPS {
+        FlightPhase::Climbing
+    } else if vertical <= -LEVEL_VERTICAL_SPEED_MPS {
+        FlightPhase::Descending
+    } else {
+        FlightPhase::Cruise
+    }
+}
+
+/// Lower of two heights, if either is known
+fn lowest(a: Option<f64>, b: Option<f64>) -> Option<f64> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// Maneuver completed by an aircraft when its phase changes
+fn transition(
+    previous: Option<&PhaseState>,
+    phase: FlightPhase,
+    height_meters: Option<f64>,
+) -> Option<PhaseTransition> {
+    // An aircraft first seen in the air didn't take off in view
+    let previous = previous?;
+    if previous.phase == phase {
+        return None;
+    }
+
+    match (is_airborne(previous.phase), is_airborne(phase)) {
+        (false, true) => Some(PhaseTransition::Takeoff),
+        (true, false) => Some(PhaseTransition::Landing),
+        _ if phase == FlightPhase::Hover => Some(PhaseTransition::Hover),
+        _ if phase == FlightPhase::Climbing => {
+            let lowest = lowest(previous.descent_height_meters, height_meters)?;
+            (lowest < GO_AROUND_MAX_HEIGHT_METERS).then_some(PhaseTransition::GoAround)
+        }
+        _ => None,
+    }
+}
+
+/// Updates the phase of an aircraft after a velocity update
+///
+/// Returns the new state of the machine, and the maneuver completed by
+///  the aircraft if any.
+pub fn update(
+    previous: Option<&PhaseState>,
+    velocity: &AircraftStateVelocity,
+    altitude_meters: Option<f64>,
+    reported: ReportedState,
+) -> (PhaseState, Option<PhaseTransition>) {
+    let ground_altitude_meters = previous.and_then(|state| state.ground_altitude_meters);
+    let height_meters = reported.height_meters.or_else(|| {
+        altitude_meters
+            .zip(ground_altitude_meters)
+            .map(|(altitude, ground)| altitude - ground)
+    });
+
+    let phase = next_phase(
+        previous.map(|state| state.phase),
+        velocity,
+        reported.operational_status,
+        height_meters,
+    );
+
+    let transition = transition(previous, phase, height_meters);
+    let (ground_altitude_meters, descent_height_meters) = match phase {
+        FlightPhase::OnGround | FlightPhase::Landed => {
+            (altitude_meters.or(ground_altitude_meters), None)
+        }
+        _ if transition == Some(PhaseTransition::GoAround) => (ground_altitude_meters, None),
+        FlightPhase::Descending => {
+            let descent = previous.and_then(|state| state.descent_height_meters);
+            (ground_altitude_meters, lowest(descent, height_meters))
+        }
+        FlightPhase::Climbing => (ground_altitude_meters, None),
+        _ => (
+            ground_altitude_meters,
+            previous.and_then(|state| state.descent_height_meters),
+        ),
+    };
+
+    let state = PhaseState {
+        phase,
+        ground_altitude_meters,
+        descent_height_meters,
+    };
+
+    (state, transition)
+}
+
+/// Publishes a change of an aircraft's flight phase to RabbitMQ
+pub async fn publish_phase_change(mq_channel: &lapin::Channel, change: &PhaseChange) {
+    tracking_debug!(
+        "(publish_phase_change) {} is now {:?} ({:?}).",
+        change.identifier,
+        change.phase,
+        change.transition
+    );
+
+    let Ok(msg) = serde_json::to_vec(change) else {
+        tracking_warn!("(publish_phase_change) could not serialize phase change.");
+        return;
+    };
+
+    let _ = mq_channel
+        .basic_publish(
+            crate::amqp::EXCHANGE_NAME_TELEMETRY,
+            crate::amqp::ROUTING_KEY_FLIGHT_PHASE,
+            lapin::options::BasicPublishOptions::default(),
+            &msg,
+            lapin::BasicProperties::default(),
+        )
+        .await
+        .map_err(|e| {
+            tracking_warn!("(publish_phase_change) could not push phase change to RabbitMQ: {e}.");
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn velocity(ground_mps: f32, vertical_mps: f32) -> AircraftStateVelocity {
+        AircraftStateVelocity {
+            velocity_horizontal_ground_mps: ground_mps,
+            velocity_vertical_mps: vertical_mps,
+            track_angle_degrees: 90.,
+            timestamp_network: Utc::now(),
+            timestamp_asset: None,
+        }
+    }
+
+    fn state(phase: FlightPhase) -> PhaseState {
+        PhaseState {
+            phase,
+            ground_altitude_meters: None,
+            descent_height_meters: None,
+        }
+    }
+
+    #[test]
+    fn test_next_phase_airborne() {
+        assert_eq!(
+            next_phase(None, &velocity(20., 3.), None, None),
+            FlightPhase::Climbing
+        );
+        assert_eq!(
+            next_phase(None, &velocity(20., 0.5), None, None),
+            FlightPhase::Cruise
+        );
+        assert_eq!(
+            next_phase(Some(FlightPhase::Cruise), &velocity(20., -3.), None, None),
+            FlightPhase::Descending
+        );
+
+        // Vertical takeoff
+        assert_eq!(
+            next_phase(Some(FlightPhase::OnGround), &velocity(0., 2.), None, None),
+            FlightPhase::Climbing
+        );
+    }
+
+    #[test]
+    fn test_next_phase_stationary() {
+        assert_eq!(
+            next_phase(None, &velocity(0.5, 0.), None, None),
+            FlightPhase::OnGround
+        );
+        assert_eq!(
+            next_phase(Some(FlightPhase::OnGround), &velocity(0., 0.), None, None),
+            FlightPhase::OnGround
+        );
+
+        assert_eq!(
+            next_phase(
+                Some(FlightPhase::Descending),
+                &velocity(0., -0.2),
+                None,
+                None
+            ),
+            FlightPhase::Landed
+        );
+
+        assert_eq!(
+            next_phase(Some(FlightPhase::Landed), &velocity(0., 0.), None, None),
+            FlightPhase::Landed
+        );
+    }
+
+    #[test]
+    fn test_next_phase_hover() {
+        assert_eq!(
+            next_phase(
+                Some(FlightPhase::Climbing),
+                &velocity(0., 0.),
+                None,
+                Some(30.)
+            ),
+            FlightPhase::Hover
+        );
+        assert_eq!(
+            next_phase(Some(FlightPhase::Hover), &velocity(0., 0.), None, None),
+            FlightPhase::Hover
+        );
+        assert_eq!(
+            next_phase(Some(FlightPhase::Hover), &velocity(0., 0.), None, Some(1.)),
+            FlightPhase::Landed
+        );
+    }
+
+    #[test]
+    fn test_next_phase_operational_status() {
+        let airborne = Some(OperationalStatus::Airborne);
+        assert_eq!(
+            next_phase(
+                Some(FlightPhase::Climbing),
+                &velocity(0., 0.),
+                airborne,
+                None
+            ),
+            FlightPhase::Hover
+        );
+
+        // Taxiing
+        let ground = Some(OperationalStatus::Ground);
+        assert_eq!(
+            next_phase(None, &velocity(5., 0.), ground, None),
+            FlightPhase::OnGround
+        );
+        assert_eq!(
+            next_phase(
+                Some(FlightPhase::Descending),
+                &velocity(5., 0.),
+                ground,
+                None
+            ),
+            FlightPhase::Landed
+        );
+    }
+
+    #[test]
+    fn test_update_takeoff_and_landing() {
+        let (ground, transition) =
+            update(None, &velocity(0., 0.), Some(20.), ReportedState::default());
+
+        assert_eq!(ground.phase, FlightPhase::OnGround);
+        assert_eq!(ground.ground_altitude_meters, Some(20.));
+        assert_eq!(transition, None);
+
+        let (climbing, transition) = update(
+            Some(&ground),
+            &velocity(0., 3.),
+            Some(25.),
+            ReportedState::default(),
+        );
+
+        assert_eq!(climbing.phase, FlightPhase::Climbing);
+        assert_eq!(transition, Some(PhaseTransition::Takeoff));
+
+        // Hovering above the takeoff point
+        let (hover, transition) = update(
+            Some(&climbing),
+            &velocity(0., 0.),
+            Some(60.),
+            ReportedState::default(),
+        );
+
+        assert_eq!(hover.phase, FlightPhase::Hover);
+        assert_eq!(transition, Some(PhaseTransition::Hover));
+
+        let (landed, transition) = update(
+            Some(&hover),
+            &velocity(0., 0.),
+            Some(21.),
+            ReportedState::default(),
+        );
+
+        assert_eq!(landed.phase, FlightPhase::Landed);
+        assert_eq!(transition, Some(PhaseTransition::Landing));
+    }
+
+    #[test]
+    fn test_update_go_around() {
+        let reported = |height_meters| ReportedState {
+            operational_status: Some(OperationalStatus::Airborne),
+            height_meters: Some(height_meters),
+        };
+
+        let (descending, _) = update(
+            Some(&state(FlightPhase::Cruise)),
+            &velocity(20., -3.),
+            None,
+            reported(80.),
+        );
+
+        assert_eq!(descending.descent_height_meters, Some(80.));
+
+        // Levelling off before climbing away
+        let (level, transition) =
+            update(Some(&descending), &velocity(20., 0.), None, reported(40.));
+
+        assert_eq!(level.phase, FlightPhase::Cruise);
+        assert_eq!(transition, None);
+
+        let (climbing, transition) = update(Some(&level), &velocity(20., 3.), None, reported(45.));
+
+        assert_eq!(climbing.phase, FlightPhase::Climbing);
+        assert_eq!(transition, Some(PhaseTransition::GoAround));
+        assert_eq!(climbing.descent_height_meters, None);
+    }
+
+    #[test]
+    fn test_update_no_go_around() {
+        // Climbing again after a descent at altitude
+        let (descending, _) = update(
+            Some(&state(FlightPhase::Cruise)),
+            &velocity(20., -3.),
+            None,
+            ReportedState {
+                operational_status: None,
+                height_meters: Some(800.),
+            },
+        );
+
+        let (_, transition) = update(
+            Some(&descending),
+            &velocity(20., 3.),
+            None,
+            ReportedState::default(),
+        );
+
+        assert_eq!(transition, None);
+
+        // Aircraft first seen in the air
+        let (_, transition) = update(
+            None,
+            &velocity(20., 3.),
+            Some(500.),
+            ReportedState::default(),
+        );
+
+        assert_eq!(transition, None);
+    }
+}
@@ -0,0 +1,388 @@
+//! Spatial queries over the live picture of aircraft
+//!
+//! The latest position of each aircraft is kept in a geospatial index,
+//!  which is updated on every position push. Queries return the latest
+//!  state of each aircraft inside a bounding box or within a radius of a
+//!  point, optionally limited to an altitude band.
+
+use super::kinematics::haversine_distance_meters;
+use super::state;
+use crate::cache::pool::{CacheError, TelemetryPool};
+use crate::rest::api::rest_types::AircraftState;
+
+/// Key of the geospatial index of aircraft positions
+const GEO_KEY: &str = "positions";
+
+/// Margin added to a bounding box search, so that no aircraft near the
+///  edges is missed before the exact check
+const BOX_SEARCH_MARGIN: f64 = 1.01;
+
+/// Errors of a spatial query
+#[derive(Debug, Clone, Copy)]
+pub enum QueryError {
+    /// The query arguments are out of range or inconsistent
+    Invalid,
+
+    /// The cache could not be queried
+    Cache(CacheError),
+}
+
+impl From<CacheError> for QueryError {
+    fn from(e: CacheError) -> Self {
+        QueryError::Cache(e)
+    }
+}
+
+/// A range of altitudes, unbounded on sides which aren't given
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct AltitudeBand {
+    /// Lowest altitude (m)
+    pub min_altitude_meters: Option<f64>,
+
+    /// Highest altitude (m)
+    pub max_altitude_meters: Option<f64>,
+}
+
+impl AltitudeBand {
+    /// If the band has a lower bound no higher than its upper bound
+    pub fn is_valid(&self) -> bool {
+        match (self.min_altitude_meters, self.max_altitude_meters) {
+            (Some(min), Some(max)) => min <= max,
+            _ => true,
+        }
+    }
+
+    /// If an altitude is inside the band
+    pub fn contains(&self, altitude_meters: f64) -> bool {
+        self.min_altitude_meters
+            .map_or(true, |min| altitude_meters >= min)
+            && self
+                .max_altitude_meters
+                .map_or(true, |max| altitude_meters <= max)
+    }
+}
+
+/// An area bounded by latitudes and longitudes
+///
+/// Boxes crossing the antimeridian are not supported.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    /// Southern edge (degrees)
+    pub min_latitude: f64,
+
+    /// Western edge (degrees)
+    pub min_longitude: f64,
+
+    /// Northern edge (degrees)
+    pub max_latitude: f64,
+
+    /// Eastern edge (degrees)
+    pub max_longitude: f64,
+}
+
+impl BoundingBox {
+    /// If the box is within valid coordinates and not inverted
+    pub fn is_valid(&self) -> bool {
+        (-90. ..=90.).contains(&self.min_latitude)
+            && (-90. ..=90.).contains(&self.max_latitude)
+            && (-180. ..=180.).contains(&self.min_longitude)
+            && (-180. ..=180.).contains(&self.max_longitude)
+            && self.min_latitude <= self.max_latitude
+            && self.min_longitude <= self.max_longitude
+    }
+
+    /// If a point is inside the box
+    pub fn contains(&self, latitude: f64, longitude: f64) -> bool {
+        (self.min_latitude..=self.max_latitude).contains(&latitude)
+            && (self.min_longitude..=self.max_longitude).contains(&longitude)
+    }
+
+    /// Center of the box (latitude, longitude)
+    fn center(&self) -> (f64, f64) {
+        (
+            (self.min_latitude + self.max_latitude) / 2.,
+            (self.min_longitude + self.max_longitude) / 2.,
+        )
+    }
+
+    /// Width and height of the box (m), at its widest latitude
+    fn size_meters(&self) -> (f64, f64) {
+        let widest_latitude = if self.min_latitude <= 0. && self.max_latitude >= 0. {
+            0.
+        } else if self.min_latitude.abs() < self.max_latitude.abs() {
+            self.min_latitude
+        } else {
+            self.max_latitude
+        };
+
+        let width = haversine_distance_meters(
+            widest_latitude,
+            self.min_longitude,
+            widest_latitude,
+            self.max_longitude,
+        );
+
+        let height = haversine_distance_meters(
+            self.min_latitude,
+            self.min_longitude,
+            self.max_latitude,
+            self.min_longitude,
+        );
+
+        (width, height)
+    }
+}
+
+/// If a point is inside a polygon of (latitude, longitude) vertices
+///
+/// Uses the even-odd rule on planar coordinates, which is accurate for
+///  areas of a few kilometers. Polygons crossing the antimeridian are not
+///  supported.
+pub fn polygon_contains(vertices: &[(f64, f64)], latitude: f64, longitude: f64) -> bool {
+    let mut inside = false;
+    let mut previous = match vertices.last() {
+        Some(vertex) => *vertex,
+        None => return false,
+    };
+
+    for &(vertex_latitude, vertex_longitude) in vertices {
+        let (previous_latitude, previous_longitude) = previous;
+        if (vertex_latitude > latitude) != (previous_latitude > latitude) {
+            let crossing_longitude = vertex_longitude
+                + (latitude - vertex_latitude) * (previous_longitude - vertex_longitude)
+                    / (previous_latitude - vertex_latitude);
+
+            if longitude < crossing_longitude {
+                inside = !inside;
+            }
+        }
+
+        previous = (vertex_latitude, vertex_longitude);
+    }
+
+    inside
+}
+
+/// Updates the position of an aircraft in the index
+pub async fn update_position(
+    pool: &mut TelemetryPool,
+    identifier: &str,
+    latitude: f64,
+    longitude: f64,
+) {
+    if let Err(e) = pool.geo_add(GEO_KEY, identifier, latitude, longitude).await {
+        tracking_warn!("(update_position) could not index position of {identifier}: {e}.");
+    }
+}
+
+/// Gets the latest states of aircraft, keeping those with a position matching `filter`
+///
+/// Aircraft whose states have expired are removed from the index.
+async fn states_of<F>(
+    pool: &mut TelemetryPool,
+    identifiers: Vec<String>,
+    filter: F,
+) -> Result<Vec<AircraftState>, CacheError>
+where
+    F: Fn(f64, f64, f64) -> bool,
+{
+    let mut states = vec![];
+    let mut expired = vec![];
+    for identifier in identifiers {
+        let Some(state) = state::get_state(pool, &identifier).await? else {
+            expired.push(identifier);
+            continue;
+        };
+
+        let Some(position) = &state.position else {
+            continue;
+        };
+
+        if filter(
+            position.latitude,
+            position.longitude,
+            position.altitude_meters,
+        ) {
+            states.push(state);
+        }
+    }
+
+    if let Err(e) = pool.index_remove(GEO_KEY, &expired).await {
+        tracking_warn!("(states_of) could not remove expired aircraft from index: {e}.");
+    }
+
+    Ok(states)
+}
+
+/// Gets the latest states of aircraft inside a bounding box
+pub async fn within_bounding_box(
+    pool: &mut TelemetryPool,
+    bounding_box: BoundingBox,
+    band: AltitudeBand,
+) -> Result<Vec<AircraftState>, QueryError> {
+    if !bounding_box.is_valid() || !band.is_valid() {
+        return Err(QueryError::Invalid);
+    }
+
+    let (latitude, longitude) = bounding_box.center();
+    let (width, height) = bounding_box.size_meters();
+    let identifiers = pool
+        .geo_search_box(
+            GEO_KEY,
+            latitude,
+            longitude,
+            width * BOX_SEARCH_MARGIN,
+            height * BOX_SEARCH_MARGIN,
+        )
+        .await?;
+
+    let states = states_of(pool, identifiers, |latitude, longitude, altitude| {
+        bounding_box.contains(latitude, longitude) && band.contains(altitude)
+    })
+    .await?;
+
+    Ok(states)
+}
+
+/// Gets the latest states of aircraft within a radius (m) of a point
+pub async fn within_radius(
+    pool: &mut TelemetryPool,
+    latitude: f64,
+    longitude: f64,
+    radius_meters: f64,
+    band: AltitudeBand,
+) -> Result<Vec<AircraftState>, QueryError> {
+    if !(-90. ..=90.).contains(&latitude)
+        || !(-180. ..=180.).contains(&longitude)
+        || radius_meters <= 0.
+        || !band.is_valid()
+    {
+        return Err(QueryError::Invalid);
+    }
+
+    let identifiers = pool
+        .geo_search_radius(GEO_KEY, latitude, longitude, radius_meters)
+        .await?;
+
+    let states = states_of(pool, identifiers, |_, _, altitude| band.contains(altitude)).await?;
+    Ok(states)
+}
+
+/// Gets the latest states of aircraft inside an altitude band
+pub async fn within_altitude_band(
+    pool: &mut TelemetryPool,
+    band: AltitudeBand,
+) -> Result<Vec<AircraftState>, QueryError> {
+    if !band.is_valid() {
+        return Err(QueryError::Invalid);
+    }
+
+    let states = state::list_states(pool)
+        .await?
+        .into_iter()
+        .filter(|state| {
+            state
+                .position
+                .as_ref()
+                .map_or(false, |position| band.contains(position.altitude_meters))
+        })
+        .collect();
+
+    Ok(states)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_altitude_band() {
+        let band = AltitudeBand {
+            min_altitude_meters: Some(100.),
+            max_altitude_meters: Some(200.),
+        };
+
+        assert!(band.is_valid());
+        assert!(band.contains(150.));
+        assert!(!band.contains(99.));
+        assert!(!band.contains(201.));
+
+        let band = AltitudeBand {
+            min_altitude_meters: Some(100.),
+            max_altitude_meters: None,
+        };
+
+        assert!(band.contains(10_000.));
+        assert!(AltitudeBand::default().contains(-100.));
+
+        let band = AltitudeBand {
+            min_altitude_meters: Some(200.),
+            max_altitude_meters: Some(100.),
+        };
+
+        assert!(!band.is_valid());
+    }
+
+    #[test]
+    fn test_bounding_box() {
+        let bounding_box = BoundingBox {
+            min_latitude: 52.,
+            min_longitude: 4.,
+            max_latitude: 53.,
+            max_longitude: 5.,
+        };
+
+        assert!(bounding_box.is_valid());
+        assert!(bounding_box.contains(52.5, 4.5));
+        assert!(!bounding_box.contains(51.9, 4.5));
+        assert!(!bounding_box.contains(52.5, 5.1));
+        assert_eq!(bounding_box.center(), (52.5, 4.5));
+
+        // One degree of latitude is about 111 km,
+        //  one degree of longitude at 52 degrees is about 68 km
+        let (width, height) = bounding_box.size_meters();
+        assert!((width - 68_459.).abs() < 500.);
+        assert!((height - 111_195.).abs() < 500.);
+    }
+
+    #[test]
+    fn test_bounding_box_invalid() {
+        let bounding_box = BoundingBox {
+            min_latitude: 53.,
+            min_longitude: 4.,
+            max_latitude: 52.,
+            max_longitude: 5.,
+        };
+
+        assert!(!bounding_box.is_valid());
+
+        // Crossing the antimeridian
+        let bounding_box = BoundingBox {
+            min_latitude: 52.,
+            min_longitude: 179.,
+            max_latitude: 53.,
+            max_longitude: -179.,
+        };
+
+        assert!(!bounding_box.is_valid());
+    }
+
+    #[test]
+    fn test_polygon_contains() {
+        // Concave polygon, an L shape
+        let vertices = [
+            (52., 4.),
+            (52., 4.2),
+            (52.1, 4.2),
+            (52.1, 4.1),
+            (52.2, 4.1),
+            (52.2, 4.),
+        ];
+
+        assert!(polygon_contains(&vertices, 52.05, 4.15));
+        assert!(polygon_contains(&vertices, 52.15, 4.05));
+        assert!(!polygon_contains(&vertices, 52.15, 4.15));
+        assert!(!polygon_contains(&vertices, 51.9, 4.05));
+        assert!(!polygon_contains(&[], 52.05, 4.05));
+    }
+}
@@ -0,0 +1,296 @@
+//! Latest state of each aircraft
+//!
+//! The latest identity, position and velocity of each aircraft are kept
+//!  in the cache, so that they can be read without going through svc-gis.
+//!  Each aircraft's state is a hash of serialized fields, so that position
+//!  and velocity updates don't overwrite each other. A sorted index of
+//!  aircraft by time of their latest update allows listing all aircraft,
+//!  and positions are also indexed for [spatial](super::spatial) queries.
+
+use super::phase::{PhaseChange, PhaseState, ReportedState};
+use crate::cache::pool::{CacheError, TelemetryPool};
+use crate::rest::api::rest_types::{
+    AircraftState, AircraftStatePosition, AircraftStateVelocity, AircraftStatus, TelemetrySource,
+};
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use svc_gis_client_grpc::prelude::types::*;
+
+/// Aircraft states in the cache will expire after 5 minutes without updates
+const CACHE_EXPIRE_MS_STATE: u32 = 300_000;
+
+/// Aircraft without updates for this long are stale
+const STALE_AFTER_SECONDS: i64 = 10;
+
+/// Key of the index of aircraft by time of their latest update
+const INDEX_KEY: &str = "states";
+
+// Fields of an aircraft's state in the cache
+const FIELD_AIRCRAFT_TYPE: &str = "aircraft_type";
+const FIELD_POSITION: &str = "position";
+const FIELD_VELOCITY: &str = "velocity";
+const FIELD_SOURCE: &str = "source";
+const FIELD_LAST_UPDATE: &str = "last_update";
+const FIELD_PHASE: &str = "phase";
+
+/// Key of an aircraft's state in the cache
+fn state_key(identifier: &str) -> String {
+    format!("{identifier}:state")
+}
+
+impl From<&AircraftPosition> for AircraftStatePosition {
+    fn from(position: &AircraftPosition) -> Self {
+        AircraftStatePosition {
+            latitude: position.position.latitude,
+            longitude: position.position.longitude,
+            altitude_meters: position.position.altitude_meters,
+            timestamp_network: position.timestamp_network,
+            timestamp_asset: position.timestamp_asset,
+        }
+    }
+}
+
+impl From<&AircraftVelocity> for AircraftStateVelocity {
+    fn from(velocity: &AircraftVelocity) -> Self {
+        AircraftStateVelocity {
+            velocity_horizontal_ground_mps: velocity.velocity_horizontal_ground_mps,
+            velocity_vertical_mps: velocity.velocity_vertical_mps,
+            track_angle_degrees: velocity.track_angle_degrees,
+            timestamp_network: velocity.timestamp_network,
+            timestamp_asset: velocity.timestamp_asset,
+        }
+    }
+}
+
+/// Deserializes a field of an aircraft's state
+fn field<T: DeserializeOwned>(fields: &HashMap<String, String>, name: &str) -> Option<T> {
+    fields
+        .get(name)
+        .and_then(|value| serde_json::from_str(value).ok())
+}
+
+/// Status of an aircraft, from the time of its latest update
+pub fn status(last_update: DateTime<Utc>, now: DateTime<Utc>) -> AircraftStatus {
+    if (now - last_update).num_seconds() < STALE_AFTER_SECONDS {
+        AircraftStatus::Active
+    } else {
+        AircraftStatus::Stale
+    }
+}
+
+/// Updates fields of an aircraft's state, and the time of its latest update
+///
+/// Failures are logged but not returned, a missed update shouldn't
+///  cause an otherwise valid packet to be rejected.
+async fn update(
+    pool: &mut TelemetryPool,
+    identifier: &str,
+    mut fields: Vec<(&str, String)>,
+    source: Option<TelemetrySource>,
+) {
+    let now = Utc::now();
+    if let Some(source) = source {
+        fields.push((FIELD_SOURCE, serde_json::json!(source).to_string()));
+    }
+
+    fields.push((FIELD_LAST_UPDATE, serde_json::json!(now).to_string()));
+    if let Err(e) = pool
+        .set_fields(&state_key(identifier), fields, CACHE_EXPIRE_MS_STATE)
+        .await
+    {
+        tracking_warn!("(update) could not update state of {identifier}: {e}.");
+        return;
+    }
+
+    if let Err(e) = pool
+        .index_add(INDEX_KEY, identifier, now.timestamp_millis())
+        .await
+    {
+        tracking_warn!("(update) could not index state of {identifier}: {e}.");
+    }
+}
+
+/// Updates the identity of an aircraft
+pub async fn update_identity(
+    pool: &mut TelemetryPool,
+    identifier: &str,
+    aircraft_type: AircraftType,
+) {
+    let fields = vec![(
+        FIELD_AIRCRAFT_TYPE,
+        serde_json::json!(format!("{:?}", aircraft_type)).to_string(),
+    )];
+
+    update(pool, identifier, fields, None).await;
+}
+
+/// Updates the position of an aircraft
+pub async fn update_position(
+    pool: &mut TelemetryPool,
+    position: &AircraftPosition,
+    source: TelemetrySource,
+) {
+    let state = AircraftStatePosition::from(position);
+
+    let fields = vec![(FIELD_POSITION, serde_json::json!(state).to_string())];
+    update(pool, &position.identifier, fields, Some(source)).await;
+    super::spatial::update_position(pool, &position.identifier, state.latitude, state.longitude)
+        .await;
+}
+
+/// Updates the velocity of an aircraft, and its flight phase
+///
+/// Returns the change of the aircraft's flight phase, if any.
+pub async fn update_velocity(
+    pool: &mut TelemetryPool,
+    velocity: &AircraftVelocity,
+    source: TelemetrySource,
+    reported: ReportedState,
+) -> Option<PhaseChange> {
+    let state = AircraftStateVelocity::from(velocity);
+    let fields = match pool.get_fields(&state_key(&velocity.identifier)).await {
+        Ok(fields) => fields,
+        Err(e) => {
+            tracking_warn!(
+                "(update_velocity) could not get phase of {}: {e}.",
+                velocity.identifier
+            );
+            HashMap::new()
+        }
+    };
+
+    let previous = field::<PhaseState>(&fields, FIELD_PHASE);
+    let position = field::<AircraftStatePosition>(&fields, FIELD_POSITION);
+    let altitude_meters = position.as_ref().map(|position| position.altitude_meters);
+    let (phase_state, transition) =
+        super::phase::update(previous.as_ref(), &state, altitude_meters, reported);
+
+    let fields = vec![
+        (FIELD_VELOCITY, serde_json::json!(state).to_string()),
+        (FIELD_PHASE, serde_json::json!(phase_state).to_string()),
+    ];
+
+    update(pool, &velocity.identifier, fields, Some(source)).await;
+
+    let previous = previous.map(|previous| previous.phase);
+    if previous == Some(phase_state.phase) {
+        return None;
+    }
+
+    Some(PhaseChange {
+        identifier: velocity.identifier.clone(),
+        previous,
+        phase: phase_state.phase,
+        transition,
+        position,
+        timestamp: Utc::now(),
+    })
+}
+
+/// Builds an aircraft's state from its fields in the cache
+///
+/// Returns `None` if the state has no time of latest update, such as
+///  when the state expired.
+fn from_fields(
+    identifier: &str,
+    fields: &HashMap<String, String>,
+    now: DateTime<Utc>,
+) -> Option<AircraftState> {
+    let last_update: DateTime<Utc> = field(fields, FIELD_LAST_UPDATE)?;
+    Some(AircraftState {
+        identifier: identifier.to_string(),
+        aircraft_type: field(fields, FIELD_AIRCRAFT_TYPE),
+        position: field(fields, FIELD_POSITION),
+        velocity: field(fields, FIELD_VELOCITY),
+        source: field(fields, FIELD_SOURCE),
+        last_update,
+        status: status(last_update, now),
+        phase: field::<PhaseState>(fields, FIELD_PHASE).map(|state| state.phase),
+    })
+}
+
+/// Gets the latest state of an aircraft, if it is known
+pub async fn get_state(
+    pool: &mut TelemetryPool,
+    identifier: &str,
+) -> Result<Option<AircraftState>, CacheError> {
+    let fields = pool.get_fields(&state_key(identifier)).await?;
+    Ok(from_fields(identifier, &fields, Utc::now()))
+}
+
+/// Gets the latest states of all aircraft updated before their states expired
+pub async fn list_states(pool: &mut TelemetryPool) -> Result<Vec<AircraftState>, CacheError> {
+    let now = Utc::now();
+    let since = now.timestamp_millis() - CACHE_EXPIRE_MS_STATE as i64;
+    let identifiers = pool.index_members_since(INDEX_KEY, since).await?;
+
+    let mut states = vec![];
+    for identifier in identifiers {
+        let fields = pool.get_fields(&state_key(&identifier)).await?;
+        if let Some(state) = from_fields(&identifier, &fields, now) {
+            states.push(state);
+        }
+    }
+
+    Ok(states)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rest::api::rest_types::FlightPhase;
+    use chrono::Duration;
+
+    #[test]
+    fn test_status() {
+        let now = Utc::now();
+        assert_eq!(status(now, now), AircraftStatus::Active);
+        assert_eq!(
+            status(now - Duration::seconds(STALE_AFTER_SECONDS), now),
+            AircraftStatus::Stale
+        );
+    }
+
+    #[test]
+    fn test_from_fields() {
+        let now = Utc::now();
+        let position = AircraftStatePosition {
+            latitude: 52.,
+            longitude: 4.,
+            altitude_meters: 100.,
+            timestamp_network: now,
+            timestamp_asset: None,
+        };
+
+        let fields = HashMap::from([
+            (
+                FIELD_POSITION.to_string(),
+                serde_json::json!(position).to_string(),
+            ),
+            (FIELD_SOURCE.to_string(), "\"netrid\"".to_string()),
+            (
+                FIELD_PHASE.to_string(),
+                r#"{"phase": "cruise", "ground_altitude_meters": 20.0}"#.to_string(),
+            ),
+            (
+                FIELD_LAST_UPDATE.to_string(),
+                serde_json::json!(now).to_string(),
+            ),
+        ]);
+
+        let state = from_fields("aircraft", &fields, now).unwrap();
+        assert_eq!(state.identifier, "aircraft");
+        assert_eq!(state.position, Some(position));
+        assert_eq!(state.velocity, None);
+        assert_eq!(state.aircraft_type, None);
+        assert_eq!(state.source, Some(TelemetrySource::Netrid));
+        assert_eq!(state.status, AircraftStatus::Active);
+        assert_eq!(state.phase, Some(FlightPhase::Cruise));
+    }
+
+    #[test]
+    fn test_from_fields_expired() {
+        assert!(from_fields("aircraft", &HashMap::new(), Utc::now()).is_none());
+    }
+}
@@ -0,0 +1,506 @@
+//! Turbulence and wind inference
+//!
+//! Telemetry is aggregated into a grid of square cells, sized in degrees
+//!  from a distance north to south. Each velocity of an aircraft with a
+//!  recent position adds samples to the cell it is in:
+//!
+//! - turbulence, as the standard deviation of the aircraft's vertical
+//!   speed over the last seconds
+//! - wind, as the difference between the ground speed and the airspeed
+//!   along the track, when the airspeed is reported
+//!
+//! Only the component of the wind along the track of an aircraft can be
+//!  observed from its speeds. Averaged over aircraft flying in different
+//!  directions, the samples of a cell approximate the wind vector.
+//!
+//! Samples decay exponentially with a half-life, so that the grid follows
+//!  changing conditions, and cells without recent samples are dropped.
+
+use super::EARTH_RADIUS_METERS;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use svc_gis_client_grpc::prelude::types::*;
+
+/// Velocities more than this long after the latest position of an
+///  aircraft (s) are not placed in the grid
+const MAX_POSITION_AGE_SECONDS: i64 = 10;
+
+/// Time over which the variance of vertical speeds is computed (s)
+const VARIANCE_WINDOW_SECONDS: i64 = 30;
+
+/// Vertical speeds needed in the window to sample turbulence
+const MIN_VARIANCE_SAMPLES: usize = 3;
+
+/// Cells whose decayed samples weigh less than this are dropped
+const MIN_CELL_WEIGHT: f64 = 0.05;
+
+/// Aircraft without updates for this long are forgotten (s)
+const AIRCRAFT_EXPIRE_SECONDS: i64 = 60;
+
+/// Size of the cells, and how fast samples decay
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WeatherSettings {
+    /// Size of a cell north to south (m)
+    pub cell_size_meters: f64,
+
+    /// Time after which the weight of a sample is halved (s)
+    pub half_life_seconds: f64,
+}
+
+impl WeatherSettings {
+    /// Size of a cell in degrees of latitude and longitude
+    fn cell_size_degrees(&self) -> f64 {
+        (self.cell_size_meters / EARTH_RADIUS_METERS).to_degrees()
+    }
+}
+
+/// Weighted mean of decaying samples
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct Mean {
+    sum: f64,
+    weight: f64,
+}
+
+impl Mean {
+    fn add(&mut self, value: f64) {
+        self.sum += value;
+        self.weight += 1.;
+    }
+
+    fn scale(&mut self, factor: f64) {
+        self.sum *= factor;
+        self.weight *= factor;
+    }
+
+    fn value(&self) -> Option<f64> {
+        (self.weight >= MIN_CELL_WEIGHT).then(|| self.sum / self.weight)
+    }
+}
+
+/// Samples of a cell, decayed to the time it was last updated
+#[derive(Debug, Clone, PartialEq)]
+struct Cell {
+    turbulence: Mean,
+    wind_north: Mean,
+    wind_east: Mean,
+    updated: DateTime<Utc>,
+}
+
+impl Cell {
+    fn new(now: DateTime<Utc>) -> Self {
+        Cell {
+            turbulence: Mean::default(),
+            wind_north: Mean::default(),
+            wind_east: Mean::default(),
+            updated: now,
+        }
+    }
+
+    /// Decays the samples of the cell to a later time
+    fn decay(&mut self, now: DateTime<Utc>, half_life_seconds: f64) {
+        let elapsed = (now - self.updated).num_milliseconds() as f64 / 1000.;
+        if elapsed <= 0. {
+            return;
+        }
+
+        let factor = 0.5_f64.powf(elapsed / half_life_seconds);
+        self.turbulence.scale(factor);
+        self.wind_north.scale(factor);
+        self.wind_east.scale(factor);
+        self.updated = now;
+    }
+
+    fn is_empty(&self) -> bool {
+        self.turbulence.weight < MIN_CELL_WEIGHT && self.wind_north.weight < MIN_CELL_WEIGHT
+    }
+}
+
+/// Latest position and recent vertical speeds of an aircraft
+#[derive(Debug, Clone, PartialEq)]
+struct Aircraft {
+    /// Latitude and longitude of the latest position
+    location: (f64, f64),
+
+    /// Time the latest position was received
+    seen: DateTime<Utc>,
+
+    /// Vertical speeds (m/s) within the variance window
+    vertical_speeds: VecDeque<(DateTime<Utc>, f64)>,
+}
+
+/// Standard deviation of vertical speeds, if there are enough of them
+fn standard_deviation(speeds: &VecDeque<(DateTime<Utc>, f64)>) -> Option<f64> {
+    if speeds.len() < MIN_VARIANCE_SAMPLES {
+        return None;
+    }
+
+    let count = speeds.len() as f64;
+    let mean = speeds.iter().map(|(_, speed)| speed).sum::<f64>() / count;
+    let variance = speeds
+        .iter()
+        .map(|(_, speed)| (speed - mean).powi(2))
+        .sum::<f64>()
+        / count;
+
+    Some(variance.sqrt())
+}
+
+/// Turbulence and wind of a cell of the grid
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WeatherCell {
+    /// Southern edge of the cell
+    pub min_latitude: f64,
+
+    /// Western edge of the cell
+    pub min_longitude: f64,
+
+    /// Northern edge of the cell
+    pub max_latitude: f64,
+
+    /// Eastern edge of the cell
+    pub max_longitude: f64,
+
+    /// Mean standard deviation of vertical speeds (m/s), if sampled
+    pub turbulence_mps: Option<f64>,
+
+    /// Decayed number of turbulence samples
+    pub turbulence_samples: f64,
+
+    /// Estimated wind towards the north (m/s), if sampled
+    pub wind_north_mps: Option<f64>,
+
+    /// Estimated wind towards the east (m/s), if sampled
+    pub wind_east_mps: Option<f64>,
+
+    /// Decayed number of wind samples
+    pub wind_samples: f64,
+
+    /// Time of the latest sample
+    pub updated: DateTime<Utc>,
+}
+
+/// Grid of turbulence and wind, published to RabbitMQ
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WeatherGrid {
+    /// Size of the cells in degrees of latitude and longitude
+    pub cell_size_degrees: f64,
+
+    /// Cells with recent samples
+    pub cells: Vec<WeatherCell>,
+
+    /// Time the samples are decayed to
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Aircraft and the cells of the grid
+#[derive(Debug, Default)]
+struct Samples {
+    aircraft: HashMap<String, Aircraft>,
+    cells: BTreeMap<(i64, i64), Cell>,
+}
+
+/// Infers turbulence and wind from the telemetry of aircraft
+#[derive(Debug, Clone)]
+pub struct WeatherMonitor {
+    settings: WeatherSettings,
+    samples: Arc<Mutex<Samples>>,
+}
+
+impl WeatherMonitor {
+    /// Creates a monitor with an empty grid
+    pub fn new(settings: WeatherSettings) -> Self {
+        WeatherMonitor {
+            settings,
+            samples: Arc::default(),
+        }
+    }
+
+    /// Updates the latest position of an aircraft
+    pub fn update_position(&self, position: &AircraftPosition, now: DateTime<Utc>) {
+        let Ok(mut samples) = self.samples.lock() else {
+            tracking_error!("(update_position) could not lock weather samples.");
+            return;
+        };
+
+        if !samples.aircraft.contains_key(&position.identifier) {
+            samples.aircraft.retain(|_, aircraft| {
+                (now - aircraft.seen).num_seconds() < AIRCRAFT_EXPIRE_SECONDS
+            });
+        }
+
+        let location = (position.position.latitude, position.position.longitude);
+        samples
+            .aircraft
+            .entry(position.identifier.clone())
+            .and_modify(|aircraft| {
+                aircraft.location = location;
+                aircraft.seen = now;
+            })
+            .or_insert_with(|| Aircraft {
+                location,
+                seen: now,
+                vertical_speeds: VecDeque::new(),
+            });
+    }
+
+    /// Adds the turbulence and wind samples of a velocity to the cell of
+    ///  the aircraft
+    ///
+    /// Velocities of aircraft without a recent position are ignored.
+    pub fn update_velocity(&self, velocity: &AircraftVelocity, now: DateTime<Utc>) {
+        let Ok(mut samples) = self.samples.lock() else {
+            tracking_error!("(update_velocity) could not lock weather samples.");
+            return;
+        };
+
+        let Samples { aircraft, cells } = &mut *samples;
+        let Some(aircraft) = aircraft.get_mut(&velocity.identifier) else {
+            return;
+        };
+
+        let (latitude, longitude) = aircraft.location;
+        if (now - aircraft.seen).num_seconds() > MAX_POSITION_AGE_SECONDS {
+            return;
+        }
+
+        let speeds = &mut aircraft.vertical_speeds;
+        speeds.push_back((now, velocity.velocity_vertical_mps as f64));
+        while speeds
+            .front()
+            .is_some_and(|(time, _)| (now - *time).num_seconds() > VARIANCE_WINDOW_SECONDS)
+        {
+            speeds.pop_front();
+        }
+
+        let turbulence = standard_deviation(speeds);
+        let wind = velocity.velocity_horizontal_air_mps.map(|air| {
+            let along_track = (velocity.velocity_horizontal_ground_mps - air) as f64;
+            let track_angle = (velocity.track_angle_degrees as f64).to_radians();
+            (
+                along_track * track_angle.cos(),
+                along_track * track_angle.sin(),
+            )
+        });
+
+        if turbulence.is_none() && wind.is_none() {
+            return;
+        }
+
+        let size = self.settings.cell_size_degrees();
+        let key = (
+            (latitude / size).floor() as i64,
+            (longitude / size).floor() as i64,
+        );
+
+        let cell = cells.entry(key).or_insert_with(|| Cell::new(now));
+        cell.decay(now, self.settings.half_life_seconds);
+        if let Some(turbulence) = turbulence {
+            cell.turbulence.add(turbulence);
+        }
+
+        if let Some((north, east)) = wind {
+            cell.wind_north.add(north);
+            cell.wind_east.add(east);
+        }
+    }
+
+    /// Returns the cells with recent samples, decayed to a time
+    pub fn grid(&self, now: DateTime<Utc>) -> WeatherGrid {
+        let size = self.settings.cell_size_degrees();
+        let mut grid = WeatherGrid {
+            cell_size_degrees: size,
+            cells: vec![],
+            timestamp: now,
+        };
+
+        let Ok(mut samples) = self.samples.lock() else {
+            tracking_error!("(grid) could not lock weather samples.");
+            return grid;
+        };
+
+        samples.cells.retain(|_, cell| {
+            cell.decay(now, self.settings.half_life_seconds);
+            !cell.is_empty()
+        });
+
+        grid.cells = samples
+            .cells
+            .iter()
+            .map(|((latitude, longitude), cell)| WeatherCell {
+                min_latitude: *latitude as f64 * size,
+                min_longitude: *longitude as f64 * size,
+                max_latitude: (*latitude + 1) as f64 * size,
+                max_longitude: (*longitude + 1) as f64 * size,
+                turbulence_mps: cell.turbulence.value(),
+                turbulence_samples: cell.turbulence.weight,
+                wind_north_mps: cell.wind_north.value(),
+                wind_east_mps: cell.wind_east.value(),
+                wind_samples: cell.wind_north.weight,
+                updated: cell.updated,
+            })
+            .collect();
+
+        grid
+    }
+}
+
+/// Publishes the grid to RabbitMQ
+pub async fn publish_grid(mq_channel: &lapin::Channel, grid: &WeatherGrid) {
+    let Ok(msg) = serde_json::to_vec(grid) else {
+        tracking_warn!("(publish_grid) could not serialize weather grid.");
+        return;
+    };
+
+    let _ = mq_channel
+        .basic_publish(
+            crate::amqp::EXCHANGE_NAME_TELEMETRY,
+            crate::amqp::ROUTING_KEY_WEATHER,
+            lapin::options::BasicPublishOptions::default(),
+            &msg,
+            lapin::BasicProperties::default(),
+        )
+        .await
+        .map_err(|e| {
+            tracking_warn!("(publish_grid) could not push weather grid to RabbitMQ: {e}.");
+        });
+}
+
+/// Publishes the grid to RabbitMQ at an interval, unless it is empty
+#[cfg(not(tarpaulin_include))]
+// no_coverage: Needs running backends to work.
+pub async fn publish_loop(monitor: WeatherMonitor, mq_channel: lapin::Channel, interval_ms: u32) {
+    tracking_info!(
+        "(publish_loop) publishing weather grid every {interval_ms} ms, settings {:?}.",
+        monitor.settings
+    );
+
+    let mut interval =
+        tokio::time::interval(std::time::Duration::from_millis(interval_ms.max(1) as u64));
+    loop {
+        interval.tick().await;
+        let grid = monitor.grid(Utc::now());
+        if grid.cells.is_empty() {
+            continue;
+        }
+
+        tracking_debug!(
+            "(publish_loop) publishing {} weather cells.",
+            grid.cells.len()
+        );
+        publish_grid(&mq_channel, &grid).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    const SETTINGS: WeatherSettings = WeatherSettings {
+        cell_size_meters: 1000.,
+        half_life_seconds: 60.,
+    };
+
+    fn time(seconds: i64) -> DateTime<Utc> {
+        Utc.timestamp_opt(seconds, 0).unwrap()
+    }
+
+    fn position(identifier: &str, latitude: f64) -> AircraftPosition {
+        AircraftPosition {
+            identifier: identifier.to_string(),
+            position: Position {
+                latitude,
+                longitude: 4.001,
+                altitude_meters: 100.,
+            },
+            timestamp_network: time(0),
+            timestamp_asset: None,
+        }
+    }
+
+    fn velocity(identifier: &str, vertical: f32, air: Option<f32>) -> AircraftVelocity {
+        AircraftVelocity {
+            identifier: identifier.to_string(),
+            velocity_horizontal_ground_mps: 20.,
+            velocity_horizontal_air_mps: air,
+            velocity_vertical_mps: vertical,
+            track_angle_degrees: 90.,
+            timestamp_network: time(0),
+            timestamp_asset: None,
+        }
+    }
+
+    #[test]
+    fn test_turbulence() {
+        let monitor = WeatherMonitor::new(SETTINGS);
+        monitor.update_position(&position("aircraft", 52.001), time(0));
+
+        // Too few vertical speeds for a variance
+        monitor.update_velocity(&velocity("aircraft", 2., None), time(0));
+        monitor.update_velocity(&velocity("aircraft", -2., None), time(1));
+        assert!(monitor.grid(time(1)).cells.is_empty());
+
+        monitor.update_velocity(&velocity("aircraft", 2., None), time(2));
+        let grid = monitor.grid(time(2));
+        assert_eq!(grid.cells.len(), 1);
+
+        let cell = &grid.cells[0];
+        assert!(cell.min_latitude <= 52.001 && 52.001 < cell.max_latitude);
+        assert!(cell.min_longitude <= 4.001 && 4.001 < cell.max_longitude);
+        assert!((cell.turbulence_mps.unwrap() - 8_f64.sqrt() * 2. / 3.).abs() < 1e-9);
+        assert_eq!(cell.wind_north_mps, None);
+    }
+
+    #[test]
+    fn test_wind() {
+        let monitor = WeatherMonitor::new(SETTINGS);
+        monitor.update_position(&position("aircraft", 52.001), time(0));
+        monitor.update_velocity(&velocity("aircraft", 0., Some(25.)), time(0));
+
+        // Flying east into a 5 m/s headwind
+        let cell = monitor.grid(time(0)).cells[0].clone();
+        assert!((cell.wind_east_mps.unwrap() + 5.).abs() < 1e-6);
+        assert!(cell.wind_north_mps.unwrap().abs() < 1e-6);
+        assert_eq!(cell.turbulence_mps, None);
+    }
+
+    #[test]
+    fn test_requires_recent_position() {
+        let monitor = WeatherMonitor::new(SETTINGS);
+        monitor.update_velocity(&velocity("unknown", 0., Some(25.)), time(0));
+        monitor.update_position(&position("aircraft", 52.001), time(0));
+        monitor.update_velocity(&velocity("aircraft", 0., Some(25.)), time(20));
+        assert!(monitor.grid(time(20)).cells.is_empty());
+    }
+
+    #[test]
+    fn test_decay() {
+        let monitor = WeatherMonitor::new(SETTINGS);
+        monitor.update_position(&position("aircraft", 52.001), time(0));
+        monitor.update_velocity(&velocity("aircraft", 0., Some(25.)), time(0));
+
+        let cell = monitor.grid(time(60)).cells[0].clone();
+        assert!((cell.wind_samples - 0.5).abs() < 1e-9);
+        // Decay doesn't change the mean
+        assert!((cell.wind_east_mps.unwrap() + 5.).abs() < 1e-6);
+
+        assert!(monitor.grid(time(600)).cells.is_empty());
+        assert!(monitor.samples.lock().unwrap().cells.is_empty());
+    }
+
+    #[test]
+    fn test_cells() {
+        let monitor = WeatherMonitor::new(SETTINGS);
+        for (identifier, latitude) in [("north", 52.05), ("south", 52.001)] {
+            monitor.update_position(&position(identifier, latitude), time(0));
+            monitor.update_velocity(&velocity(identifier, 0., Some(25.)), time(0));
+        }
+
+        let grid = monitor.grid(time(0));
+        assert_eq!(grid.cells.len(), 2);
+        assert!(grid.cells[0].max_latitude <= grid.cells[1].min_latitude);
+        assert!((grid.cell_size_degrees - 0.008993).abs() < 1e-6);
+    }
+}
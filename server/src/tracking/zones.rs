@@ -0,0 +1,614 @@
+//! Restricted zones
+//!
+//! Every accepted position is checked against the restricted (no-fly)
+//!  zones. An alert is published when an aircraft enters a zone, or when
+//!  its latest velocity would take it into a zone within the lookahead
+//!  time. Each aircraft is alerted once per zone, until it is no longer
+//!  inside or headed into the zone.
+//!
+//! Zones are loaded at startup from the GeoJSON file named by
+//!  `RESTRICTED_ZONES_FILE`, a FeatureCollection of Polygon and
+//!  MultiPolygon features. The optional `name`, `floor_meters` and
+//!  `ceiling_meters` properties name a zone and bound its altitudes.
+
+use super::events::{self, EventBus};
+use super::spatial::{polygon_contains, AltitudeBand};
+use super::EARTH_RADIUS_METERS;
+use crate::rest::api::rest_types::{AircraftStatePosition, TelemetrySource, ZoneIntrusion};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use svc_gis_client_grpc::prelude::types::*;
+
+/// Interval between predicted positions of an aircraft (s)
+const PREDICTION_STEP_SECONDS: u32 = 1;
+
+/// Velocities older than this are not used to predict positions (s)
+const VELOCITY_MAX_AGE_SECONDS: i64 = 10;
+
+/// Aircraft without updates for this long are forgotten (s)
+const AIRCRAFT_EXPIRE_SECONDS: i64 = 60;
+
+/// A restricted zone
+#[derive(Debug, Clone, PartialEq)]
+pub struct RestrictedZone {
+    /// Identifier of the zone
+    pub identifier: String,
+
+    /// Name of the zone
+    pub name: String,
+
+    /// Polygons of the zone, each an exterior ring followed by its holes,
+    ///  as (latitude, longitude) pairs (degrees)
+    pub polygons: Vec<Vec<Vec<(f64, f64)>>>,
+
+    /// Altitudes of the zone
+    pub altitude: AltitudeBand,
+}
+
+impl RestrictedZone {
+    /// If every ring of the zone has at least 3 vertices, and its
+    ///  altitudes are valid
+    pub fn is_valid(&self) -> bool {
+        !self.polygons.is_empty()
+            && self.polygons.iter().flatten().all(|ring| {
+                ring.len() >= 3
+                    && ring.iter().all(|(latitude, longitude)| {
+                        (-90. ..=90.).contains(latitude) && (-180. ..=180.).contains(longitude)
+                    })
+            })
+            && self.altitude.is_valid()
+    }
+
+    /// If a position is inside the zone
+    pub fn contains(&self, latitude: f64, longitude: f64, altitude_meters: f64) -> bool {
+        self.altitude.contains(altitude_meters)
+            && self.polygons.iter().any(|rings| {
+                let Some((exterior, holes)) = rings.split_first() else {
+                    return false;
+                };
+
+                polygon_contains(exterior, latitude, longitude)
+                    && !holes
+                        .iter()
+                        .any(|hole| polygon_contains(hole, latitude, longitude))
+            })
+    }
+}
+
+/// Errors loading restricted zones
+#[derive(Debug)]
+pub enum ZoneError {
+    /// The file could not be read
+    Read(std::io::Error),
+
+    /// The file is not a GeoJSON FeatureCollection of polygons
+    Parse(serde_json::Error),
+
+    /// A zone has a ring with too few vertices, or an inverted altitude range
+    Invalid(String),
+}
+
+impl std::fmt::Display for ZoneError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ZoneError::Read(e) => write!(f, "could not read file: {e}"),
+            ZoneError::Parse(e) => write!(f, "could not parse file: {e}"),
+            ZoneError::Invalid(identifier) => write!(f, "invalid zone {identifier}"),
+        }
+    }
+}
+
+/// A GeoJSON FeatureCollection
+#[derive(Debug, Deserialize)]
+struct FeatureCollection {
+    features: Vec<Feature>,
+}
+
+/// A GeoJSON Feature
+#[derive(Debug, Deserialize)]
+struct Feature {
+    id: Option<serde_json::Value>,
+    geometry: Geometry,
+    properties: Option<ZoneProperties>,
+}
+
+/// Geometries of restricted zones, with [longitude, latitude] positions
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type")]
+enum Geometry {
+    Polygon {
+        coordinates: Vec<Vec<Vec<f64>>>,
+    },
+    MultiPolygon {
+        coordinates: Vec<Vec<Vec<Vec<f64>>>>,
+    },
+}
+
+/// Properties of a restricted zone
+#[derive(Debug, Default, Deserialize)]
+struct ZoneProperties {
+    name: Option<String>,
+    floor_meters: Option<f64>,
+    ceiling_meters: Option<f64>,
+}
+
+/// Converts GeoJSON rings to rings of (latitude, longitude) pairs
+///
+/// Positions without a latitude and longitude are dropped, so that the
+///  zone fails validation if too few vertices remain.
+fn rings(coordinates: Vec<Vec<Vec<f64>>>) -> Vec<Vec<(f64, f64)>> {
+    coordinates
+        .into_iter()
+        .map(|ring| {
+            ring.into_iter()
+                .filter_map(|position| match position[..] {
+                    [longitude, latitude, ..] => Some((latitude, longitude)),
+                    _ => None,
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Parses a GeoJSON FeatureCollection of restricted zones
+fn parse_zones(text: &str) -> Result<Vec<RestrictedZone>, ZoneError> {
+    let collection: FeatureCollection = serde_json::from_str(text).map_err(ZoneError::Parse)?;
+    let zones: Vec<RestrictedZone> = collection
+        .features
+        .into_iter()
+        .enumerate()
+        .map(|(index, feature)| {
+            let identifier = match feature.id {
+                Some(serde_json::Value::String(id)) => id,
+                Some(id) => id.to_string(),
+                None => format!("zone-{index}"),
+            };
+
+            let properties = feature.properties.unwrap_or_default();
+            let polygons = match feature.geometry {
+                Geometry::Polygon { coordinates } => vec![rings(coordinates)],
+                Geometry::MultiPolygon { coordinates } => {
+                    coordinates.into_iter().map(rings).collect()
+                }
+            };
+
+            RestrictedZone {
+                name: properties.name.unwrap_or_else(|| identifier.clone()),
+                identifier,
+                polygons,
+                altitude: AltitudeBand {
+                    min_altitude_meters: properties.floor_meters,
+                    max_altitude_meters: properties.ceiling_meters,
+                },
+            }
+        })
+        .collect();
+
+    if let Some(zone) = zones.iter().find(|zone| !zone.is_valid()) {
+        return Err(ZoneError::Invalid(zone.identifier.clone()));
+    }
+
+    Ok(zones)
+}
+
+/// Loads restricted zones from a GeoJSON file
+pub fn load_zones(path: &str) -> Result<Vec<RestrictedZone>, ZoneError> {
+    let text = std::fs::read_to_string(path).map_err(ZoneError::Read)?;
+    parse_zones(&text)
+}
+
+/// An aircraft inside or headed into a restricted zone, published to RabbitMQ
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ZoneAlert {
+    /// Identifier of the aircraft
+    pub identifier: String,
+
+    /// The zone, and when the aircraft enters it
+    #[serde(flatten)]
+    pub intrusion: ZoneIntrusion,
+
+    /// Position of the aircraft
+    pub position: AircraftStatePosition,
+
+    /// Time of the position
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Whether an aircraft was alerted for being inside or headed into a zone
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Intrusion {
+    Inside,
+    Predicted,
+}
+
+/// Latest velocity of an aircraft, and the zones it was alerted for
+#[derive(Debug, Clone)]
+struct AircraftZones {
+    velocity: Option<AircraftVelocity>,
+    intrusions: HashMap<usize, Intrusion>,
+    updated: DateTime<Utc>,
+}
+
+impl AircraftZones {
+    fn new(updated: DateTime<Utc>) -> Self {
+        AircraftZones {
+            velocity: None,
+            intrusions: HashMap::new(),
+            updated,
+        }
+    }
+}
+
+/// Restricted zones, and the zones each aircraft was alerted for
+#[derive(Debug, Clone, Default)]
+pub struct ZoneMonitor {
+    zones: Arc<Vec<RestrictedZone>>,
+    lookahead_seconds: u32,
+    aircraft: Arc<Mutex<HashMap<String, AircraftZones>>>,
+}
+
+/// Forgets aircraft without updates for a while
+fn expire(aircraft: &mut HashMap<String, AircraftZones>, now: DateTime<Utc>) {
+    aircraft.retain(|_, zones| (now - zones.updated).num_seconds() < AIRCRAFT_EXPIRE_SECONDS);
+}
+
+/// Position of an aircraft after moving with a velocity for some time
+fn project(position: &Position, velocity: &AircraftVelocity, seconds: f64) -> Position {
+    let speed = velocity.velocity_horizontal_ground_mps as f64;
+    let track_angle = (velocity.track_angle_degrees as f64).to_radians();
+    let north = speed * track_angle.cos() * seconds;
+    let east = speed * track_angle.sin() * seconds;
+
+    Position {
+        latitude: position.latitude + (north / EARTH_RADIUS_METERS).to_degrees(),
+        longitude: position.longitude
+            + (east / (EARTH_RADIUS_METERS * position.latitude.to_radians().cos())).to_degrees(),
+        altitude_meters: position.altitude_meters + velocity.velocity_vertical_mps as f64 * seconds,
+    }
+}
+
+impl ZoneMonitor {
+    /// Creates a monitor of restricted zones, predicting entries up to
+    ///  `lookahead_seconds` ahead
+    pub fn new(zones: Vec<RestrictedZone>, lookahead_seconds: u32) -> Self {
+        ZoneMonitor {
+            zones: Arc::new(zones),
+            lookahead_seconds,
+            aircraft: Arc::default(),
+        }
+    }
+
+    /// Records the latest velocity of an aircraft
+    pub fn update_velocity(&self, velocity: &AircraftVelocity) {
+        if self.zones.is_empty() {
+            return;
+        }
+
+        let Ok(mut aircraft) = self.aircraft.lock() else {
+            tracking_error!("(update_velocity) could not lock restricted zones.");
+            return;
+        };
+
+        let now = Utc::now();
+        if !aircraft.contains_key(&velocity.identifier) {
+            expire(&mut aircraft, now);
+        }
+
+        let zones = aircraft
+            .entry(velocity.identifier.clone())
+            .or_insert_with(|| AircraftZones::new(now));
+
+        zones.velocity = Some(velocity.clone());
+        zones.updated = now;
+    }
+
+    /// Seconds until a position moving with a velocity enters each zone
+    ///  it is not already inside, if within the lookahead time
+    fn predict_entries(
+        &self,
+        position: &Position,
+        velocity: &AircraftVelocity,
+        inside: &[usize],
+    ) -> HashMap<usize, f64> {
+        let mut entries = HashMap::new();
+        for step in 1..=self.lookahead_seconds / PREDICTION_STEP_SECONDS {
+            let seconds = (step * PREDICTION_STEP_SECONDS) as f64;
+            let predicted = project(position, velocity, seconds);
+            for (index, zone) in self.zones.iter().enumerate() {
+                if inside.contains(&index) || entries.contains_key(&index) {
+                    continue;
+                }
+
+                if zone.contains(
+                    predicted.latitude,
+                    predicted.longitude,
+                    predicted.altitude_meters,
+                ) {
+                    entries.insert(index, seconds);
+                }
+            }
+        }
+
+        entries
+    }
+
+    /// Checks a position against the zones, returning alerts for zones
+    ///  the aircraft newly entered or is newly predicted to enter
+    pub fn check_position(&self, position: &AircraftPosition) -> Vec<ZoneAlert> {
+        if self.zones.is_empty() {
+            return vec![];
+        }
+
+        let Position {
+            latitude,
+            longitude,
+            altitude_meters,
+        } = position.position;
+
+        let timestamp = position
+            .timestamp_asset
+            .unwrap_or(position.timestamp_network);
+
+        let inside: Vec<usize> = self
+            .zones
+            .iter()
+            .enumerate()
+            .filter(|(_, zone)| zone.contains(latitude, longitude, altitude_meters))
+            .map(|(index, _)| index)
+            .collect();
+
+        let Ok(mut aircraft) = self.aircraft.lock() else {
+            tracking_error!("(check_position) could not lock restricted zones.");
+            return vec![];
+        };
+
+        let now = Utc::now();
+        if !aircraft.contains_key(&position.identifier) {
+            expire(&mut aircraft, now);
+        }
+
+        let zones = aircraft
+            .entry(position.identifier.clone())
+            .or_insert_with(|| AircraftZones::new(now));
+
+        zones.updated = now;
+
+        let entries = match &zones.velocity {
+            Some(velocity)
+                if (timestamp
+                    - velocity
+                        .timestamp_asset
+                        .unwrap_or(velocity.timestamp_network))
+                .num_seconds()
+                .abs()
+                    <= VELOCITY_MAX_AGE_SECONDS =>
+            {
+                self.predict_entries(&position.position, velocity, &inside)
+            }
+            _ => HashMap::new(),
+        };
+
+        let mut intrusions: HashMap<usize, (Intrusion, f64)> = entries
+            .into_iter()
+            .map(|(index, seconds)| (index, (Intrusion::Predicted, seconds)))
+            .collect();
+        intrusions.extend(inside.iter().map(|index| (*index, (Intrusion::Inside, 0.))));
+
+        let alerted = std::mem::take(&mut zones.intrusions);
+        zones.intrusions = intrusions
+            .iter()
+            .map(|(index, (intrusion, _))| (*index, *intrusion))
+            .collect();
+
+        let state = AircraftStatePosition::from(position);
+        intrusions
+            .into_iter()
+            .filter(|(index, (intrusion, _))| alerted.get(index) != Some(intrusion))
+            .filter_map(|(index, (intrusion, seconds_to_entry))| {
+                let zone = self.zones.get(index)?;
+                Some(ZoneAlert {
+                    identifier: position.identifier.clone(),
+                    intrusion: ZoneIntrusion {
+                        zone_id: zone.identifier.clone(),
+                        zone_name: zone.name.clone(),
+                        predicted: intrusion == Intrusion::Predicted,
+                        seconds_to_entry,
+                    },
+                    position: state.clone(),
+                    timestamp,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Publishes restricted zone alerts to RabbitMQ and live subscribers
+pub async fn publish_alerts(
+    mq_channel: &lapin::Channel,
+    event_bus: &EventBus,
+    source: TelemetrySource,
+    alerts: Vec<ZoneAlert>,
+) {
+    for alert in alerts {
+        tracking_warn!(
+            "(publish_alerts) {} {} restricted zone {} ({:.0} s).",
+            alert.identifier,
+            if alert.intrusion.predicted {
+                "heading into"
+            } else {
+                "inside"
+            },
+            alert.intrusion.zone_id,
+            alert.intrusion.seconds_to_entry
+        );
+
+        event_bus.publish(events::intrusion_event(&alert, source));
+
+        let Ok(msg) = serde_json::to_vec(&alert) else {
+            tracking_warn!("(publish_alerts) could not serialize zone alert.");
+            continue;
+        };
+
+        let _ = mq_channel
+            .basic_publish(
+                crate::amqp::EXCHANGE_NAME_TELEMETRY,
+                crate::amqp::ROUTING_KEY_ZONE_INTRUSION,
+                lapin::options::BasicPublishOptions::default(),
+                &msg,
+                lapin::BasicProperties::default(),
+            )
+            .await
+            .map_err(|e| {
+                tracking_warn!("(publish_alerts) could not push zone alert to RabbitMQ: {e}.");
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ZONES: &str = r#"{
+        "type": "FeatureCollection",
+        "features": [
+            {
+                "type": "Feature",
+                "id": "airport",
+                "properties": {"name": "Airport", "ceiling_meters": 500},
+                "geometry": {
+                    "type": "Polygon",
+                    "coordinates": [
+                        [[4, 52], [4.1, 52], [4.1, 52.1], [4, 52.1], [4, 52]],
+                        [[4.04, 52.04], [4.06, 52.04], [4.06, 52.06], [4.04, 52.06], [4.04, 52.04]]
+                    ]
+                }
+            },
+            {
+                "type": "Feature",
+                "id": 7,
+                "properties": null,
+                "geometry": {
+                    "type": "MultiPolygon",
+                    "coordinates": [
+                        [[[5, 52], [5.1, 52], [5.1, 52.1], [5, 52]]],
+                        [[[6, 52], [6.1, 52], [6.1, 52.1], [6, 52]]]
+                    ]
+                }
+            }
+        ]
+    }"#;
+
+    fn position(latitude: f64, longitude: f64, altitude_meters: f64) -> AircraftPosition {
+        AircraftPosition {
+            identifier: "aircraft".to_string(),
+            position: Position {
+                latitude,
+                longitude,
+                altitude_meters,
+            },
+            timestamp_network: Utc::now(),
+            timestamp_asset: None,
+        }
+    }
+
+    fn velocity(speed: f32, track_angle_degrees: f32) -> AircraftVelocity {
+        AircraftVelocity {
+            identifier: "aircraft".to_string(),
+            velocity_horizontal_ground_mps: speed,
+            velocity_horizontal_air_mps: None,
+            velocity_vertical_mps: 0.,
+            track_angle_degrees,
+            timestamp_network: Utc::now(),
+            timestamp_asset: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_zones() {
+        let zones = parse_zones(ZONES).unwrap();
+        assert_eq!(zones.len(), 2);
+        assert_eq!(zones[0].identifier, "airport");
+        assert_eq!(zones[0].name, "Airport");
+        assert_eq!(zones[0].polygons[0][0][1], (52., 4.1));
+        assert_eq!(zones[0].altitude.max_altitude_meters, Some(500.));
+        assert_eq!(zones[1].identifier, "7");
+        assert_eq!(zones[1].polygons.len(), 2);
+
+        let inverted = ZONES.replace(
+            r#""ceiling_meters": 500"#,
+            r#""floor_meters": 600, "ceiling_meters": 500"#,
+        );
+        assert!(matches!(parse_zones(&inverted), Err(ZoneError::Invalid(_))));
+
+        let point = r#"{"features": [{"geometry": {"type": "Point", "coordinates": [4, 52]}}]}"#;
+        assert!(matches!(parse_zones(point), Err(ZoneError::Parse(_))));
+    }
+
+    #[test]
+    fn test_zone_contains() {
+        let zones = parse_zones(ZONES).unwrap();
+        assert!(zones[0].contains(52.02, 4.02, 100.));
+        assert!(!zones[0].contains(52.02, 4.02, 600.));
+
+        // Inside the hole
+        assert!(!zones[0].contains(52.05, 4.05, 100.));
+
+        assert!(zones[1].contains(52.02, 6.05, 100.));
+        assert!(!zones[1].contains(52.02, 5.5, 100.));
+    }
+
+    #[test]
+    fn test_check_position() {
+        let monitor = ZoneMonitor::new(parse_zones(ZONES).unwrap(), 30);
+
+        // 1.1 km south of the airport, without a velocity
+        assert!(monitor
+            .check_position(&position(51.99, 4.05, 100.))
+            .is_empty());
+
+        // Heading north at 50 m/s, entering within about 22 s
+        monitor.update_velocity(&velocity(50., 0.));
+        let alerts = monitor.check_position(&position(51.99, 4.05, 100.));
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].intrusion.zone_id, "airport");
+        assert!(alerts[0].intrusion.predicted);
+        assert!((20. ..=25.).contains(&alerts[0].intrusion.seconds_to_entry));
+
+        // Already alerted
+        assert!(monitor
+            .check_position(&position(51.995, 4.05, 100.))
+            .is_empty());
+
+        let alerts = monitor.check_position(&position(52.01, 4.05, 100.));
+        assert_eq!(alerts.len(), 1);
+        assert!(!alerts[0].intrusion.predicted);
+        assert_eq!(alerts[0].intrusion.seconds_to_entry, 0.);
+
+        // Turned away, and left the zone
+        monitor.update_velocity(&velocity(50., 180.));
+        assert!(monitor
+            .check_position(&position(51.9, 4.05, 100.))
+            .is_empty());
+        assert!(monitor.aircraft.lock().unwrap()["aircraft"]
+            .intrusions
+            .is_empty());
+    }
+
+    #[test]
+    fn test_project() {
+        let start = Position {
+            latitude: 52.,
+            longitude: 4.,
+            altitude_meters: 100.,
+        };
+
+        let mut climbing = velocity(100., 90.);
+        climbing.velocity_vertical_mps = 2.;
+        let end = project(&start, &climbing, 10.);
+        assert!((end.latitude - 52.).abs() < 1e-9);
+        assert!(end.longitude > 4.01 && end.longitude < 4.02);
+        assert!((end.altitude_meters - 120.).abs() < 1e-3);
+    }
+}